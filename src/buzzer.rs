@@ -0,0 +1,269 @@
+use crate::{
+    constants::{AUDIO_PATTERN_SIZE, TIMER_HZ},
+    cpu::CpuOutput,
+};
+use serde::{Deserialize, Serialize};
+use std::{f32::consts::TAU, fmt, str::FromStr};
+
+const PATTERN_BITS: f32 = (AUDIO_PATTERN_SIZE * 8) as f32;
+
+/// The shape of the tone played without an XO-CHIP audio pattern.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Waveform {
+    #[default]
+    Square,
+    Triangle,
+    Sine,
+    /// White noise from a 15 bit LFSR, changing level at the buzzer's frequency.
+    Noise,
+}
+
+impl FromStr for Waveform {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "square" => Ok(Self::Square),
+            "triangle" => Ok(Self::Triangle),
+            "sine" => Ok(Self::Sine),
+            "noise" => Ok(Self::Noise),
+            _ => Err(format!(
+                "unknown waveform {value}, expected square, triangle, sine or noise"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Waveform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Square => write!(f, "square"),
+            Self::Triangle => write!(f, "triangle"),
+            Self::Sine => write!(f, "sine"),
+            Self::Noise => write!(f, "noise"),
+        }
+    }
+}
+
+/// How the buzzer sounds. Fields missing from a settings file keep their defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BuzzerSettings {
+    /// The tone's frequency in Hz. XO-CHIP audio patterns play at their own pitch instead.
+    pub frequency: f32,
+    /// From 0 for silent to 1 for full scale.
+    pub volume: f32,
+    pub waveform: Waveform,
+    pub muted: bool,
+    /// How long the sound takes to fade in when it starts, in milliseconds.
+    pub attack_ms: f32,
+    /// How long the sound takes to fade out when it stops, in milliseconds.
+    pub release_ms: f32,
+}
+
+impl Default for BuzzerSettings {
+    fn default() -> Self {
+        Self {
+            frequency: 440.0,
+            volume: 0.25,
+            waveform: Waveform::Square,
+            muted: false,
+            attack_ms: 5.0,
+            release_ms: 5.0,
+        }
+    }
+}
+
+impl BuzzerSettings {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// These settings with the fields set in `json` replaced, such as a rom's own settings over
+    /// the global ones.
+    pub fn with_overrides(&self, json: &str) -> serde_json::Result<Self> {
+        let mut settings = serde_json::to_value(self)?;
+        let overrides: serde_json::Map<String, serde_json::Value> = serde_json::from_str(json)?;
+
+        if let Some(settings) = settings.as_object_mut() {
+            settings.extend(overrides);
+        }
+
+        serde_json::from_value(settings)
+    }
+}
+
+/// The sound the CPU makes while its sound timer runs, as samples between -1 and 1.
+///
+/// The frontend's audio device pulls samples from it as they're played, and a run without one can
+/// render them a frame at a time, which gives the same samples for the same run.
+#[derive(Debug, Clone)]
+pub struct Buzzer {
+    sample_rate: u32,
+    settings: BuzzerSettings,
+    phase_inc: f32,
+    phase: f32,
+    pattern: Option<[u8; AUDIO_PATTERN_SIZE]>,
+    pitch: u8,
+    beeping: bool,
+    /// The envelope, rising to 1 over the attack and falling to 0 over the release.
+    gain: f32,
+    noise: u16,
+    /// The number of frames rendered so far, so 60 Hz frames add up to the sample rate.
+    frames: u64,
+}
+
+impl Buzzer {
+    pub fn new(sample_rate: u32) -> Self {
+        let mut buzzer = Self {
+            sample_rate,
+            settings: BuzzerSettings::default(),
+            phase_inc: 0.0,
+            phase: 0.0,
+            pattern: None,
+            pitch: 0,
+            beeping: false,
+            gain: 0.0,
+            noise: 1,
+            frames: 0,
+        };
+
+        buzzer.set_pattern(None, 0);
+        buzzer
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn settings(&self) -> &BuzzerSettings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: BuzzerSettings) {
+        self.settings = settings;
+        self.set_pattern(self.pattern, self.pitch);
+    }
+
+    /// The rate in bits per second that an XO-CHIP audio pattern plays back at for a given pitch.
+    fn pattern_rate(pitch: u8) -> f32 {
+        4000.0 * 2.0_f32.powf((pitch as f32 - 64.0) / 48.0)
+    }
+
+    /// Play an XO-CHIP audio pattern at the given pitch instead of the tone.
+    pub fn set_pattern(&mut self, pattern: Option<[u8; AUDIO_PATTERN_SIZE]>, pitch: u8) {
+        self.pattern = pattern;
+        self.pitch = pitch;
+
+        self.phase_inc = match pattern {
+            Some(_) => Self::pattern_rate(pitch) / PATTERN_BITS / self.sample_rate as f32,
+            None => self.settings.frequency / self.sample_rate as f32,
+        };
+    }
+
+    /// Start or stop the sound, fading over the attack or release. Once faded out the buzzer is
+    /// silent and the wave holds its phase.
+    pub fn set_beeping(&mut self, beeping: bool) {
+        self.beeping = beeping;
+    }
+
+    pub fn is_beeping(&self) -> bool {
+        self.beeping
+    }
+
+    /// Follow the sound timer and audio pattern of a frame the CPU just ran.
+    pub fn update(&mut self, output: &CpuOutput) {
+        if output.audio_pattern.copied() != self.pattern || output.pitch != self.pitch {
+            self.set_pattern(output.audio_pattern.copied(), output.pitch);
+        }
+
+        self.set_beeping(output.should_beep);
+    }
+
+    /// How far the envelope moves in a sample over a fade of `ms` milliseconds.
+    fn envelope_step(&self, ms: f32) -> f32 {
+        let samples = ms * self.sample_rate as f32 / 1000.0;
+
+        if samples < 1.0 {
+            1.0
+        } else {
+            1.0 / samples
+        }
+    }
+
+    /// Fill `out` with the next samples.
+    pub fn fill(&mut self, out: &mut [f32]) {
+        let sounding = self.beeping && !self.settings.muted;
+        let attack = self.envelope_step(self.settings.attack_ms);
+        let release = self.envelope_step(self.settings.release_ms);
+
+        for x in out.iter_mut() {
+            self.gain = if sounding {
+                (self.gain + attack).min(1.0)
+            } else {
+                (self.gain - release).max(0.0)
+            };
+
+            if self.gain == 0.0 {
+                *x = 0.0;
+                continue;
+            }
+
+            *x = self.wave() * self.gain * self.settings.volume;
+
+            let phase = self.phase + self.phase_inc;
+            self.phase = phase % 1.0;
+
+            if phase >= 1.0 {
+                /* a Galois LFSR with taps at bits 15 and 14 */
+                let bit = self.noise & 1;
+                self.noise >>= 1;
+
+                if bit == 1 {
+                    self.noise ^= 0x6000;
+                }
+            }
+        }
+    }
+
+    /// The wave at the current phase, from -1 to 1.
+    fn wave(&self) -> f32 {
+        let high = match (self.pattern, self.settings.waveform) {
+            /* play the 128-bit pattern, most significant bit first */
+            (Some(pattern), _) => {
+                let bit = (self.phase * PATTERN_BITS) as usize;
+                (pattern[bit / 8] >> (7 - bit % 8)) & 1 == 1
+            }
+            (None, Waveform::Square) => self.phase <= 0.5,
+            (None, Waveform::Triangle) => return 4.0 * (self.phase - 0.5).abs() - 1.0,
+            (None, Waveform::Sine) => return (self.phase * TAU).sin(),
+            (None, Waveform::Noise) => self.noise & 1 == 1,
+        };
+
+        if high {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+
+    /// The samples for the next 60th of a second, for rendering a run without an audio device.
+    pub fn render_frame(&mut self) -> Vec<f32> {
+        let start = self.frames as u128 * self.sample_rate as u128 / TIMER_HZ;
+        self.frames += 1;
+        let end = self.frames as u128 * self.sample_rate as u128 / TIMER_HZ;
+
+        let mut samples = vec![0.0; (end - start) as usize];
+        self.fill(&mut samples);
+        samples
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/buzzer/buzzer_tests.rs"]
+mod buzzer_tests;
@@ -0,0 +1,146 @@
+use crate::{constants::RAM_SIZE, cpu::Cpu};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A byte a cheat can watch or change: an address in ram or one of the V registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Location {
+    Ram(usize),
+    Register(usize),
+}
+
+impl Location {
+    /// Every location a search starts from, the registers first.
+    fn all() -> impl Iterator<Item = Self> {
+        (0..16)
+            .map(Self::Register)
+            .chain((0..RAM_SIZE).map(Self::Ram))
+    }
+
+    pub fn read(self, cpu: &Cpu) -> u8 {
+        match self {
+            Self::Ram(addr) => cpu.ram().get(addr).copied().unwrap_or_default(),
+            Self::Register(x) => cpu.registers().get(x).copied().unwrap_or_default(),
+        }
+    }
+
+    pub fn write(self, cpu: &mut Cpu, value: u8) {
+        let byte = match self {
+            Self::Ram(addr) => cpu.ram_mut().get_mut(addr),
+            Self::Register(x) => cpu.registers_mut().get_mut(x),
+        };
+
+        if let Some(byte) = byte {
+            *byte = value;
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ram(addr) => write!(f, "{addr:04X}"),
+            Self::Register(x) => write!(f, "V{x:X}"),
+        }
+    }
+}
+
+/// How a search narrows its candidates, comparing each with its value at the last search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchFilter {
+    Changed,
+    Unchanged,
+    Equal(u8),
+}
+
+/// A byte still in the running, with the value it had at the last search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candidate {
+    pub location: Location,
+    pub value: u8,
+}
+
+/// A classic narrowing search: start with every byte, then keep only those that changed, stayed
+/// the same or hold a given value, frame after frame, until the one you're after is left.
+#[derive(Debug, Clone)]
+pub struct CheatSearch {
+    candidates: Vec<Candidate>,
+    searches: usize,
+}
+
+impl CheatSearch {
+    /// Start a search from the current values of ram and the registers.
+    pub fn new(cpu: &Cpu) -> Self {
+        Self {
+            candidates: Location::all()
+                .map(|location| Candidate {
+                    location,
+                    value: location.read(cpu),
+                })
+                .collect(),
+            searches: 0,
+        }
+    }
+
+    /// Drop the candidates that don't match, remembering the current values of the rest.
+    pub fn filter(&mut self, cpu: &Cpu, filter: SearchFilter) {
+        self.candidates.retain_mut(|candidate| {
+            let value = candidate.location.read(cpu);
+            let keep = match filter {
+                SearchFilter::Changed => value != candidate.value,
+                SearchFilter::Unchanged => value == candidate.value,
+                SearchFilter::Equal(expected) => value == expected,
+            };
+
+            candidate.value = value;
+            keep
+        });
+
+        self.searches += 1;
+    }
+
+    pub fn candidates(&self) -> &[Candidate] {
+        &self.candidates
+    }
+
+    /// How many times the search has been narrowed.
+    pub fn searches(&self) -> usize {
+        self.searches
+    }
+}
+
+/// A named location, either frozen to a value every frame or left alone to be poked by hand.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cheat {
+    pub name: String,
+    pub location: Location,
+    pub value: u8,
+    pub frozen: bool,
+}
+
+/// The cheats for one ROM, saved as JSON by its title.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheatList {
+    pub cheats: Vec<Cheat>,
+}
+
+impl CheatList {
+    /// Write every frozen cheat's value, once a frame.
+    pub fn apply(&self, cpu: &mut Cpu) {
+        for cheat in self.cheats.iter().filter(|cheat| cheat.frozen) {
+            cheat.location.write(cpu, cheat.value);
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/cheat/cheat_tests.rs"]
+mod cheat_tests;
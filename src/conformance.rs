@@ -0,0 +1,198 @@
+use crate::{
+    constants::{HIRES_VRAM_HEIGHT, HIRES_VRAM_WIDTH, VRAM_HEIGHT, VRAM_WIDTH},
+    cpu::Cpu,
+    fault::CpuFault,
+    quirks::Quirks,
+    random::{Random, RandomMode},
+};
+use std::{fmt, path::Path};
+
+/// Set this environment variable to write the screens a run produces as its golden images,
+/// instead of comparing against them.
+pub const UPDATE_GOLDEN_VAR: &str = "UPDATE_GOLDEN";
+
+/// A key held down from `frame` for `frames` frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyPress {
+    pub key: usize,
+    pub frame: usize,
+    pub frames: usize,
+}
+
+/// A whole ROM run without a display for a fixed number of frames, pressing keys on cue.
+///
+/// The run is seeded and frame locked, so the screen it ends on is the same every time.
+#[derive(Debug, Clone)]
+pub struct ConformanceRun {
+    pub frames: usize,
+    pub instructions_per_frame: usize,
+    pub quirks: Quirks,
+    pub seed: u64,
+    pub presses: Vec<KeyPress>,
+}
+
+impl Default for ConformanceRun {
+    fn default() -> Self {
+        Self {
+            frames: 60,
+            instructions_per_frame: 15,
+            quirks: Quirks::default(),
+            seed: 0,
+            presses: Vec::new(),
+        }
+    }
+}
+
+impl ConformanceRun {
+    /// The keypad during a frame.
+    pub fn keypad(&self, frame: usize) -> [bool; 16] {
+        let mut keypad = [false; 16];
+
+        for press in &self.presses {
+            if (press.frame..press.frame + press.frames).contains(&frame) {
+                keypad[press.key] = true;
+            }
+        }
+
+        keypad
+    }
+
+    /// Run `rom` for every frame, stopping early if it exits.
+    pub fn run(&self, rom: &[u8]) -> Result<Cpu, CpuFault> {
+        let mut cpu = Cpu::new();
+        cpu.set_quirks(self.quirks);
+        cpu.load(rom)?;
+        cpu.set_random(Random::new(self.seed, RandomMode::Modern));
+
+        for frame in 0..self.frames {
+            if cpu
+                .run_frame(self.keypad(frame), self.instructions_per_frame)?
+                .should_exit
+            {
+                break;
+            }
+        }
+
+        Ok(cpu)
+    }
+}
+
+/// The screen as text, a row per line: `.` for an unlit pixel, `#` for one lit in the first
+/// plane, and `2` or `3` for XO-CHIP pixels lit in the second or both planes.
+///
+/// Only the low resolution corner is shown unless the CPU is in high resolution mode.
+pub fn screen(cpu: &Cpu) -> String {
+    let (width, height) = if cpu.is_hires() {
+        (HIRES_VRAM_WIDTH, HIRES_VRAM_HEIGHT)
+    } else {
+        (VRAM_WIDTH, VRAM_HEIGHT)
+    };
+
+    cpu.vram()[..height]
+        .iter()
+        .map(|row| {
+            let mut line: String = row[..width]
+                .iter()
+                .map(|&pixel| match pixel {
+                    0 => '.',
+                    1 => '#',
+                    2 => '2',
+                    _ => '3',
+                })
+                .collect();
+
+            line.push('\n');
+            line
+        })
+        .collect()
+}
+
+/// Compare a screen with the golden image at `path`.
+///
+/// With [`UPDATE_GOLDEN_VAR`] set, the screen is written to `path` instead.
+pub fn check_golden(screen: &str, path: &Path) -> Result<(), ConformanceError> {
+    if std::env::var_os(UPDATE_GOLDEN_VAR).is_some() {
+        return std::fs::write(path, screen)
+            .map_err(|error| ConformanceError::Golden(format!("{}: {error}", path.display())));
+    }
+
+    let golden = std::fs::read_to_string(path)
+        .map_err(|error| ConformanceError::Golden(format!("{}: {error}", path.display())))?;
+
+    compare(screen, &golden)
+}
+
+/// Compare two screens drawn by [`screen`], pointing out the rows that differ.
+pub fn compare(actual: &str, golden: &str) -> Result<(), ConformanceError> {
+    let actual: Vec<&str> = actual.lines().collect();
+    let golden: Vec<&str> = golden.lines().collect();
+
+    if actual.len() != golden.len()
+        || actual.first().map(|row| row.len()) != golden.first().map(|row| row.len())
+    {
+        return Err(ConformanceError::SizeMismatch {
+            found: (actual.first().map_or(0, |row| row.len()), actual.len()),
+            expected: (golden.first().map_or(0, |row| row.len()), golden.len()),
+        });
+    }
+
+    let mut pixels = 0;
+    let mut diff = String::new();
+
+    for (y, (actual_row, golden_row)) in actual.iter().zip(&golden).enumerate() {
+        let differences = actual_row
+            .chars()
+            .zip(golden_row.chars())
+            .filter(|(a, g)| a != g)
+            .count();
+
+        if differences > 0 {
+            pixels += differences;
+            diff.push_str(&format!(
+                "{y:>2} expected {golden_row}\n{y:>2} found    {actual_row}\n"
+            ));
+        }
+    }
+
+    if pixels > 0 {
+        return Err(ConformanceError::Mismatch { pixels, diff });
+    }
+
+    Ok(())
+}
+
+/// An error raised when a run can't be checked or doesn't end on its golden image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConformanceError {
+    /// The golden image could not be read or written.
+    Golden(String),
+    /// The screen is a different resolution from the golden image.
+    SizeMismatch {
+        found: (usize, usize),
+        expected: (usize, usize),
+    },
+    /// Some pixels differ, with the rows they're on.
+    Mismatch { pixels: usize, diff: String },
+}
+
+impl fmt::Display for ConformanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Golden(message) => write!(f, "golden image: {message}"),
+            Self::SizeMismatch { found, expected } => write!(
+                f,
+                "screen is {}x{}, expected {}x{}",
+                found.0, found.1, expected.0, expected.1
+            ),
+            Self::Mismatch { pixels, diff } => {
+                write!(f, "{pixels} pixels differ from the golden image\n{diff}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConformanceError {}
+
+#[cfg(test)]
+#[path = "../tests/conformance/conformance_tests.rs"]
+mod conformance_tests;
@@ -1,9 +1,31 @@
-pub const RAM_SIZE: usize = 4096;
+pub const RAM_SIZE: usize = 65536;
+/* the ram of every platform but XO-CHIP */
+pub const CHIP8_RAM_SIZE: usize = 4096;
 pub const OPCODE_SIZE: usize = 2;
+pub const PROGRAM_START: usize = 0x200;
 pub const TICKRATE: u128 = 1000;
+pub const TIMER_HZ: u128 = 60;
+pub const TIMER_INTERVAL_NS: u128 = 1e9 as u128 / TIMER_HZ;
+/* the longest stall Cpu::process catches up on, six frames */
+pub const MAX_CATCH_UP_NS: u128 = TIMER_INTERVAL_NS * 6;
+
+pub const FAST_FORWARD_SPEED: f64 = 4.0;
+pub const SLOW_MOTION_SPEED: f64 = 0.25;
+
+pub const REWIND_SECONDS: usize = 30;
+pub const REWIND_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+pub const TRACE_MAX_BYTES: u64 = 256 * 1024 * 1024;
+
+pub const MOVIE_CHECKPOINT_FRAMES: usize = 60;
+
+/* GIF pixels per high resolution pixel, the size the game is shown at */
+pub const GIF_SCALE: usize = 5;
 
 pub const VRAM_WIDTH: usize = 64;
 pub const VRAM_HEIGHT: usize = 32;
+pub const HIRES_VRAM_WIDTH: usize = 128;
+pub const HIRES_VRAM_HEIGHT: usize = 64;
 
 #[cfg(target_os = "windows")]
 pub const SPRITE_SCALE: u32 = 10;
@@ -16,10 +38,23 @@ pub const SPRITE_SCALE: u32 = 20;
 
 pub const DISPLAY_WIDTH: u32 = 640;
 pub const DISPLAY_HEIGHT: u32 = 320;
+pub const DEBUGGER_WIDTH: u32 = 400;
+pub const DEBUGGER_HEIGHT: u32 = 480;
+pub const MEMORY_VIEW_WIDTH: u32 = 600;
 
 pub const CHIP8_ARCHIVE_RAW_URL: &str =
     "https://raw.githubusercontent.com/JohnEarnest/chip8Archive";
 pub const CHIP8_ARCHIVE_URL: &str = "https://github.com/JohnEarnest/chip8Archive";
+/* bumped whenever the cached rom list would differ, such as when more platforms are kept */
+pub const ROM_LIST_VERSION: u32 = 2;
+
+pub const AUDIO_PATTERN_SIZE: usize = 16;
+pub const DEFAULT_PITCH: u8 = 64;
+pub const SAMPLE_RATE: u32 = 44100;
+pub const VOLUME_STEP: f32 = 0.05;
+
+pub const FONT_ADDR: usize = 0x00;
+pub const BIG_FONT_ADDR: usize = 0x50;
 
 pub const FONT: [u8; 80] = [
     0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
@@ -39,3 +74,22 @@ pub const FONT: [u8; 80] = [
     0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
     0xF0, 0x80, 0xF0, 0x80, 0x80, // F
 ];
+
+pub const BIG_FONT: [u8; 160] = [
+    0x3C, 0x7E, 0xE7, 0xC3, 0xC3, 0xC3, 0xC3, 0xE7, 0x7E, 0x3C, // 0
+    0x18, 0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3C, // 1
+    0x3E, 0x7F, 0xC3, 0x06, 0x0C, 0x18, 0x30, 0x60, 0xFF, 0xFF, // 2
+    0x3C, 0x7E, 0xC3, 0x03, 0x0E, 0x0E, 0x03, 0xC3, 0x7E, 0x3C, // 3
+    0x06, 0x0E, 0x1E, 0x36, 0x66, 0xC6, 0xFF, 0xFF, 0x06, 0x06, // 4
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFC, 0xFE, 0x03, 0xC3, 0x7E, 0x3C, // 5
+    0x3E, 0x7C, 0xC0, 0xC0, 0xFC, 0xFE, 0xC3, 0xC3, 0x7E, 0x3C, // 6
+    0xFF, 0xFF, 0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x60, 0x60, // 7
+    0x3C, 0x7E, 0xC3, 0xC3, 0x7E, 0x7E, 0xC3, 0xC3, 0x7E, 0x3C, // 8
+    0x3C, 0x7E, 0xC3, 0xC3, 0x7F, 0x3F, 0x03, 0x03, 0x3E, 0x7C, // 9
+    0x7E, 0xFF, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xC3, // A
+    0xFC, 0xFE, 0xC3, 0xC3, 0xFE, 0xFE, 0xC3, 0xC3, 0xFE, 0xFC, // B
+    0x3C, 0xFF, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0xFF, 0x3C, // C
+    0xFC, 0xFE, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFE, 0xFC, // D
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // E
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xC0, 0xC0, // F
+];
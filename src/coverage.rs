@@ -0,0 +1,168 @@
+use crate::{
+    constants::{AUDIO_PATTERN_SIZE, OPCODE_SIZE, PROGRAM_START, RAM_SIZE},
+    disasm::{self, LineKind},
+    instruction::Instruction,
+};
+use std::{fmt::Write, ops::Range};
+
+const EXECUTED: u8 = 0b001;
+const READ: u8 = 0b010;
+const WRITTEN: u8 = 0b100;
+
+/// Which bytes of ram were executed, read as data or written, collected by the [`Cpu`] while set
+/// with [`Cpu::set_coverage`].
+///
+/// Reads are sprite data for `DRW`, `LD Vx, [I]` and audio patterns. Writes are `LD B, Vx` and
+/// `LD [I], Vx`.
+///
+/// [`Cpu`]: crate::cpu::Cpu
+/// [`Cpu::set_coverage`]: crate::cpu::Cpu::set_coverage
+#[derive(Debug, Clone)]
+pub struct Coverage {
+    flags: Vec<u8>,
+}
+
+impl Default for Coverage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Self {
+            flags: vec![0; RAM_SIZE],
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.flags.fill(0);
+    }
+
+    /// Mark the bytes an instruction at `pc` was made of and the bytes it read or wrote.
+    ///
+    /// `i` is the index register as it was before the instruction ran and `planes` the number of
+    /// XO-CHIP bitplanes selected for drawing.
+    pub fn record(&mut self, pc: usize, i: usize, instruction: Instruction, planes: usize) {
+        let size = match instruction {
+            Instruction::LoadILong => 2 * OPCODE_SIZE,
+            _ => OPCODE_SIZE,
+        };
+
+        self.mark(pc..pc + size, EXECUTED);
+
+        match instruction {
+            Instruction::Draw(_, _, 0) => self.mark(i..i + 32 * planes, READ),
+            Instruction::Draw(_, _, n) => self.mark(i..i + n * planes, READ),
+            Instruction::LoadRegisters(x) => self.mark(i..i + x + 1, READ),
+            Instruction::Audio => self.mark(i..i + AUDIO_PATTERN_SIZE, READ),
+            Instruction::StoreBcd(_) => self.mark(i..i + 3, WRITTEN),
+            Instruction::StoreRegisters(x) => self.mark(i..i + x + 1, WRITTEN),
+            Instruction::Save(x, y) => self.mark(i..i + x.abs_diff(y) + 1, WRITTEN),
+            Instruction::Load(x, y) => self.mark(i..i + x.abs_diff(y) + 1, READ),
+            _ => {}
+        }
+    }
+
+    fn mark(&mut self, range: Range<usize>, flag: u8) {
+        let end = range.end.min(RAM_SIZE);
+
+        for flags in &mut self.flags[range.start.min(end)..end] {
+            *flags |= flag;
+        }
+    }
+
+    fn has(&self, addr: usize, flag: u8) -> bool {
+        self.flags.get(addr).is_some_and(|flags| flags & flag != 0)
+    }
+
+    pub fn is_executed(&self, addr: usize) -> bool {
+        self.has(addr, EXECUTED)
+    }
+
+    pub fn is_read(&self, addr: usize) -> bool {
+        self.has(addr, READ)
+    }
+
+    pub fn is_written(&self, addr: usize) -> bool {
+        self.has(addr, WRITTEN)
+    }
+
+    /// The disassembly of a ROM with each line marked `X` if executed, `R` if read and `W` if
+    /// written, after a summary of how much of the ROM was touched.
+    ///
+    /// Code the disassembler can reach that never ran is marked `!`, which is where dead code and
+    /// untested branches show up.
+    pub fn report(&self, rom: &[u8]) -> String {
+        let rom_range = PROGRAM_START..(PROGRAM_START + rom.len()).min(RAM_SIZE);
+        let count = |flag: u8| {
+            rom_range
+                .clone()
+                .filter(|&addr| self.has(addr, flag))
+                .count()
+        };
+        let untouched = rom_range
+            .clone()
+            .filter(|&addr| self.flags[addr] == 0)
+            .count();
+
+        let disassembly = disasm::disassemble(rom);
+        let unexecuted = disassembly
+            .lines
+            .iter()
+            .filter(|line| line.kind == LineKind::Code && !self.is_executed(line.addr))
+            .count();
+
+        let percent = |bytes: usize| bytes as f64 * 100.0 / rom.len().max(1) as f64;
+        let mut report = String::new();
+
+        let _ = writeln!(report, "; {} bytes", rom.len());
+
+        for (name, bytes) in [
+            ("executed", count(EXECUTED)),
+            ("read", count(READ)),
+            ("written", count(WRITTEN)),
+            ("untouched", untouched),
+        ] {
+            let _ = writeln!(report, "; {bytes:>5} {name} ({:.1}%)", percent(bytes));
+        }
+
+        let _ = writeln!(report, "; {unexecuted:>5} instructions never executed");
+        let _ = writeln!(report);
+
+        for line in &disassembly.lines {
+            if let Some(label) = &line.label {
+                let _ = writeln!(report, "{label}:");
+            }
+
+            let addrs = line.addr..line.addr + line.bytes.len();
+            let any = |flag: u8| addrs.clone().any(|addr| self.has(addr, flag));
+
+            let marks: String = [(EXECUTED, 'X'), (READ, 'R'), (WRITTEN, 'W')]
+                .into_iter()
+                .map(|(flag, mark)| if any(flag) { mark } else { '-' })
+                .collect();
+            let dead = match line.kind {
+                LineKind::Code if !self.is_executed(line.addr) => '!',
+                _ => ' ',
+            };
+
+            let bytes: String = line
+                .bytes
+                .iter()
+                .map(|byte| format!("{byte:02X}"))
+                .collect();
+            let _ = writeln!(
+                report,
+                "{dead}{marks} {:04X}  {bytes:<16}  {}",
+                line.addr, line.text
+            );
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/coverage/coverage_tests.rs"]
+mod coverage_tests;
@@ -1,6 +1,16 @@
-use crate::{constants::*, timer};
-use rand::Rng;
-use std::path::Path;
+use crate::{
+    constants::*,
+    coverage::Coverage,
+    fault::CpuFault,
+    instruction::Instruction,
+    profiler::Profiler,
+    quirks::Quirks,
+    random::Random,
+    state::{CpuState, StateError, STATE_VERSION},
+    timer::Clock,
+    trace::Tracer,
+};
+use std::{ops::Range, path::Path};
 
 enum Operation {
     Noop,
@@ -19,14 +29,24 @@ impl Operation {
     }
 }
 
+/// The framebuffer, sized for SUPER-CHIP high resolution mode.
+///
+/// In low resolution mode only the top left [`VRAM_WIDTH`] x [`VRAM_HEIGHT`] pixels are used.
+/// Each pixel holds a bitmask of the XO-CHIP bitplanes it is lit in: bit 0 for plane 1 and bit 1 for plane 2.
+pub type Vram = [[u8; HIRES_VRAM_WIDTH]; HIRES_VRAM_HEIGHT];
+
 pub struct CpuOutput<'a> {
     pub should_beep: bool,
     pub should_draw: bool,
-    pub vram: &'a [[u8; VRAM_WIDTH]; VRAM_HEIGHT],
+    pub should_exit: bool,
+    pub hires: bool,
+    pub vram: &'a Vram,
+    pub audio_pattern: Option<&'a [u8; AUDIO_PATTERN_SIZE]>,
+    pub pitch: u8,
 }
 
 pub struct Cpu {
-    vram: [[u8; VRAM_WIDTH]; VRAM_HEIGHT],
+    vram: Vram,
     ram: [u8; RAM_SIZE],
     v: [u8; 16],
     i: usize,
@@ -35,18 +55,39 @@ pub struct Cpu {
     pc: usize,
     delay_timer: u8,
     sound_timer: u8,
+    rpl: [u8; 16],
+    planes: u8,
+    audio_pattern: Option<[u8; AUDIO_PATTERN_SIZE]>,
+    pitch: u8,
     keypad: [bool; 16],
     keypad_wait_input: usize,
     should_draw: bool,
     should_keypad_wait: bool,
+    should_display_wait: bool,
+    hires: bool,
+    halted: bool,
+    quirks: Quirks,
+    random: Random,
+    cycles: u64,
+    tracer: Option<Tracer>,
+    profiler: Option<Profiler>,
+    coverage: Option<Coverage>,
     last_timer_t: u128,
     last_instruction_t: u128,
+    /// The instructions already run of a frame that `run_frame_until` stopped partway through.
+    frame_progress: usize,
+}
+
+impl Default for Cpu {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Cpu {
     pub fn new() -> Self {
         Cpu {
-            vram: [[0; VRAM_WIDTH]; VRAM_HEIGHT],
+            vram: [[0; HIRES_VRAM_WIDTH]; HIRES_VRAM_HEIGHT],
             ram: [0; RAM_SIZE],
             v: [0; 16],
             i: 0,
@@ -55,150 +96,664 @@ impl Cpu {
             pc: 0x200,
             delay_timer: 0,
             sound_timer: 0,
+            rpl: [0; 16],
+            planes: 1,
+            audio_pattern: None,
+            pitch: DEFAULT_PITCH,
             keypad: [false; 16],
             keypad_wait_input: 0,
             should_draw: true,
             should_keypad_wait: false,
-            last_timer_t: timer::time_nanos(),
-            last_instruction_t: timer::time_nanos(),
+            should_display_wait: false,
+            hires: false,
+            halted: false,
+            quirks: Quirks::default(),
+            random: Random::default(),
+            cycles: 0,
+            tracer: None,
+            profiler: None,
+            coverage: None,
+            last_timer_t: 0,
+            last_instruction_t: 0,
+            frame_progress: 0,
         }
     }
 
     pub fn init(&mut self, filepath: &Path) -> std::result::Result<(), Box<dyn std::error::Error>> {
-        self.load_fonts();
-        self.load_rom(filepath)?;
+        let rom = std::fs::read(filepath)?;
+        self.load(&rom)?;
 
         Ok(())
     }
 
-    pub fn process(&mut self, keypad: [bool; 16], instruction_time_ns: u128) -> CpuOutput {
+    /// Load the font set and the given ROM bytes into ram, ready to execute from 0x200.
+    ///
+    /// Set the quirks first, as they decide how much ram the ROM may fill.
+    pub fn load(&mut self, rom: &[u8]) -> Result<(), CpuFault> {
+        self.load_fonts();
+        self.load_rom(rom)
+    }
+
+    /// Run the CPU against a clock, executing an instruction every `instruction_time_ns` and
+    /// ticking the timers at 60 Hz.
+    ///
+    /// Every instruction and tick that fell due since the last call runs, in the order they fell
+    /// due, and the time left over carries to the next call, so both rates hold however often
+    /// this is called. A gap longer than [`MAX_CATCH_UP_NS`], such as before the first call, is
+    /// skipped rather than caught up on.
+    pub fn process(
+        &mut self,
+        keypad: [bool; 16],
+        instruction_time_ns: u128,
+        clock: &dyn Clock,
+    ) -> Result<CpuOutput<'_>, CpuFault> {
+        self.process_until(keypad, instruction_time_ns, clock, |_| false)
+    }
+
+    /// Like [`Cpu::process`], except `stop` is asked before each instruction and nothing more
+    /// runs once it returns `true`, such as at a breakpoint. What was left due is caught up on
+    /// by the next call.
+    pub fn process_until(
+        &mut self,
+        keypad: [bool; 16],
+        instruction_time_ns: u128,
+        clock: &dyn Clock,
+        mut stop: impl FnMut(&Self) -> bool,
+    ) -> Result<CpuOutput<'_>, CpuFault> {
         self.keypad = keypad;
         self.should_draw = false;
 
-        let time_ns: u128 = timer::time_nanos();
+        let time_ns = clock.now_nanos();
+        let instruction_time_ns = instruction_time_ns.max(1);
 
-        if self.should_keypad_wait {
-            for (index, _key) in keypad.iter().enumerate() {
-                if keypad[index] {
-                    self.should_keypad_wait = false;
-                    self.v[self.keypad_wait_input] = index as u8;
+        if time_ns.saturating_sub(self.last_timer_t.min(self.last_instruction_t)) > MAX_CATCH_UP_NS
+        {
+            self.last_timer_t = time_ns;
+            self.last_instruction_t = time_ns;
+        }
+
+        loop {
+            let next_timer_t = self.last_timer_t + TIMER_INTERVAL_NS;
+            let next_instruction_t = self.last_instruction_t + instruction_time_ns;
+
+            if next_timer_t.min(next_instruction_t) > time_ns {
+                break;
+            }
+
+            if next_timer_t <= next_instruction_t {
+                self.tick_timers();
+                self.last_timer_t = next_timer_t;
+            } else {
+                if stop(self) {
                     break;
                 }
+
+                self.step()?;
+                self.last_instruction_t = next_instruction_t;
             }
-        } else {
-            if time_ns - self.last_timer_t > 16_666_666 {
-                if self.sound_timer > 0 {
-                    self.sound_timer -= 1;
-                }
+        }
 
-                if self.delay_timer > 0 {
-                    self.delay_timer -= 1;
-                }
+        Ok(self.output())
+    }
+
+    /// Run one 60 Hz frame: execute `instructions` instructions, then tick the timers once.
+    ///
+    /// This does not consult any clock, so identical input always produces identical output as
+    /// long as the random number generator starts from the same seed. See [`Cpu::set_random`].
+    pub fn run_frame(
+        &mut self,
+        keypad: [bool; 16],
+        instructions: usize,
+    ) -> Result<CpuOutput<'_>, CpuFault> {
+        self.run_frame_until(keypad, instructions, |_| false)?;
+        Ok(self.output())
+    }
+
+    /// Like [`Cpu::run_frame`], except `stop` is asked before each instruction, such as for a
+    /// breakpoint, and the frame ends early once it returns `true`.
+    ///
+    /// Returns whether the frame finished. A frame that didn't is picked up where it stopped by
+    /// the next call, so it still runs `instructions` instructions before the timers tick.
+    pub fn run_frame_until(
+        &mut self,
+        keypad: [bool; 16],
+        instructions: usize,
+        mut stop: impl FnMut(&Self) -> bool,
+    ) -> Result<bool, CpuFault> {
+        self.keypad = keypad;
+        self.should_draw = false;
 
-                self.last_timer_t = time_ns;
+        while self.frame_progress < instructions {
+            if stop(self) {
+                return Ok(false);
             }
 
-            if time_ns - self.last_instruction_t > instruction_time_ns {
-                let opcode = self.get_opcode();
-                self.exec_opcode(opcode);
-                self.last_instruction_t = time_ns;
+            self.step()?;
+            self.frame_progress += 1;
+        }
+
+        self.frame_progress = 0;
+        self.tick_timers();
+        Ok(true)
+    }
+
+    /// Decrement the delay and sound timers by one 60 Hz tick.
+    pub fn tick_timers(&mut self) {
+        self.should_display_wait = false;
+
+        if self.sound_timer > 0 {
+            self.sound_timer -= 1;
+        }
+
+        if self.delay_timer > 0 {
+            self.delay_timer -= 1;
+        }
+
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.end_frame(self.should_keypad_wait);
+        }
+    }
+
+    /// Execute a single instruction at the program counter.
+    ///
+    /// While the CPU is blocked on `LD Vx, K` no instruction runs; instead the keypad is checked
+    /// for a pressed key to resume with. With [`Quirks::display_wait`] no instruction runs after
+    /// a draw until the timers tick.
+    ///
+    /// If the instruction faults, the CPU is left as it was before the instruction ran.
+    pub fn step(&mut self) -> Result<(), CpuFault> {
+        if self.halted || self.should_display_wait {
+            return Ok(());
+        }
+
+        if self.should_keypad_wait {
+            if let Some(profiler) = self.profiler.as_mut() {
+                profiler.record_key_wait();
             }
+
+            self.poll_keypad_wait();
+            return Ok(());
+        }
+
+        let instruction = self.instruction()?;
+
+        if let Some(mut tracer) = self.tracer.take() {
+            tracer.trace(self, instruction);
+            self.tracer = Some(tracer);
+        }
+
+        let (pc, i) = (self.pc, self.i);
+        self.exec(instruction)?;
+        self.cycles += 1;
+
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.record(pc, instruction);
+        }
+
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.record(pc, i, instruction, self.planes.count_ones() as usize);
+        }
+
+        Ok(())
+    }
+
+    /// Capture the machine state, including the quirks it runs with and the random number generator.
+    ///
+    /// The live keypad is not part of the state; it is fed in again on the next step.
+    pub fn save_state(&self) -> CpuState {
+        CpuState {
+            version: STATE_VERSION,
+            ram: self.ram.to_vec(),
+            vram: self.vram.iter().flatten().copied().collect(),
+            v: self.v,
+            i: self.i,
+            stack: self.stack,
+            sp: self.sp,
+            pc: self.pc,
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+            rpl: self.rpl,
+            planes: self.planes,
+            audio_pattern: self.audio_pattern,
+            pitch: self.pitch,
+            keypad_wait_input: self.keypad_wait_input,
+            should_keypad_wait: self.should_keypad_wait,
+            should_display_wait: self.should_display_wait,
+            hires: self.hires,
+            halted: self.halted,
+            quirks: self.quirks,
+            random: self.random,
+        }
+    }
+
+    /// Restore a state captured with [`Cpu::save_state`].
+    ///
+    /// The state is checked in full before anything is changed, so on error the CPU is untouched.
+    pub fn load_state(&mut self, state: &CpuState) -> Result<(), StateError> {
+        if state.version != STATE_VERSION {
+            return Err(StateError::UnsupportedVersion {
+                found: state.version,
+                expected: STATE_VERSION,
+            });
+        }
+
+        if state.ram.len() != RAM_SIZE {
+            return Err(StateError::Invalid("ram size"));
+        }
+
+        if state.vram.len() != HIRES_VRAM_WIDTH * HIRES_VRAM_HEIGHT {
+            return Err(StateError::Invalid("vram size"));
+        }
+
+        if state.sp >= self.stack.len() {
+            return Err(StateError::Invalid("stack pointer"));
+        }
+
+        if state.keypad_wait_input >= self.v.len() {
+            return Err(StateError::Invalid("key wait register"));
+        }
+
+        self.ram.copy_from_slice(&state.ram);
+
+        for (row, pixels) in self
+            .vram
+            .iter_mut()
+            .zip(state.vram.chunks_exact(HIRES_VRAM_WIDTH))
+        {
+            row.copy_from_slice(pixels);
         }
 
+        self.v = state.v;
+        self.i = state.i;
+        self.stack = state.stack;
+        self.sp = state.sp;
+        self.pc = state.pc;
+        self.delay_timer = state.delay_timer;
+        self.sound_timer = state.sound_timer;
+        self.rpl = state.rpl;
+        self.planes = state.planes;
+        self.audio_pattern = state.audio_pattern;
+        self.pitch = state.pitch;
+        self.keypad_wait_input = state.keypad_wait_input;
+        self.should_keypad_wait = state.should_keypad_wait;
+        self.should_display_wait = state.should_display_wait;
+        self.hires = state.hires;
+        self.halted = state.halted;
+        self.quirks = state.quirks;
+        self.random = state.random;
+        self.frame_progress = 0;
+
+        Ok(())
+    }
+
+    /// Trace every instruction executed from now on, or stop tracing with `None`.
+    pub fn set_tracer(&mut self, tracer: Option<Tracer>) {
+        self.tracer = tracer;
+    }
+
+    /// Stop tracing, handing back the tracer so it can be finished.
+    pub fn take_tracer(&mut self) -> Option<Tracer> {
+        self.tracer.take()
+    }
+
+    /// Profile every instruction executed from now on, or stop profiling with `None`.
+    pub fn set_profiler(&mut self, profiler: Option<Profiler>) {
+        self.profiler = profiler;
+    }
+
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+
+    pub fn profiler_mut(&mut self) -> Option<&mut Profiler> {
+        self.profiler.as_mut()
+    }
+
+    /// Track the bytes executed, read and written from now on, or stop tracking with `None`.
+    pub fn set_coverage(&mut self, coverage: Option<Coverage>) {
+        self.coverage = coverage;
+    }
+
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
+    /// Draw `RND` numbers from the given generator, such as one with a fixed seed.
+    pub fn set_random(&mut self, random: Random) {
+        self.random = random;
+    }
+
+    pub fn random(&self) -> &Random {
+        &self.random
+    }
+
+    pub fn quirks(&self) -> Quirks {
+        self.quirks
+    }
+
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+    }
+
+    /// The bytes of ram the program can reach: 4 KiB, or 64 KiB with
+    /// [`Quirks::extended_memory`]. Anything past it faults.
+    pub fn memory_size(&self) -> usize {
+        if self.quirks.extended_memory {
+            RAM_SIZE
+        } else {
+            CHIP8_RAM_SIZE
+        }
+    }
+
+    pub fn set_keypad(&mut self, keypad: [bool; 16]) {
+        self.keypad = keypad;
+    }
+
+    pub fn set_key(&mut self, key: usize, pressed: bool) {
+        self.keypad[key] = pressed;
+    }
+
+    /// Ram, for debuggers and tools to edit. Writes take effect from the next instruction.
+    pub fn ram_mut(&mut self) -> &mut [u8; RAM_SIZE] {
+        &mut self.ram
+    }
+
+    pub fn registers_mut(&mut self) -> &mut [u8; 16] {
+        &mut self.v
+    }
+
+    pub fn set_i(&mut self, i: usize) {
+        self.i = i;
+    }
+
+    pub fn set_pc(&mut self, pc: usize) {
+        self.pc = pc;
+    }
+
+    pub fn set_delay_timer(&mut self, value: u8) {
+        self.delay_timer = value;
+    }
+
+    pub fn set_sound_timer(&mut self, value: u8) {
+        self.sound_timer = value;
+    }
+
+    pub fn vram(&self) -> &Vram {
+        &self.vram
+    }
+
+    /// Whether the display is in SUPER-CHIP 128x64 high resolution mode.
+    pub fn is_hires(&self) -> bool {
+        self.hires
+    }
+
+    /// The width and height of the display in the current resolution mode.
+    pub fn screen_size(&self) -> (usize, usize) {
+        if self.hires {
+            (HIRES_VRAM_WIDTH, HIRES_VRAM_HEIGHT)
+        } else {
+            (VRAM_WIDTH, VRAM_HEIGHT)
+        }
+    }
+
+    /// Whether the program has exited with `EXIT`.
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    pub fn ram(&self) -> &[u8; RAM_SIZE] {
+        &self.ram
+    }
+
+    pub fn registers(&self) -> &[u8; 16] {
+        &self.v
+    }
+
+    pub fn i(&self) -> usize {
+        self.i
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    pub fn sp(&self) -> usize {
+        self.sp
+    }
+
+    pub fn stack(&self) -> &[usize; 12] {
+        &self.stack
+    }
+
+    pub fn delay_timer(&self) -> u8 {
+        self.delay_timer
+    }
+
+    pub fn sound_timer(&self) -> u8 {
+        self.sound_timer
+    }
+
+    pub fn rpl_flags(&self) -> &[u8; 16] {
+        &self.rpl
+    }
+
+    /// The bitmask of XO-CHIP bitplanes selected for drawing.
+    pub fn planes(&self) -> u8 {
+        self.planes
+    }
+
+    /// The XO-CHIP audio pattern, if the program has loaded one with `AUDIO`.
+    pub fn audio_pattern(&self) -> Option<&[u8; AUDIO_PATTERN_SIZE]> {
+        self.audio_pattern.as_ref()
+    }
+
+    pub fn pitch(&self) -> u8 {
+        self.pitch
+    }
+
+    pub fn is_waiting_for_key(&self) -> bool {
+        self.should_keypad_wait
+    }
+
+    /// The number of instructions executed since the CPU was created.
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Decode the instruction at the program counter.
+    pub fn instruction(&self) -> Result<Instruction, CpuFault> {
+        self.read_u16(self.pc).map(Instruction::decode)
+    }
+
+    /// What the CPU has for the frontend to draw and play, as returned by the last frame.
+    pub fn output(&self) -> CpuOutput<'_> {
         CpuOutput {
             should_beep: self.sound_timer > 0,
             should_draw: self.should_draw,
+            should_exit: self.halted,
+            hires: self.hires,
             vram: &self.vram,
+            audio_pattern: self.audio_pattern.as_ref(),
+            pitch: self.pitch,
+        }
+    }
+
+    fn poll_keypad_wait(&mut self) {
+        if let Some(index) = self.keypad.iter().position(|&pressed| pressed) {
+            self.should_keypad_wait = false;
+            self.v[self.keypad_wait_input] = index as u8;
         }
     }
 
-    fn get_opcode(&self) -> u16 {
-        (self.ram[self.pc] as u16) << 8 | (self.ram[self.pc + 1] as u16)
+    fn read_u16(&self, addr: usize) -> Result<u16, CpuFault> {
+        let range = self.ram_range(addr, 2)?;
+        Ok((self.ram[range.start] as u16) << 8 | (self.ram[range.start + 1] as u16))
+    }
+
+    /// The range of `len` bytes of ram starting at `addr`, or a fault if it runs past
+    /// [`Cpu::memory_size`].
+    fn ram_range(&self, addr: usize, len: usize) -> Result<Range<usize>, CpuFault> {
+        let memory_size = self.memory_size();
+
+        if addr + len > memory_size {
+            return Err(CpuFault::MemoryOutOfRange {
+                pc: self.pc,
+                addr: addr.max(memory_size),
+            });
+        }
+
+        Ok(addr..addr + len)
     }
 
     fn load_fonts(&mut self) {
-        self.ram[0..80].copy_from_slice(&FONT);
+        self.ram[FONT_ADDR..FONT_ADDR + FONT.len()].copy_from_slice(&FONT);
+        self.ram[BIG_FONT_ADDR..BIG_FONT_ADDR + BIG_FONT.len()].copy_from_slice(&BIG_FONT);
     }
 
-    fn load_rom(&mut self, filepath: &Path) -> std::result::Result<(), Box<dyn std::error::Error>> {
-        let rom = std::fs::read(filepath)?;
-        let rom_end = self.pc + rom.len();
+    fn load_rom(&mut self, rom: &[u8]) -> Result<(), CpuFault> {
+        let max = self.memory_size().saturating_sub(self.pc);
 
-        self.ram[self.pc..rom_end].copy_from_slice(&rom);
+        if rom.len() > max {
+            return Err(CpuFault::RomTooLarge {
+                size: rom.len(),
+                max,
+            });
+        }
+
+        let rom_end = self.pc + rom.len();
+        self.ram[self.pc..rom_end].copy_from_slice(rom);
 
         Ok(())
     }
 
-    fn exec_opcode(&mut self, opcode: u16) {
-        let nibbles = (
-            (opcode & 0xF000) >> 12,
-            (opcode & 0x0F00) >> 8,
-            (opcode & 0x00F0) >> 4,
-            (opcode & 0x000F),
-        );
-
-        let nnn = (opcode & 0x0FFF) as usize;
-        let kk = (opcode & 0x00FF) as u8;
-        let x = nibbles.1 as usize;
-        let y = nibbles.2 as usize;
-        let n = nibbles.3 as usize;
-
-        let operation: Operation = match nibbles {
-            (0x0, 0x0, 0xE, 0x0) => self.op_00e0(),  // CLS
-            (0x0, 0x0, 0xE, 0xE) => self.op_00ee(),  // RET
-            (0x1, _, _, _) => self.op_1nnn(nnn),     // JP addr
-            (0x2, _, _, _) => self.op_2nnn(nnn),     // CALL addr
-            (0x3, _, _, _) => self.op_3xkk(x, kk),   // SE Vx, byte
-            (0x4, _, _, _) => self.op_4xkk(x, kk),   // SNE Vx, byte
-            (0x5, _, _, 0x0) => self.op_5xy0(x, y),  // SE Vx, Vy
-            (0x6, _, _, _) => self.op_6xkk(x, kk),   // LD Vx, byte
-            (0x7, _, _, _) => self.op_7xkk(x, kk),   // ADD Vx, byte
-            (0x8, _, _, 0x0) => self.op_8xy0(x, y),  // LD Vx, Vy
-            (0x8, _, _, 0x1) => self.op_8xy1(x, y),  // OR Vx, Vy
-            (0x8, _, _, 0x2) => self.op_8xy2(x, y),  // AND Vx, Vy
-            (0x8, _, _, 0x3) => self.op_8xy3(x, y),  // XOR Vx, Vy
-            (0x8, _, _, 0x4) => self.op_8xy4(x, y),  // ADD Vx, Vy
-            (0x8, _, _, 0x5) => self.op_8xy5(x, y),  // SUB Vx, Vy
-            (0x8, _, _, 0x6) => self.op_8x06(x),     // SHR Vx {, Vy}
-            (0x8, _, _, 0x7) => self.op_8xy7(x, y),  // SUBN Vx, Vy
-            (0x8, _, _, 0xE) => self.op_8x0e(x),     // SHL Vx {, Vy}
-            (0x9, _, _, 0x0) => self.op_9xy0(x, y),  // SNE Vx, Vy
-            (0xA, _, _, _) => self.op_annn(nnn),     // LD I, addr
-            (0xB, _, _, _) => self.op_bnnn(nnn),     // JP V0, addr
-            (0xC, _, _, _) => self.op_cxkk(x, kk),   // RND Vx, byte
-            (0xD, _, _, _) => self.op_dxyn(x, y, n), // DRW Vx, Vy, nibble
-            (0xE, _, 0x9, 0xE) => self.op_ex9e(x),   // SKP Vx
-            (0xE, _, 0xA, 0x1) => self.op_exa1(x),   // SKNP Vx
-            (0xF, _, 0x0, 0x7) => self.op_fx07(x),   // LD Vx, DT
-            (0xF, _, 0x0, 0xA) => self.op_fx0a(x),   // LD Vx {, K}
-            (0xF, _, 0x1, 0x5) => self.op_fx15(x),   // LD DT, Vx
-            (0xF, _, 0x1, 0x8) => self.op_fx18(x),   // LD ST, Vx
-            (0xF, _, 0x1, 0xE) => self.op_fx1e(x),   // ADD I, Vx
-            (0xF, _, 0x2, 0x9) => self.op_fx29(x),   // LD F, Vx
-            (0xF, _, 0x3, 0x3) => self.op_fx33(x),   // LD B, Vx
-            (0xF, _, 0x5, 0x5) => self.op_fx55(x),   // LD [I], Vx
-            (0xF, _, 0x6, 0x5) => self.op_fx65(x),   // LD Vx, [I]
-            _ => Operation::Next,
+    fn exec(&mut self, instruction: Instruction) -> Result<(), CpuFault> {
+        let operation: Operation = match instruction {
+            Instruction::ScrollDown(n) => self.op_00cn(n),
+            Instruction::ScrollUp(n) => self.op_00dn(n),
+            Instruction::Clear => self.op_00e0(),
+            Instruction::Return => self.op_00ee()?,
+            Instruction::ScrollRight => self.op_00fb(),
+            Instruction::ScrollLeft => self.op_00fc(),
+            Instruction::Exit => self.op_00fd(),
+            Instruction::LowRes => self.op_00fe(),
+            Instruction::HighRes => self.op_00ff(),
+            Instruction::Sys(_) => Operation::Next,
+            Instruction::Jump(nnn) => self.op_1nnn(nnn),
+            Instruction::Call(nnn) => self.op_2nnn(nnn)?,
+            Instruction::SkipEqualByte(x, kk) => self.op_3xkk(x, kk),
+            Instruction::SkipNotEqualByte(x, kk) => self.op_4xkk(x, kk),
+            Instruction::SkipEqual(x, y) => self.op_5xy0(x, y),
+            Instruction::Save(x, y) => self.op_5xy2(x, y)?,
+            Instruction::Load(x, y) => self.op_5xy3(x, y)?,
+            Instruction::LoadByte(x, kk) => self.op_6xkk(x, kk),
+            Instruction::AddByte(x, kk) => self.op_7xkk(x, kk),
+            Instruction::Move(x, y) => self.op_8xy0(x, y),
+            Instruction::Or(x, y) => self.op_8xy1(x, y),
+            Instruction::And(x, y) => self.op_8xy2(x, y),
+            Instruction::Xor(x, y) => self.op_8xy3(x, y),
+            Instruction::Add(x, y) => self.op_8xy4(x, y),
+            Instruction::Sub(x, y) => self.op_8xy5(x, y),
+            Instruction::ShiftRight(x, y) => self.op_8xy6(x, y),
+            Instruction::SubNegated(x, y) => self.op_8xy7(x, y),
+            Instruction::ShiftLeft(x, y) => self.op_8xye(x, y),
+            Instruction::SkipNotEqual(x, y) => self.op_9xy0(x, y),
+            Instruction::LoadI(nnn) => self.op_annn(nnn),
+            Instruction::JumpOffset(nnn) => self.op_bnnn(nnn >> 8, nnn),
+            Instruction::Random(x, kk) => self.op_cxkk(x, kk),
+            Instruction::Draw(x, y, n) => self.op_dxyn(x, y, n)?,
+            Instruction::SkipKey(x) => self.op_ex9e(x),
+            Instruction::SkipNotKey(x) => self.op_exa1(x),
+            Instruction::LoadILong => self.op_f000()?,
+            Instruction::Plane(n) => self.op_fn01(n),
+            Instruction::Audio => self.op_f002()?,
+            Instruction::LoadDelay(x) => self.op_fx07(x),
+            Instruction::WaitKey(x) => self.op_fx0a(x),
+            Instruction::SetDelay(x) => self.op_fx15(x),
+            Instruction::SetSound(x) => self.op_fx18(x),
+            Instruction::AddI(x) => self.op_fx1e(x),
+            Instruction::LoadFont(x) => self.op_fx29(x),
+            Instruction::LoadBigFont(x) => self.op_fx30(x),
+            Instruction::StoreBcd(x) => self.op_fx33(x)?,
+            Instruction::Pitch(x) => self.op_fx3a(x),
+            Instruction::StoreRegisters(x) => self.op_fx55(x)?,
+            Instruction::LoadRegisters(x) => self.op_fx65(x)?,
+            Instruction::StoreFlags(x) => self.op_fx75(x),
+            Instruction::LoadFlags(x) => self.op_fx85(x),
+            Instruction::Unknown(opcode) => {
+                return Err(CpuFault::UnknownOpcode { pc: self.pc, opcode })
+            }
         };
 
         match operation {
             Operation::Noop => {}
             Operation::Next => self.pc += OPCODE_SIZE,
-            Operation::Skip => self.pc += OPCODE_SIZE * 2,
+            Operation::Skip => self.pc += OPCODE_SIZE + self.opcode_size_at(self.pc + OPCODE_SIZE)?,
             Operation::Jump(addr) => self.pc = addr,
         }
+
+        Ok(())
+    }
+
+    /// The size of the instruction at `addr`, which is 4 bytes for XO-CHIP `LD I, long addr`.
+    fn opcode_size_at(&self, addr: usize) -> Result<usize, CpuFault> {
+        if self.read_u16(addr)? == 0xF000 {
+            Ok(OPCODE_SIZE * 2)
+        } else {
+            Ok(OPCODE_SIZE)
+        }
+    }
+
+    /// Move the selected bitplanes by (dx, dy) pixels, filling the uncovered area with 0.
+    fn scroll(&mut self, dx: isize, dy: isize) {
+        let (width, height) = self.screen_size();
+        let previous = self.vram;
+
+        for y in 0..height {
+            for x in 0..width {
+                let src_x = x as isize - dx;
+                let src_y = y as isize - dy;
+
+                let moved = if (0..width as isize).contains(&src_x)
+                    && (0..height as isize).contains(&src_y)
+                {
+                    previous[src_y as usize][src_x as usize] & self.planes
+                } else {
+                    0
+                };
+
+                self.vram[y][x] = (previous[y][x] & !self.planes) | moved;
+            }
+        }
+
+        self.should_draw = true;
+    }
+
+    /// SCD nibble
+    ///
+    /// Scroll the display down n pixels.
+    fn op_00cn(&mut self, n: usize) -> Operation {
+        self.scroll(0, n as isize);
+        Operation::Next
+    }
+
+    /// SCU nibble
+    ///
+    /// Scroll the display up n pixels.
+    fn op_00dn(&mut self, n: usize) -> Operation {
+        self.scroll(0, -(n as isize));
+        Operation::Next
     }
 
     /// CLS
     ///
     /// Clear the display.
+    /// Only the selected bitplanes are cleared.
     fn op_00e0(&mut self) -> Operation {
-        for y in 0..VRAM_HEIGHT {
-            for x in 0..VRAM_WIDTH {
-                self.vram[y][x] = 0
-            }
+        for pixel in self.vram.iter_mut().flatten() {
+            *pixel &= !self.planes;
         }
 
+        self.should_draw = true;
         Operation::Next
     }
 
@@ -206,12 +761,64 @@ impl Cpu {
     ///
     /// Return from a subroutine.
     /// The interpreter sets the program counter to the address at the top of the stack, then subtracts 1 from the stack pointer.
-    fn op_00ee(&mut self) -> Operation {
+    fn op_00ee(&mut self) -> Result<Operation, CpuFault> {
+        if self.sp == 0 {
+            return Err(CpuFault::StackUnderflow { pc: self.pc });
+        }
+
         self.pc = self.stack[self.sp];
         self.sp -= 1;
+        Ok(Operation::Noop)
+    }
+
+    /// SCR
+    ///
+    /// Scroll the display right 4 pixels.
+    fn op_00fb(&mut self) -> Operation {
+        self.scroll(4, 0);
+        Operation::Next
+    }
+
+    /// SCL
+    ///
+    /// Scroll the display left 4 pixels.
+    fn op_00fc(&mut self) -> Operation {
+        self.scroll(-4, 0);
+        Operation::Next
+    }
+
+    /// EXIT
+    ///
+    /// Exit the interpreter.
+    /// No further instructions are executed.
+    fn op_00fd(&mut self) -> Operation {
+        self.halted = true;
         Operation::Noop
     }
 
+    /// LOW
+    ///
+    /// Switch to the 64x32 low resolution display and clear it.
+    fn op_00fe(&mut self) -> Operation {
+        self.hires = false;
+        self.clear_vram();
+        Operation::Next
+    }
+
+    /// HIGH
+    ///
+    /// Switch to the 128x64 high resolution display and clear it.
+    fn op_00ff(&mut self) -> Operation {
+        self.hires = true;
+        self.clear_vram();
+        Operation::Next
+    }
+
+    fn clear_vram(&mut self) {
+        self.vram = [[0; HIRES_VRAM_WIDTH]; HIRES_VRAM_HEIGHT];
+        self.should_draw = true;
+    }
+
     /// JP addr
     ///
     /// Jump to location nnn.
@@ -224,10 +831,14 @@ impl Cpu {
     ///
     /// Call subroutine at nnn.
     /// The interpreter increments the stack pointer, then puts the current PC on the top of the stack. The PC is then set to nnn.
-    fn op_2nnn(&mut self, addr: usize) -> Operation {
+    fn op_2nnn(&mut self, addr: usize) -> Result<Operation, CpuFault> {
+        if self.sp + 1 >= self.stack.len() {
+            return Err(CpuFault::StackOverflow { pc: self.pc });
+        }
+
         self.sp += 1;
         self.stack[self.sp] = self.pc + OPCODE_SIZE;
-        Operation::Jump(addr)
+        Ok(Operation::Jump(addr))
     }
 
     /// SE Vx, byte
@@ -254,6 +865,42 @@ impl Cpu {
         Operation::skip_if(self.v[x] == self.v[y])
     }
 
+    /// SAVE Vx - Vy
+    ///
+    /// Store registers Vx through Vy in memory starting at location I.
+    /// If x is greater than y, the registers are stored in reverse order. I is not changed.
+    fn op_5xy2(&mut self, x: usize, y: usize) -> Result<Operation, CpuFault> {
+        let range = self.ram_range(self.i, x.abs_diff(y) + 1)?;
+
+        for (addr, reg) in range.zip(Self::register_range(x, y)) {
+            self.ram[addr] = self.v[reg];
+        }
+
+        Ok(Operation::Next)
+    }
+
+    /// LOAD Vx - Vy
+    ///
+    /// Read registers Vx through Vy from memory starting at location I.
+    /// If x is greater than y, the registers are read in reverse order. I is not changed.
+    fn op_5xy3(&mut self, x: usize, y: usize) -> Result<Operation, CpuFault> {
+        let range = self.ram_range(self.i, x.abs_diff(y) + 1)?;
+
+        for (addr, reg) in range.zip(Self::register_range(x, y)) {
+            self.v[reg] = self.ram[addr];
+        }
+
+        Ok(Operation::Next)
+    }
+
+    fn register_range(x: usize, y: usize) -> Box<dyn Iterator<Item = usize>> {
+        if x <= y {
+            Box::new(x..=y)
+        } else {
+            Box::new((y..=x).rev())
+        }
+    }
+
     /// LD Vx, byte
     ///
     /// Set Vx = kk.
@@ -293,6 +940,7 @@ impl Cpu {
     /// Otherwise, it is 0.
     fn op_8xy1(&mut self, x: usize, y: usize) -> Operation {
         self.v[x] |= self.v[y];
+        self.reset_vf_after_logic();
         Operation::Next
     }
 
@@ -304,6 +952,7 @@ impl Cpu {
     /// Otherwise, it is 0.
     fn op_8xy2(&mut self, x: usize, y: usize) -> Operation {
         self.v[x] &= self.v[y];
+        self.reset_vf_after_logic();
         Operation::Next
     }
 
@@ -315,9 +964,17 @@ impl Cpu {
     /// Otherwise, it is 0.
     fn op_8xy3(&mut self, x: usize, y: usize) -> Operation {
         self.v[x] ^= self.v[y];
+        self.reset_vf_after_logic();
         Operation::Next
     }
 
+    /// With [`Quirks::logic_resets_vf`], the logic operations clear VF as the COSMAC VIP did.
+    fn reset_vf_after_logic(&mut self) {
+        if self.quirks.logic_resets_vf {
+            self.v[0xF] = 0;
+        }
+    }
+
     /// ADD Vx, Vy
     ///
     /// Set Vx = Vx + Vy, set VF = carry.
@@ -347,9 +1004,16 @@ impl Cpu {
     ///
     /// Set Vx = Vx SHR 1.
     /// If the least-significant bit of Vx is 1, then VF is set to 1, otherwise 0. Then Vx is divided by 2.
-    fn op_8x06(&mut self, x: usize) -> Operation {
-        self.v[0xF] = self.v[x] & 1;
-        self.v[x] >>= 1;
+    /// With [`Quirks::shift_uses_vy`], Vy is shifted and the result stored in Vx.
+    fn op_8xy6(&mut self, x: usize, y: usize) -> Operation {
+        let value = if self.quirks.shift_uses_vy {
+            self.v[y]
+        } else {
+            self.v[x]
+        };
+
+        self.v[x] = value >> 1;
+        self.v[0xF] = value & 1;
         Operation::Next
     }
 
@@ -367,9 +1031,16 @@ impl Cpu {
     ///
     /// Set Vx = Vx SHL 1.
     /// If the most-significant bit of Vx is 1, then VF is set to 1, otherwise to 0. Then Vx is multiplied by 2.
-    fn op_8x0e(&mut self, x: usize) -> Operation {
-        self.v[0xF] = self.v[x] >> 7;
-        self.v[x] <<= 1;
+    /// With [`Quirks::shift_uses_vy`], Vy is shifted and the result stored in Vx.
+    fn op_8xye(&mut self, x: usize, y: usize) -> Operation {
+        let value = if self.quirks.shift_uses_vy {
+            self.v[y]
+        } else {
+            self.v[x]
+        };
+
+        self.v[x] = value << 1;
+        self.v[0xF] = value >> 7;
         Operation::Next
     }
 
@@ -393,8 +1064,15 @@ impl Cpu {
     ///
     /// Jump to location nnn + V0.
     /// The program counter is set to nnn plus the value of V0.
-    fn op_bnnn(&mut self, nnn: usize) -> Operation {
-        Operation::Jump(self.v[0] as usize + nnn)
+    /// With [`Quirks::jump_uses_vx`], the jump is to xnn plus the value of Vx instead.
+    fn op_bnnn(&mut self, x: usize, nnn: usize) -> Operation {
+        let offset = if self.quirks.jump_uses_vx {
+            self.v[x]
+        } else {
+            self.v[0]
+        };
+
+        Operation::Jump(offset as usize + nnn)
     }
 
     // RND Vx, byte
@@ -402,9 +1080,9 @@ impl Cpu {
     /// Set Vx = random byte AND kk.
     /// The interpreter generates a random number from 0 to 255, which is then ANDed with the value kk.
     /// The results are stored in Vx. See instruction [`Self::op_8xy2`] for more information on AND.
+    /// The number comes from the CPU's own [`Random`], so a seeded run draws the same numbers every time.
     fn op_cxkk(&mut self, x: usize, kk: u8) -> Operation {
-        let mut rng = rand::thread_rng();
-        self.v[x] = rng.gen::<u8>() & kk;
+        self.v[x] = self.random.next_byte() & kk;
         Operation::Next
     }
 
@@ -416,22 +1094,79 @@ impl Cpu {
     /// Sprites are XORed onto the existing screen.
     /// If this causes any pixels to be erased, VF is set to 1, otherwise it is set to 0.
     /// If the sprite is positioned so part of it is outside the coordinates of the display, it wraps around to the opposite side of the screen.
-    fn op_dxyn(&mut self, x: usize, y: usize, n: usize) -> Operation {
-        self.v[0xF] = 0;
+    /// With [`Quirks::clip_sprites`], only the starting position wraps and the parts of the sprite past the edge are not drawn.
+    /// When n is 0, a 16x16 sprite is drawn from 32 bytes of memory, two bytes per row.
+    fn op_dxyn(&mut self, x: usize, y: usize, n: usize) -> Result<Operation, CpuFault> {
+        let (sprite_width, rows) = if n == 0 { (16, 16) } else { (8, n) };
+        let sprite_size = rows * sprite_width / 8;
+        let start_x = self.v[x] as usize;
+        let start_y = self.v[y] as usize;
+        let mut sprite_addr = self.i;
+
+        let planes = self.planes.count_ones() as usize;
+        self.ram_range(sprite_addr, sprite_size * planes)?;
 
-        for byte in 0..n {
-            let y = (self.v[y] as usize + byte) % VRAM_HEIGHT;
+        self.v[0xF] = 0;
 
-            for bit in 0..8 {
-                let x = (self.v[x] as usize + bit) % VRAM_WIDTH;
-                let color = (self.ram[self.i + byte] >> (7 - bit)) & 1;
-                self.v[0xF] |= color & self.vram[y][x];
-                self.vram[y][x] ^= color;
+        for plane in [0b01, 0b10] {
+            if self.planes & plane == 0 {
+                continue;
             }
+
+            self.draw_sprite(sprite_addr, plane, (start_x, start_y), sprite_width, rows);
+            sprite_addr += sprite_size;
         }
 
         self.should_draw = true;
-        Operation::Next
+        self.should_display_wait = self.quirks.display_wait;
+        Ok(Operation::Next)
+    }
+
+    /// XOR a sprite onto a single bitplane, setting VF if any lit pixel is erased.
+    fn draw_sprite(
+        &mut self,
+        addr: usize,
+        plane: u8,
+        (start_x, start_y): (usize, usize),
+        sprite_width: usize,
+        rows: usize,
+    ) {
+        let (width, height) = self.screen_size();
+        let start_x = start_x % width;
+        let start_y = start_y % height;
+
+        for row in 0..rows {
+            let y = start_y + row;
+
+            if self.quirks.clip_sprites && y >= height {
+                break;
+            }
+
+            let y = y % height;
+            let sprite_row = if sprite_width == 16 {
+                (self.ram[addr + row * 2] as u16) << 8 | self.ram[addr + row * 2 + 1] as u16
+            } else {
+                self.ram[addr + row] as u16
+            };
+
+            for bit in 0..sprite_width {
+                let x = start_x + bit;
+
+                if self.quirks.clip_sprites && x >= width {
+                    break;
+                }
+
+                let x = x % width;
+
+                if (sprite_row >> (sprite_width - 1 - bit)) & 1 == 1 {
+                    if self.vram[y][x] & plane != 0 {
+                        self.v[0xF] = 1;
+                    }
+
+                    self.vram[y][x] ^= plane;
+                }
+            }
+        }
     }
 
     /// SKP Vx
@@ -439,7 +1174,7 @@ impl Cpu {
     /// Skip next instruction if key with the value of Vx is pressed.
     /// Checks the keyboard, and if the key corresponding to the value of Vx is currently in the down position, PC is increased by 2.
     fn op_ex9e(&self, x: usize) -> Operation {
-        Operation::skip_if(self.keypad[self.v[x] as usize])
+        Operation::skip_if(self.keypad[(self.v[x] & 0xF) as usize])
     }
 
     /// SKNP Vx
@@ -447,7 +1182,36 @@ impl Cpu {
     /// Skip next instruction if key with the value of Vx is not pressed.
     /// Checks the keyboard, and if the key corresponding to the value of Vx is currently in the up position, PC is increased by 2.
     fn op_exa1(&self, x: usize) -> Operation {
-        Operation::skip_if(!self.keypad[self.v[x] as usize])
+        Operation::skip_if(!self.keypad[(self.v[x] & 0xF) as usize])
+    }
+
+    /// LD I, long addr
+    ///
+    /// Set I = the 16-bit address stored in the two bytes following this instruction.
+    fn op_f000(&mut self) -> Result<Operation, CpuFault> {
+        self.i = self.read_u16(self.pc + OPCODE_SIZE)? as usize;
+        Ok(Operation::Jump(self.pc + OPCODE_SIZE * 2))
+    }
+
+    /// PLANE n
+    ///
+    /// Select the bitplanes drawn to, cleared and scrolled by later instructions.
+    /// Bit 0 of n selects plane 1 and bit 1 selects plane 2.
+    fn op_fn01(&mut self, n: usize) -> Operation {
+        self.planes = (n & 0b11) as u8;
+        Operation::Next
+    }
+
+    /// AUDIO
+    ///
+    /// Load the 16-byte audio pattern starting at memory location I into the audio buffer.
+    fn op_f002(&mut self) -> Result<Operation, CpuFault> {
+        let range = self.ram_range(self.i, AUDIO_PATTERN_SIZE)?;
+        let mut pattern = [0; AUDIO_PATTERN_SIZE];
+        pattern.copy_from_slice(&self.ram[range]);
+        self.audio_pattern = Some(pattern);
+
+        Ok(Operation::Next)
     }
 
     /// LD Vx, DT
@@ -511,16 +1275,34 @@ impl Cpu {
         Operation::Next
     }
 
+    /// LD HF, Vx
+    ///
+    /// Set I = location of the 10-byte high resolution sprite for digit Vx.
+    fn op_fx30(&mut self, x: usize) -> Operation {
+        self.i = BIG_FONT_ADDR + (self.v[x] as usize & 0xF) * 10;
+        Operation::Next
+    }
+
     /// LD B, Vx
     ///
     /// Store BCD representation of Vx in memory locations I, I+1, and I+2.
     /// The interpreter takes the decimal value of Vx, and places the hundreds digit in memory at location in I,
     /// the tens digit at location I+1, and the ones digit at location I+2.
-    fn op_fx33(&mut self, x: usize) -> Operation {
-        self.ram[self.i] = self.v[x] / 100;
-        self.ram[self.i + 1] = (self.v[x] % 100) / 10;
-        self.ram[self.i + 2] = self.v[x] % 10;
+    fn op_fx33(&mut self, x: usize) -> Result<Operation, CpuFault> {
+        let range = self.ram_range(self.i, 3)?;
 
+        self.ram[range.start] = self.v[x] / 100;
+        self.ram[range.start + 1] = (self.v[x] % 100) / 10;
+        self.ram[range.start + 2] = self.v[x] % 10;
+
+        Ok(Operation::Next)
+    }
+
+    /// PITCH Vx
+    ///
+    /// Set the audio pattern playback pitch = Vx.
+    fn op_fx3a(&mut self, x: usize) -> Operation {
+        self.pitch = self.v[x];
         Operation::Next
     }
 
@@ -528,27 +1310,61 @@ impl Cpu {
     ///
     /// Store registers V0 through Vx in memory starting at location I.
     /// The interpreter copies the values of registers V0 through Vx into memory, starting at the address in I.
-    fn op_fx55(&mut self, x: usize) -> Operation {
-        for i in 0..=x {
-            self.ram[self.i + i] = self.v[i];
+    /// With [`Quirks::load_store_increments_i`], I is left at I + x + 1, and with
+    /// [`Quirks::load_store_increments_i_by_x`] at I + x.
+    fn op_fx55(&mut self, x: usize) -> Result<Operation, CpuFault> {
+        let range = self.ram_range(self.i, x + 1)?;
+        self.ram[range].copy_from_slice(&self.v[0..=x]);
+
+        if self.quirks.load_store_increments_i {
+            self.i += x + 1;
+        } else if self.quirks.load_store_increments_i_by_x {
+            self.i += x;
         }
 
-        Operation::Next
+        Ok(Operation::Next)
     }
 
     /// LD Vx, [I]
     ///
     /// Read registers V0 through Vx from memory starting at location I.
     /// The interpreter reads values from memory starting at location I into registers V0 through Vx.
-    fn op_fx65(&mut self, x: usize) -> Operation {
-        for i in 0..=x {
-            self.v[i] = self.ram[self.i + i];
+    /// With [`Quirks::load_store_increments_i`], I is left at I + x + 1, and with
+    /// [`Quirks::load_store_increments_i_by_x`] at I + x.
+    fn op_fx65(&mut self, x: usize) -> Result<Operation, CpuFault> {
+        let range = self.ram_range(self.i, x + 1)?;
+        self.v[0..=x].copy_from_slice(&self.ram[range]);
+
+        if self.quirks.load_store_increments_i {
+            self.i += x + 1;
+        } else if self.quirks.load_store_increments_i_by_x {
+            self.i += x;
         }
 
+        Ok(Operation::Next)
+    }
+
+    /// LD R, Vx
+    ///
+    /// Store registers V0 through Vx in the RPL user flags.
+    fn op_fx75(&mut self, x: usize) -> Operation {
+        self.rpl[0..=x].copy_from_slice(&self.v[0..=x]);
+        Operation::Next
+    }
+
+    /// LD Vx, R
+    ///
+    /// Read registers V0 through Vx from the RPL user flags.
+    fn op_fx85(&mut self, x: usize) -> Operation {
+        self.v[0..=x].copy_from_slice(&self.rpl[0..=x]);
         Operation::Next
     }
 }
 
+#[cfg(test)]
+#[path = "../tests/helpers/opcode_helper.rs"]
+mod opcode_helper;
+
 #[cfg(test)]
 #[path = "../tests/cpu/cpu_tests.rs"]
 mod cpu_tests;
@@ -556,3 +1372,23 @@ mod cpu_tests;
 #[cfg(test)]
 #[path = "../tests/cpu/opcode_tests.rs"]
 mod opcode_tests;
+
+#[cfg(test)]
+#[path = "../tests/cpu/quirks_tests.rs"]
+mod quirks_tests;
+
+#[cfg(test)]
+#[path = "../tests/cpu/schip_tests.rs"]
+mod schip_tests;
+
+#[cfg(test)]
+#[path = "../tests/cpu/xochip_tests.rs"]
+mod xochip_tests;
+
+#[cfg(test)]
+#[path = "../tests/cpu/fault_tests.rs"]
+mod fault_tests;
+
+#[cfg(test)]
+#[path = "../tests/cpu/state_tests.rs"]
+mod state_tests;
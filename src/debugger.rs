@@ -0,0 +1,137 @@
+use crate::{constants::OPCODE_SIZE, cpu::Cpu, fault::CpuFault, instruction::Instruction};
+use std::collections::BTreeSet;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    #[default]
+    Running,
+    Paused,
+    /// Run until the `CALL` at `pc - 2` has returned to `pc`.
+    StepOver {
+        pc: usize,
+        sp: usize,
+    },
+    /// Run until the subroutine entered with `sp` stack entries returns.
+    StepOut {
+        sp: usize,
+    },
+}
+
+/// Breakpoints and stepping for a [`Cpu`].
+///
+/// The debugger doesn't run the CPU itself. Whoever drives the CPU asks [`Debugger::should_run`]
+/// before each instruction, passing it to [`Cpu::process_until`] or [`Cpu::run_frame_until`] or
+/// asking before [`Cpu::step`], so breakpoints are hit however the CPU runs.
+#[derive(Debug, Default)]
+pub struct Debugger {
+    breakpoints: BTreeSet<usize>,
+    mode: Mode,
+    /// The cycle execution resumed at, so the breakpoint the CPU is sitting on isn't hit again.
+    resume_cycle: Option<u64>,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = usize> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    pub fn has_breakpoint(&self, addr: usize) -> bool {
+        self.breakpoints.contains(&addr)
+    }
+
+    pub fn add_breakpoint(&mut self, addr: usize) {
+        self.breakpoints.insert(addr);
+    }
+
+    pub fn remove_breakpoint(&mut self, addr: usize) {
+        self.breakpoints.remove(&addr);
+    }
+
+    pub fn toggle_breakpoint(&mut self, addr: usize) {
+        if !self.breakpoints.remove(&addr) {
+            self.breakpoints.insert(addr);
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.mode == Mode::Paused
+    }
+
+    pub fn pause(&mut self) {
+        self.mode = Mode::Paused;
+    }
+
+    /// Continue running until the next breakpoint.
+    pub fn resume(&mut self, cpu: &Cpu) {
+        self.run(Mode::Running, cpu);
+    }
+
+    /// Execute the instruction at the program counter and stay paused.
+    pub fn step(&mut self, cpu: &mut Cpu) -> Result<(), CpuFault> {
+        self.mode = Mode::Paused;
+        cpu.step()
+    }
+
+    /// Like [`Debugger::step`], except a `CALL` runs until the subroutine returns.
+    pub fn step_over(&mut self, cpu: &mut Cpu) -> Result<(), CpuFault> {
+        let pc = cpu.pc();
+        let is_call = matches!(cpu.instruction(), Ok(Instruction::Call(_)));
+
+        if !is_call {
+            return self.step(cpu);
+        }
+
+        let sp = cpu.sp();
+        self.run(
+            Mode::StepOver {
+                pc: pc + OPCODE_SIZE,
+                sp,
+            },
+            cpu,
+        );
+
+        Ok(())
+    }
+
+    /// Run until the current subroutine returns, or until the next breakpoint outside of one.
+    pub fn step_out(&mut self, cpu: &Cpu) {
+        match cpu.sp() {
+            0 => self.run(Mode::Running, cpu),
+            sp => self.run(Mode::StepOut { sp }, cpu),
+        }
+    }
+
+    /// Check the CPU before it executes the instruction at its program counter.
+    ///
+    /// Returns `false`, pausing if needed, when the CPU is at a breakpoint or a step has finished.
+    pub fn should_run(&mut self, cpu: &Cpu) -> bool {
+        let finished = match self.mode {
+            Mode::Running => false,
+            Mode::Paused => return false,
+            Mode::StepOver { pc, sp } => cpu.pc() == pc && cpu.sp() <= sp,
+            Mode::StepOut { sp } => cpu.sp() < sp,
+        };
+
+        let resuming = self.resume_cycle == Some(cpu.cycles());
+
+        if finished || (!resuming && self.breakpoints.contains(&cpu.pc())) {
+            self.mode = Mode::Paused;
+            return false;
+        }
+
+        true
+    }
+
+    fn run(&mut self, mode: Mode, cpu: &Cpu) {
+        self.mode = mode;
+        self.resume_cycle = Some(cpu.cycles());
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/debugger/debugger_tests.rs"]
+mod debugger_tests;
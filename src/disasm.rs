@@ -0,0 +1,241 @@
+use crate::{
+    constants::{OPCODE_SIZE, PROGRAM_START},
+    instruction::Instruction,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt,
+};
+
+/// The most data bytes shown on one line.
+const DATA_LINE_SIZE: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    /// Bytes reached by following the program from its start.
+    Code,
+    /// Bytes never reached as an instruction, such as sprites.
+    Data,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub addr: usize,
+    pub bytes: Vec<u8>,
+    pub kind: LineKind,
+    /// Set when something jumps to, calls or points I at this address.
+    pub label: Option<String>,
+    pub text: String,
+}
+
+/// A ROM decoded into Cowgod style mnemonics, split into code and data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disassembly {
+    pub lines: Vec<Line>,
+}
+
+impl Disassembly {
+    pub fn line_at(&self, addr: usize) -> Option<&Line> {
+        self.lines.iter().find(|line| line.addr == addr)
+    }
+}
+
+impl fmt::Display for Disassembly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            if let Some(label) = &line.label {
+                writeln!(f, "{label}:")?;
+            }
+
+            let bytes: String = line
+                .bytes
+                .iter()
+                .map(|byte| format!("{byte:02X}"))
+                .collect();
+            writeln!(f, "    {:04X}  {bytes:<16}  {}", line.addr, line.text)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Where control can go after an instruction.
+struct Flow {
+    size: usize,
+    next: Vec<usize>,
+    /// An address the instruction refers to, which gets a label.
+    target: Option<usize>,
+}
+
+/// Disassemble a ROM loaded at the program start address.
+///
+/// Code is found by following the program from its start through jumps, calls and skips.
+/// `JP V0, addr` can't be followed without running the program, so its target is labeled but
+/// only traced if something else reaches it. Everything never reached is shown as data.
+pub fn disassemble(rom: &[u8]) -> Disassembly {
+    let end = PROGRAM_START + rom.len();
+    let read = |addr: usize| -> Option<u16> {
+        let offset = addr.checked_sub(PROGRAM_START)?;
+        let bytes = rom.get(offset..offset + OPCODE_SIZE)?;
+        Some((bytes[0] as u16) << 8 | bytes[1] as u16)
+    };
+
+    let mut code: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut targets: BTreeSet<usize> = BTreeSet::new();
+    let mut queue = VecDeque::from([PROGRAM_START]);
+
+    while let Some(addr) = queue.pop_front() {
+        if code.contains_key(&addr) {
+            continue;
+        }
+
+        let Some(instruction) = read(addr)
+            .map(Instruction::decode)
+            .filter(|instruction| !matches!(instruction, Instruction::Unknown(_)))
+        else {
+            continue;
+        };
+
+        let Some(flow) = flow(instruction, addr, &read) else {
+            continue;
+        };
+
+        if addr + flow.size > end {
+            continue;
+        }
+
+        if let Some(target) = flow.target {
+            targets.insert(target);
+        }
+
+        code.insert(addr, flow.size);
+        queue.extend(flow.next.into_iter().filter(|&next| next < end));
+    }
+
+    let labels: BTreeMap<usize, String> = targets
+        .into_iter()
+        .filter(|&addr| (PROGRAM_START..end).contains(&addr))
+        .map(|addr| (addr, format!("L{addr:03X}")))
+        .collect();
+
+    let name = |addr: usize| match labels.get(&addr) {
+        Some(label) => label.clone(),
+        None => format!("#{addr:03X}"),
+    };
+
+    let mut lines = Vec::new();
+    let mut addr = PROGRAM_START;
+
+    while addr < end {
+        let offset = addr - PROGRAM_START;
+
+        if let Some(&size) = code.get(&addr) {
+            let instruction = Instruction::decode(read(addr).unwrap_or_default());
+            let long = read(addr + OPCODE_SIZE);
+
+            lines.push(Line {
+                addr,
+                bytes: rom[offset..offset + size].to_vec(),
+                kind: LineKind::Code,
+                label: labels.get(&addr).cloned(),
+                text: format(instruction, long, &name),
+            });
+
+            addr += size;
+            continue;
+        }
+
+        /* data runs until the next instruction or label, a line at a time */
+        let mut size = 1;
+
+        while size < DATA_LINE_SIZE
+            && addr + size < end
+            && !code.contains_key(&(addr + size))
+            && !labels.contains_key(&(addr + size))
+        {
+            size += 1;
+        }
+
+        let bytes = rom[offset..offset + size].to_vec();
+        let text = bytes
+            .iter()
+            .map(|byte| format!("#{byte:02X}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        lines.push(Line {
+            addr,
+            bytes,
+            kind: LineKind::Data,
+            label: labels.get(&addr).cloned(),
+            text: format!("DB {text}"),
+        });
+
+        addr += size;
+    }
+
+    Disassembly { lines }
+}
+
+/// The mnemonic for a single opcode, or `None` if it isn't a known instruction.
+///
+/// Addresses are shown as raw hex. The address of `LD I, long addr` is in the following word,
+/// which isn't known here, so it is shown as `LD I, long`.
+pub fn mnemonic(opcode: u16) -> Option<String> {
+    match Instruction::decode(opcode) {
+        Instruction::Unknown(_) => None,
+        instruction => Some(instruction.to_string()),
+    }
+}
+
+fn flow(
+    instruction: Instruction,
+    addr: usize,
+    read: &dyn Fn(usize) -> Option<u16>,
+) -> Option<Flow> {
+    let next = addr + OPCODE_SIZE;
+    let target = instruction.target();
+
+    let flow = |size, next| Flow { size, next, target };
+
+    Some(match instruction {
+        Instruction::Return | Instruction::Exit => flow(OPCODE_SIZE, vec![]),
+        Instruction::Jump(addr) => flow(OPCODE_SIZE, vec![addr]),
+        Instruction::Call(addr) => flow(OPCODE_SIZE, vec![addr, next]),
+        Instruction::JumpOffset(_) => flow(OPCODE_SIZE, vec![]),
+        Instruction::LoadILong => Flow {
+            size: 2 * OPCODE_SIZE,
+            next: vec![next + OPCODE_SIZE],
+            target: Some(read(next)? as usize),
+        },
+        /* a skipped instruction may be the four byte LD I, long addr */
+        instruction if instruction.is_skip() => {
+            let skipped = match read(next).map(Instruction::decode) {
+                Some(Instruction::LoadILong) => 2 * OPCODE_SIZE,
+                _ => OPCODE_SIZE,
+            };
+
+            flow(OPCODE_SIZE, vec![next, next + skipped])
+        }
+        _ => flow(OPCODE_SIZE, vec![next]),
+    })
+}
+
+/// Like [`Instruction`]'s mnemonic, with addresses given by `name` and the long address filled in.
+fn format(instruction: Instruction, long: Option<u16>, name: &dyn Fn(usize) -> String) -> String {
+    match instruction {
+        Instruction::Jump(addr) => format!("JP {}", name(addr)),
+        Instruction::Call(addr) => format!("CALL {}", name(addr)),
+        Instruction::LoadI(addr) => format!("LD I, {}", name(addr)),
+        Instruction::JumpOffset(addr) => format!("JP V0, {}", name(addr)),
+        Instruction::LoadILong => match long {
+            Some(addr) => format!("LD I, long {}", name(addr as usize)),
+            None => instruction.to_string(),
+        },
+        _ => instruction.to_string(),
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/disasm/disasm_tests.rs"]
+mod disasm_tests;
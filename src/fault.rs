@@ -0,0 +1,36 @@
+use std::fmt;
+
+/// An error raised by a ROM doing something the CPU cannot carry out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuFault {
+    /// `CALL` was executed with every stack slot already in use.
+    StackOverflow { pc: usize },
+    /// `RET` was executed with nothing on the stack.
+    StackUnderflow { pc: usize },
+    /// An instruction read or wrote past the end of ram.
+    MemoryOutOfRange { pc: usize, addr: usize },
+    /// The opcode at `pc` is not a known instruction.
+    UnknownOpcode { pc: usize, opcode: u16 },
+    /// The ROM does not fit in ram after the program start address.
+    RomTooLarge { size: usize, max: usize },
+}
+
+impl fmt::Display for CpuFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StackOverflow { pc } => write!(f, "stack overflow at {pc:#05X}"),
+            Self::StackUnderflow { pc } => write!(f, "stack underflow at {pc:#05X}"),
+            Self::MemoryOutOfRange { pc, addr } => {
+                write!(f, "memory access out of range at {pc:#05X}: {addr:#06X}")
+            }
+            Self::UnknownOpcode { pc, opcode } => {
+                write!(f, "unknown opcode {opcode:#06X} at {pc:#05X}")
+            }
+            Self::RomTooLarge { size, max } => {
+                write!(f, "rom is {size} bytes but at most {max} bytes fit in ram")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CpuFault {}
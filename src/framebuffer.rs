@@ -0,0 +1,321 @@
+use crate::{
+    constants::{HIRES_VRAM_HEIGHT, HIRES_VRAM_WIDTH, VRAM_HEIGHT, VRAM_WIDTH},
+    cpu::{Cpu, Vram},
+};
+use std::fmt;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// The most bytes a stored deflate block can hold.
+const STORED_BLOCK_SIZE: usize = 0xFFFF;
+
+/// An RGB color for each combination of lit XO-CHIP planes, indexed by the pixel's plane bits.
+pub type Palette = [[u8; 3]; 4];
+
+/// Black on white for the first plane, with the other planes in shades of grey.
+pub const DEFAULT_PALETTE: Palette = [[255, 255, 255], [0, 0, 0], [170, 170, 170], [85, 85, 85]];
+
+/// A copy of the screen at the resolution it was shown at, for writing out as an image or
+/// comparing with another.
+///
+/// Each pixel holds the bitmask of planes it is lit in, as in [`Vram`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Framebuffer {
+    /// The screen as the CPU shows it now, only the low resolution corner unless it's in high
+    /// resolution mode.
+    pub fn from_cpu(cpu: &Cpu) -> Self {
+        Self::from_vram(cpu.vram(), cpu.is_hires())
+    }
+
+    /// A screen as [`CpuOutput`](crate::cpu::CpuOutput) hands it to the display.
+    pub fn from_vram(vram: &Vram, hires: bool) -> Self {
+        let (width, height) = if hires {
+            (HIRES_VRAM_WIDTH, HIRES_VRAM_HEIGHT)
+        } else {
+            (VRAM_WIDTH, VRAM_HEIGHT)
+        };
+
+        Self {
+            width,
+            height,
+            pixels: vram[..height]
+                .iter()
+                .flat_map(|row| row[..width].iter().copied())
+                .collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> u8 {
+        self.pixels[y * self.width + x]
+    }
+
+    /// A binary PBM, with every pixel lit in any plane black and each pixel drawn `scale` times
+    /// over in both directions.
+    pub fn to_pbm(&self, scale: usize) -> Vec<u8> {
+        let scale = scale.max(1);
+        let (width, height) = (self.width * scale, self.height * scale);
+        let mut pbm = format!("P4\n{width} {height}\n").into_bytes();
+
+        for y in 0..height {
+            let mut row = vec![0; width.div_ceil(8)];
+
+            for x in 0..width {
+                if self.pixel(x / scale, y / scale) != 0 {
+                    row[x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+
+            pbm.extend(row);
+        }
+
+        pbm
+    }
+
+    /// Read a binary PBM such as one written by [`Framebuffer::to_pbm`] with a scale of 1.
+    ///
+    /// Black pixels are read as lit in the first plane.
+    pub fn from_pbm(pbm: &[u8]) -> Result<Self, ImageError> {
+        let mut fields = Vec::new();
+        let mut at = 0;
+
+        /* the magic number, width and height, each followed by whitespace */
+        while fields.len() < 3 {
+            while pbm.get(at).is_some_and(u8::is_ascii_whitespace) {
+                at += 1;
+            }
+
+            if pbm.get(at) == Some(&b'#') {
+                while pbm.get(at).is_some_and(|&byte| byte != b'\n') {
+                    at += 1;
+                }
+
+                continue;
+            }
+
+            let start = at;
+
+            while pbm.get(at).is_some_and(|byte| !byte.is_ascii_whitespace()) {
+                at += 1;
+            }
+
+            if start == at {
+                return Err(ImageError::Malformed("header ends early"));
+            }
+
+            fields.push(&pbm[start..at]);
+        }
+
+        if fields[0] != b"P4" {
+            return Err(ImageError::Malformed("not a binary PBM"));
+        }
+
+        let number = |field: &[u8]| -> Result<usize, ImageError> {
+            std::str::from_utf8(field)
+                .ok()
+                .and_then(|field| field.parse().ok())
+                .ok_or(ImageError::Malformed("size is not a number"))
+        };
+
+        let (width, height) = (number(fields[1])?, number(fields[2])?);
+        let row_size = width.div_ceil(8);
+        let data = pbm
+            .get(at + 1..at + 1 + row_size * height)
+            .ok_or(ImageError::Malformed("pixel data ends early"))?;
+
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| (data[y * row_size + x / 8] >> (7 - x % 8)) & 1)
+            .collect();
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// An indexed color PNG in the given palette, with each pixel drawn `scale` times over in
+    /// both directions.
+    ///
+    /// The image data is stored without compression, which keeps the encoder small.
+    pub fn to_png(&self, scale: usize, palette: &Palette) -> Vec<u8> {
+        let scale = scale.max(1);
+        let (width, height) = (self.width * scale, self.height * scale);
+
+        /* two bits a pixel, each row starting with filter type 0 */
+        let row_size = 1 + width.div_ceil(4);
+        let mut data = Vec::with_capacity(row_size * height);
+
+        for y in 0..height {
+            let mut row = vec![0; row_size];
+
+            for x in 0..width {
+                let index = self.pixel(x / scale, y / scale) & 0b11;
+                row[1 + x / 4] |= index << (6 - 2 * (x % 4));
+            }
+
+            data.extend(row);
+        }
+
+        let mut header = Vec::with_capacity(13);
+        header.extend((width as u32).to_be_bytes());
+        header.extend((height as u32).to_be_bytes());
+        header.extend([2, 3, 0, 0, 0]);
+
+        let mut png = PNG_SIGNATURE.to_vec();
+        png_chunk(&mut png, b"IHDR", &header);
+        png_chunk(&mut png, b"PLTE", palette.as_flattened());
+        png_chunk(&mut png, b"IDAT", &zlib_stored(&data));
+        png_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    /// The pixels that differ from `other`, which must be the same size.
+    pub fn diff(&self, other: &Self) -> Result<FrameDiff, ImageError> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(ImageError::SizeMismatch {
+                found: (other.width, other.height),
+                expected: (self.width, self.height),
+            });
+        }
+
+        let pixels = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.pixel(x, y) != other.pixel(x, y))
+            .collect();
+
+        Ok(FrameDiff { pixels })
+    }
+}
+
+/// The pixels that differ between two frames, row by row.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrameDiff {
+    pub pixels: Vec<(usize, usize)>,
+}
+
+impl FrameDiff {
+    pub fn is_empty(&self) -> bool {
+        self.pixels.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pixels.len()
+    }
+
+    /// The smallest rectangle holding every differing pixel, as its top left and bottom right
+    /// corners.
+    pub fn bounds(&self) -> Option<((usize, usize), (usize, usize))> {
+        let (&(x, y), rest) = self.pixels.split_first()?;
+
+        Some(rest.iter().fold(((x, y), (x, y)), |(min, max), &(x, y)| {
+            ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+        }))
+    }
+}
+
+impl fmt::Display for FrameDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.bounds() {
+            None => write!(f, "frames match"),
+            Some(((left, top), (right, bottom))) => write!(
+                f,
+                "{} pixels differ between ({left}, {top}) and ({right}, {bottom})",
+                self.len()
+            ),
+        }
+    }
+}
+
+/// An error raised when an image cannot be read or compared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageError {
+    /// The image could not be parsed.
+    Malformed(&'static str),
+    /// The frames being compared are different sizes.
+    SizeMismatch {
+        found: (usize, usize),
+        expected: (usize, usize),
+    },
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(message) => write!(f, "image is malformed: {message}"),
+            Self::SizeMismatch { found, expected } => write!(
+                f,
+                "frame is {}x{}, expected {}x{}",
+                found.0, found.1, expected.0, expected.1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    png.extend(kind);
+    png.extend(data);
+
+    let crc = crc32(kind.iter().chain(data));
+    png.extend(crc.to_be_bytes());
+}
+
+fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    !bytes.into_iter().fold(!0, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| {
+            if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+/// A zlib stream holding `data` in uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = data.chunks(STORED_BLOCK_SIZE).peekable();
+
+    if blocks.peek().is_none() {
+        zlib.extend([1, 0, 0, 0xFF, 0xFF]);
+    }
+
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+
+        zlib.push(last as u8);
+        zlib.extend(len.to_le_bytes());
+        zlib.extend((!len).to_le_bytes());
+        zlib.extend(block);
+    }
+
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    zlib.extend((b << 16 | a).to_be_bytes());
+    zlib
+}
+
+#[cfg(test)]
+#[path = "../tests/framebuffer/framebuffer_tests.rs"]
+mod framebuffer_tests;
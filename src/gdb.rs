@@ -0,0 +1,470 @@
+use crate::{constants::RAM_SIZE, cpu::Cpu, debugger::Debugger, fault::CpuFault};
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+};
+
+/// The registers in the order GDB numbers them: V0-VF, then I, PC, SP, DT and ST.
+const REGISTER_COUNT: usize = 21;
+const I: usize = 16;
+const PC: usize = 17;
+const SP: usize = 18;
+const DT: usize = 19;
+const ST: usize = 20;
+
+/// The largest packet GDB may send, reported in `qSupported`.
+const PACKET_SIZE: usize = 0x4000;
+
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.chip8.cpu">
+    <reg name="v0" bitsize="8" type="uint8"/>
+    <reg name="v1" bitsize="8" type="uint8"/>
+    <reg name="v2" bitsize="8" type="uint8"/>
+    <reg name="v3" bitsize="8" type="uint8"/>
+    <reg name="v4" bitsize="8" type="uint8"/>
+    <reg name="v5" bitsize="8" type="uint8"/>
+    <reg name="v6" bitsize="8" type="uint8"/>
+    <reg name="v7" bitsize="8" type="uint8"/>
+    <reg name="v8" bitsize="8" type="uint8"/>
+    <reg name="v9" bitsize="8" type="uint8"/>
+    <reg name="va" bitsize="8" type="uint8"/>
+    <reg name="vb" bitsize="8" type="uint8"/>
+    <reg name="vc" bitsize="8" type="uint8"/>
+    <reg name="vd" bitsize="8" type="uint8"/>
+    <reg name="ve" bitsize="8" type="uint8"/>
+    <reg name="vf" bitsize="8" type="uint8"/>
+    <reg name="i" bitsize="16" type="data_ptr"/>
+    <reg name="pc" bitsize="16" type="code_ptr"/>
+    <reg name="sp" bitsize="8" type="uint8"/>
+    <reg name="dt" bitsize="8" type="uint8"/>
+    <reg name="st" bitsize="8" type="uint8"/>
+  </feature>
+</target>
+"#;
+
+/// A GDB remote serial protocol server for a [`Cpu`], listening on localhost.
+///
+/// The stub never blocks: whoever drives the CPU calls [`GdbStub::poll`] once per loop, and
+/// breakpoints and stepping go through the same [`Debugger`] the CPU is already checked against.
+/// Registers are numbered V0-VF, I, PC, SP, DT, ST, with I and PC sent big-endian like the
+/// CHIP-8 itself. SP is read-only since it can't be changed without the stack.
+pub struct GdbStub {
+    listener: TcpListener,
+    client: Option<TcpStream>,
+    buffer: Vec<u8>,
+    /// Set while GDB waits for a stop reply after `c`.
+    running: bool,
+}
+
+impl GdbStub {
+    /// Listen on `port` of 127.0.0.1, where `0` picks a free port.
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            listener,
+            client: None,
+            buffer: Vec::new(),
+            running: false,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.client.is_some()
+    }
+
+    /// Accept a connection, answer any packets received and report a stop if the CPU paused.
+    ///
+    /// The CPU is paused as soon as GDB attaches, and resumed when it detaches or disconnects.
+    /// Only an error from the listener is returned. One on the client's socket means GDB has
+    /// gone, and detaches it so another can attach.
+    pub fn poll(&mut self, cpu: &mut Cpu, debugger: &mut Debugger) -> io::Result<()> {
+        if self.client.is_none() && !self.accept(debugger)? {
+            return Ok(());
+        }
+
+        if self.serve(cpu, debugger).is_err() {
+            self.disconnect(cpu, debugger);
+        }
+
+        Ok(())
+    }
+
+    /// Take a waiting connection, returning whether one was attached.
+    fn accept(&mut self, debugger: &mut Debugger) -> io::Result<bool> {
+        let client = match self.listener.accept() {
+            Ok((client, _)) => client,
+            Err(error) if error.kind() == ErrorKind::WouldBlock => return Ok(false),
+            Err(error) => return Err(error),
+        };
+
+        /* a client that can't be set up is dropped as if it had disconnected straight away */
+        if client
+            .set_nonblocking(true)
+            .and_then(|()| client.set_nodelay(true))
+            .is_err()
+        {
+            return Ok(false);
+        }
+
+        self.client = Some(client);
+        self.buffer.clear();
+        self.running = false;
+        debugger.pause();
+
+        Ok(true)
+    }
+
+    /// Answer the client, failing once its socket does.
+    fn serve(&mut self, cpu: &mut Cpu, debugger: &mut Debugger) -> io::Result<()> {
+        self.receive()?;
+
+        while let Some(packet) = self.next_packet()? {
+            match packet {
+                Packet::Interrupt => debugger.pause(),
+                Packet::Command(command) => {
+                    if let Some(reply) = self.handle(&command, cpu, debugger) {
+                        self.send(&reply)?;
+                    }
+                }
+            }
+
+            if self.client.is_none() {
+                return Ok(());
+            }
+        }
+
+        if self.running && debugger.is_paused() {
+            self.running = false;
+            self.send("S05")?;
+        }
+
+        Ok(())
+    }
+
+    /// Read whatever has arrived, failing with `UnexpectedEof` once the client has closed.
+    fn receive(&mut self) -> io::Result<()> {
+        let Some(client) = self.client.as_mut() else {
+            return Ok(());
+        };
+
+        let mut chunk = [0; 1024];
+
+        loop {
+            match client.read(&mut chunk) {
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(size) => self.buffer.extend_from_slice(&chunk[..size]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Take the next complete packet off the buffer, acknowledging it.
+    fn next_packet(&mut self) -> io::Result<Option<Packet>> {
+        loop {
+            let Some(&first) = self.buffer.first() else {
+                return Ok(None);
+            };
+
+            match first {
+                0x03 => {
+                    self.buffer.remove(0);
+                    return Ok(Some(Packet::Interrupt));
+                }
+                b'$' => {}
+                /* acks, and anything else between packets, are dropped */
+                _ => {
+                    self.buffer.remove(0);
+                    continue;
+                }
+            }
+
+            let Some(end) = self.buffer.iter().position(|&byte| byte == b'#') else {
+                return Ok(None);
+            };
+
+            if self.buffer.len() < end + 3 {
+                return Ok(None);
+            }
+
+            let packet: Vec<u8> = self.buffer.drain(..end + 3).collect();
+            let body = &packet[1..end];
+            let checksum = std::str::from_utf8(&packet[end + 1..])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+            if checksum != Some(Self::checksum(body)) {
+                self.write(b"-")?;
+                continue;
+            }
+
+            self.write(b"+")?;
+            return Ok(Some(Packet::Command(
+                String::from_utf8_lossy(body).into_owned(),
+            )));
+        }
+    }
+
+    /// Carry out a command, returning the reply to send straight away, if any.
+    fn handle(&mut self, command: &str, cpu: &mut Cpu, debugger: &mut Debugger) -> Option<String> {
+        let split = command.chars().next().map_or(0, char::len_utf8);
+        let (kind, args) = command.split_at(split);
+
+        let reply = match kind {
+            "?" => String::from("S05"),
+            "g" => Self::read_registers(cpu),
+            "G" => ok_or_error(Self::write_registers(cpu, args)),
+            "p" => match usize::from_str_radix(args, 16) {
+                Ok(register) if register < REGISTER_COUNT => Self::read_register(cpu, register),
+                _ => String::from("E01"),
+            },
+            "P" => ok_or_error(Self::write_register(cpu, args)),
+            "m" => Self::read_memory(cpu, args).unwrap_or_else(|| String::from("E01")),
+            "M" => ok_or_error(Self::write_memory(cpu, args)),
+            "Z" | "z" => match Self::breakpoint(args) {
+                Some(addr) if kind == "Z" => {
+                    debugger.add_breakpoint(addr);
+                    String::from("OK")
+                }
+                Some(addr) => {
+                    debugger.remove_breakpoint(addr);
+                    String::from("OK")
+                }
+                None => String::new(),
+            },
+            "s" => match debugger.step(cpu) {
+                Ok(()) => String::from("S05"),
+                Err(fault) => Self::fault_signal(fault),
+            },
+            "c" => {
+                debugger.resume(cpu);
+                self.running = true;
+                return None;
+            }
+            "D" => {
+                self.send("OK").ok();
+                self.disconnect(cpu, debugger);
+                return None;
+            }
+            "k" => {
+                self.disconnect(cpu, debugger);
+                return None;
+            }
+            "H" => String::from("OK"),
+            "q" => Self::query(args),
+            _ => String::new(),
+        };
+
+        Some(reply)
+    }
+
+    fn query(query: &str) -> String {
+        if query.starts_with("Supported") {
+            return format!("PacketSize={PACKET_SIZE:x};qXfer:features:read+");
+        }
+
+        if query == "Attached" {
+            return String::from("1");
+        }
+
+        /* qXfer:features:read:target.xml:offset,length */
+        if let Some(range) = query.strip_prefix("Xfer:features:read:target.xml:") {
+            let Some((offset, length)) = range.split_once(',') else {
+                return String::from("E01");
+            };
+
+            let (Ok(offset), Ok(length)) = (
+                usize::from_str_radix(offset, 16),
+                usize::from_str_radix(length, 16),
+            ) else {
+                return String::from("E01");
+            };
+
+            let start = offset.min(TARGET_XML.len());
+            let end = (start + length).min(TARGET_XML.len());
+            let more = if end < TARGET_XML.len() { 'm' } else { 'l' };
+
+            return format!("{more}{}", &TARGET_XML[start..end]);
+        }
+
+        String::new()
+    }
+
+    fn read_registers(cpu: &Cpu) -> String {
+        (0..REGISTER_COUNT)
+            .map(|register| Self::read_register(cpu, register))
+            .collect()
+    }
+
+    fn read_register(cpu: &Cpu, register: usize) -> String {
+        match register {
+            I => format!("{:04x}", cpu.i() as u16),
+            PC => format!("{:04x}", cpu.pc() as u16),
+            SP => format!("{:02x}", cpu.sp() as u8),
+            DT => format!("{:02x}", cpu.delay_timer()),
+            ST => format!("{:02x}", cpu.sound_timer()),
+            x => format!("{:02x}", cpu.registers()[x]),
+        }
+    }
+
+    /// The hex digits a register takes up in `g` and `G` packets.
+    fn register_width(register: usize) -> usize {
+        match register {
+            I | PC => 4,
+            _ => 2,
+        }
+    }
+
+    fn write_registers(cpu: &mut Cpu, hex: &str) -> Option<()> {
+        let mut offset = 0;
+        let mut values = [0; REGISTER_COUNT];
+
+        for (register, value) in values.iter_mut().enumerate() {
+            let width = Self::register_width(register);
+            *value = usize::from_str_radix(hex.get(offset..offset + width)?, 16).ok()?;
+            offset += width;
+        }
+
+        if offset != hex.len() || values[SP] != cpu.sp() {
+            return None;
+        }
+
+        for (register, &value) in values.iter().enumerate() {
+            Self::set_register(cpu, register, value)?;
+        }
+
+        Some(())
+    }
+
+    fn write_register(cpu: &mut Cpu, args: &str) -> Option<()> {
+        let (register, value) = args.split_once('=')?;
+        let register = usize::from_str_radix(register, 16).ok()?;
+
+        if register >= REGISTER_COUNT || value.len() != Self::register_width(register) {
+            return None;
+        }
+
+        Self::set_register(cpu, register, usize::from_str_radix(value, 16).ok()?)
+    }
+
+    fn set_register(cpu: &mut Cpu, register: usize, value: usize) -> Option<()> {
+        match register {
+            I => cpu.set_i(value),
+            PC => cpu.set_pc(value),
+            SP if value == cpu.sp() => {}
+            SP => return None,
+            DT => cpu.set_delay_timer(value as u8),
+            ST => cpu.set_sound_timer(value as u8),
+            x => cpu.registers_mut()[x] = value as u8,
+        }
+
+        Some(())
+    }
+
+    /// `addr,length`, checked to lie within ram.
+    fn memory_range(args: &str) -> Option<std::ops::Range<usize>> {
+        let (addr, length) = args.split_once(',')?;
+        let addr = usize::from_str_radix(addr, 16).ok()?;
+        let end = addr.checked_add(usize::from_str_radix(length, 16).ok()?)?;
+
+        (end <= RAM_SIZE).then_some(addr..end)
+    }
+
+    fn read_memory(cpu: &Cpu, args: &str) -> Option<String> {
+        let range = Self::memory_range(args)?;
+        Some(
+            cpu.ram()[range]
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect(),
+        )
+    }
+
+    fn write_memory(cpu: &mut Cpu, args: &str) -> Option<()> {
+        let (range, data) = args.split_once(':')?;
+        let range = Self::memory_range(range)?;
+
+        if data.len() != range.len() * 2 {
+            return None;
+        }
+
+        let bytes = (0..range.len())
+            .map(|index| u8::from_str_radix(&data[index * 2..index * 2 + 2], 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+
+        cpu.ram_mut()[range].copy_from_slice(&bytes);
+        Some(())
+    }
+
+    /// The address of a software or hardware breakpoint, `type,addr,kind`.
+    fn breakpoint(args: &str) -> Option<usize> {
+        let mut fields = args.split(',');
+
+        match fields.next()? {
+            "0" | "1" => usize::from_str_radix(fields.next()?, 16).ok(),
+            _ => None,
+        }
+    }
+
+    /// The stop reply for a fault, as the signal a real CPU would raise.
+    fn fault_signal(fault: CpuFault) -> String {
+        match fault {
+            CpuFault::UnknownOpcode { .. } => String::from("S04"),
+            _ => String::from("S0b"),
+        }
+    }
+
+    fn send(&mut self, reply: &str) -> io::Result<()> {
+        let packet = format!("${reply}#{:02x}", Self::checksum(reply.as_bytes()));
+        self.write(packet.as_bytes())
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let Some(client) = self.client.as_mut() else {
+            return Ok(());
+        };
+
+        /* replies are small, so wait out a full socket buffer rather than drop part of one */
+        client.set_nonblocking(false)?;
+        let result = client.write_all(bytes);
+        client.set_nonblocking(true)?;
+
+        result
+    }
+
+    fn disconnect(&mut self, cpu: &Cpu, debugger: &mut Debugger) {
+        self.client = None;
+        self.buffer.clear();
+        self.running = false;
+        debugger.resume(cpu);
+    }
+
+    fn checksum(bytes: &[u8]) -> u8 {
+        bytes.iter().fold(0, |sum, &byte| sum.wrapping_add(byte))
+    }
+}
+
+enum Packet {
+    /// Ctrl-C sent on its own, outside of a packet.
+    Interrupt,
+    Command(String),
+}
+
+fn ok_or_error(result: Option<()>) -> String {
+    match result {
+        Some(()) => String::from("OK"),
+        None => String::from("E01"),
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/gdb/gdb_tests.rs"]
+mod gdb_tests;
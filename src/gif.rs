@@ -0,0 +1,214 @@
+use crate::{
+    constants::{HIRES_VRAM_HEIGHT, HIRES_VRAM_WIDTH, TIMER_HZ},
+    framebuffer::{Framebuffer, Palette},
+};
+use std::collections::HashMap;
+
+/// Codes hold at most 12 bits, so the LZW table starts over once it has this many entries.
+const MAX_CODES: u16 = 1 << 12;
+
+/// Two bits a pixel, one for each plane.
+const MIN_CODE_SIZE: u8 = 2;
+const CLEAR_CODE: u16 = 1 << MIN_CODE_SIZE;
+const END_CODE: u16 = CLEAR_CODE + 1;
+
+/// Data sub-blocks hold at most 255 bytes.
+const SUB_BLOCK_SIZE: usize = 255;
+
+/// Records the screen once a frame into an animated GIF in the rom's palette.
+///
+/// Frames are always written at the high resolution size, with low resolution pixels doubled, so
+/// a rom that switches mode keeps the same image size. A run of identical frames becomes a
+/// single frame shown for longer.
+pub struct GifRecorder {
+    scale: usize,
+    gif: Vec<u8>,
+    /// The last frame, not written until the next different one shows how long it lasted.
+    pending: Option<Framebuffer>,
+    pending_start: usize,
+    frames: usize,
+}
+
+impl GifRecorder {
+    /// A recording `scale` image pixels for each high resolution pixel.
+    pub fn new(scale: usize, palette: &Palette) -> Self {
+        let scale = scale.max(1);
+        let (width, height) = (HIRES_VRAM_WIDTH * scale, HIRES_VRAM_HEIGHT * scale);
+
+        let mut gif = b"GIF89a".to_vec();
+        gif.extend((width as u16).to_le_bytes());
+        gif.extend((height as u16).to_le_bytes());
+        /* a global color table of 4 entries, with the background the first */
+        gif.extend([0b1000_0001, 0, 0]);
+        gif.extend(palette.as_flattened());
+
+        /* the NETSCAPE2.0 extension, looping forever */
+        gif.extend([0x21, 0xFF, 11]);
+        gif.extend(b"NETSCAPE2.0");
+        gif.extend([3, 1, 0, 0, 0]);
+
+        Self {
+            scale,
+            gif,
+            pending: None,
+            pending_start: 0,
+            frames: 0,
+        }
+    }
+
+    /// The number of frames pushed so far.
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// Add the screen shown for the next 60th of a second.
+    pub fn push(&mut self, frame: Framebuffer) {
+        if self.pending.as_ref() != Some(&frame) {
+            self.flush();
+            self.pending = Some(frame);
+            self.pending_start = self.frames;
+        }
+
+        self.frames += 1;
+    }
+
+    /// The whole GIF, holding every frame pushed.
+    pub fn finish(mut self) -> Vec<u8> {
+        self.flush();
+        self.gif.push(0x3B);
+        self.gif
+    }
+
+    fn flush(&mut self) {
+        let Some(frame) = self.pending.take() else {
+            return;
+        };
+
+        /* GIF delays are in hundredths of a second, so 60 Hz frames are rounded against the run's
+        start to keep the clip from drifting */
+        let centiseconds = |frames: usize| (frames as u128 * 100 + TIMER_HZ / 2) / TIMER_HZ;
+        let delay = centiseconds(self.frames) - centiseconds(self.pending_start);
+
+        /* the graphic control extension, with the delay and no disposal */
+        self.gif.extend([0x21, 0xF9, 4, 0b0000_0100]);
+        self.gif
+            .extend((delay.min(u16::MAX as u128) as u16).to_le_bytes());
+        self.gif.extend([0, 0]);
+
+        /* an image descriptor covering the whole screen, using the global color table */
+        let (width, height) = (
+            HIRES_VRAM_WIDTH * self.scale,
+            HIRES_VRAM_HEIGHT * self.scale,
+        );
+        self.gif.push(0x2C);
+        self.gif.extend([0, 0, 0, 0]);
+        self.gif.extend((width as u16).to_le_bytes());
+        self.gif.extend((height as u16).to_le_bytes());
+        self.gif.push(0);
+
+        let scale = self.scale * HIRES_VRAM_WIDTH / frame.width();
+        let indices = (0..height).flat_map(|y| {
+            let frame = &frame;
+            (0..width).map(move |x| frame.pixel(x / scale, y / scale) & 0b11)
+        });
+
+        self.gif.push(MIN_CODE_SIZE);
+
+        for block in lzw(indices).chunks(SUB_BLOCK_SIZE) {
+            self.gif.push(block.len() as u8);
+            self.gif.extend(block);
+        }
+
+        self.gif.push(0);
+    }
+}
+
+/// Variable width codes packed least significant bit first.
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: u32,
+    len: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.bits |= (code as u32) << self.len;
+        self.len += size;
+
+        while self.len >= 8 {
+            self.bytes.push(self.bits as u8);
+            self.bits >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.bits as u8);
+        }
+
+        self.bytes
+    }
+}
+
+/// GIF's LZW compression of color indices below 4.
+fn lzw(indices: impl IntoIterator<Item = u8>) -> Vec<u8> {
+    let mut writer = BitWriter {
+        bytes: Vec::new(),
+        bits: 0,
+        len: 0,
+    };
+
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next_code = END_CODE + 1;
+    let mut code_size = MIN_CODE_SIZE + 1;
+    let mut prefix: Option<u16> = None;
+
+    writer.write(CLEAR_CODE, code_size);
+
+    for index in indices {
+        let Some(code) = prefix else {
+            prefix = Some(index as u16);
+            continue;
+        };
+
+        if let Some(&longer) = table.get(&(code, index)) {
+            prefix = Some(longer);
+            continue;
+        }
+
+        writer.write(code, code_size);
+        table.insert((code, index), next_code);
+        next_code += 1;
+
+        /* the decoder adds its entries a code behind, so widens a code later */
+        if next_code > 1 << code_size {
+            code_size += 1;
+        }
+
+        if next_code == MAX_CODES {
+            writer.write(CLEAR_CODE, code_size);
+            table.clear();
+            next_code = END_CODE + 1;
+            code_size = MIN_CODE_SIZE + 1;
+        }
+
+        prefix = Some(index as u16);
+    }
+
+    if let Some(code) = prefix {
+        writer.write(code, code_size);
+
+        /* the decoder catches up on the last code before reading the end */
+        if next_code == 1 << code_size && next_code < MAX_CODES {
+            code_size += 1;
+        }
+    }
+
+    writer.write(END_CODE, code_size);
+    writer.finish()
+}
+
+#[cfg(test)]
+#[path = "../tests/gif/gif_tests.rs"]
+mod gif_tests;
@@ -0,0 +1,419 @@
+use std::fmt;
+
+/// A decoded opcode, covering CHIP-8, SUPER-CHIP 1.1 and XO-CHIP.
+///
+/// Register indexes (`x`, `y`), nibbles (`n`) and addresses (`nnn`) are kept as `usize` so they
+/// index straight into ram and registers. Decoding never fails: opcodes that aren't an
+/// instruction become [`Instruction::Unknown`], so every opcode decodes and encodes back to
+/// itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    /// `00Cn` SCD nibble
+    ScrollDown(usize),
+    /// `00Dn` SCU nibble
+    ScrollUp(usize),
+    /// `00E0` CLS
+    Clear,
+    /// `00EE` RET
+    Return,
+    /// `00FB` SCR
+    ScrollRight,
+    /// `00FC` SCL
+    ScrollLeft,
+    /// `00FD` EXIT
+    Exit,
+    /// `00FE` LOW
+    LowRes,
+    /// `00FF` HIGH
+    HighRes,
+    /// `0nnn` SYS addr, ignored by modern interpreters
+    Sys(usize),
+    /// `1nnn` JP addr
+    Jump(usize),
+    /// `2nnn` CALL addr
+    Call(usize),
+    /// `3xkk` SE Vx, byte
+    SkipEqualByte(usize, u8),
+    /// `4xkk` SNE Vx, byte
+    SkipNotEqualByte(usize, u8),
+    /// `5xy0` SE Vx, Vy
+    SkipEqual(usize, usize),
+    /// `5xy2` SAVE Vx - Vy
+    Save(usize, usize),
+    /// `5xy3` LOAD Vx - Vy
+    Load(usize, usize),
+    /// `6xkk` LD Vx, byte
+    LoadByte(usize, u8),
+    /// `7xkk` ADD Vx, byte
+    AddByte(usize, u8),
+    /// `8xy0` LD Vx, Vy
+    Move(usize, usize),
+    /// `8xy1` OR Vx, Vy
+    Or(usize, usize),
+    /// `8xy2` AND Vx, Vy
+    And(usize, usize),
+    /// `8xy3` XOR Vx, Vy
+    Xor(usize, usize),
+    /// `8xy4` ADD Vx, Vy
+    Add(usize, usize),
+    /// `8xy5` SUB Vx, Vy
+    Sub(usize, usize),
+    /// `8xy6` SHR Vx {, Vy}
+    ShiftRight(usize, usize),
+    /// `8xy7` SUBN Vx, Vy
+    SubNegated(usize, usize),
+    /// `8xyE` SHL Vx {, Vy}
+    ShiftLeft(usize, usize),
+    /// `9xy0` SNE Vx, Vy
+    SkipNotEqual(usize, usize),
+    /// `Annn` LD I, addr
+    LoadI(usize),
+    /// `Bnnn` JP V0, addr
+    ///
+    /// The high nibble of the address is kept, since [`Quirks::jump_uses_vx`] reads it as x.
+    ///
+    /// [`Quirks::jump_uses_vx`]: crate::quirks::Quirks::jump_uses_vx
+    JumpOffset(usize),
+    /// `Cxkk` RND Vx, byte
+    Random(usize, u8),
+    /// `Dxyn` DRW Vx, Vy, nibble
+    Draw(usize, usize, usize),
+    /// `Ex9E` SKP Vx
+    SkipKey(usize),
+    /// `ExA1` SKNP Vx
+    SkipNotKey(usize),
+    /// `F000` LD I, long addr
+    ///
+    /// The address is the word following the opcode, so it isn't part of the instruction.
+    LoadILong,
+    /// `Fn01` PLANE n
+    Plane(usize),
+    /// `F002` AUDIO
+    Audio,
+    /// `Fx07` LD Vx, DT
+    LoadDelay(usize),
+    /// `Fx0A` LD Vx, K
+    WaitKey(usize),
+    /// `Fx15` LD DT, Vx
+    SetDelay(usize),
+    /// `Fx18` LD ST, Vx
+    SetSound(usize),
+    /// `Fx1E` ADD I, Vx
+    AddI(usize),
+    /// `Fx29` LD F, Vx
+    LoadFont(usize),
+    /// `Fx30` LD HF, Vx
+    LoadBigFont(usize),
+    /// `Fx33` LD B, Vx
+    StoreBcd(usize),
+    /// `Fx3A` PITCH Vx
+    Pitch(usize),
+    /// `Fx55` LD [I], Vx
+    StoreRegisters(usize),
+    /// `Fx65` LD Vx, [I]
+    LoadRegisters(usize),
+    /// `Fx75` LD R, Vx
+    StoreFlags(usize),
+    /// `Fx85` LD Vx, R
+    LoadFlags(usize),
+    /// Any opcode that isn't one of the above.
+    Unknown(u16),
+}
+
+impl Instruction {
+    pub fn decode(opcode: u16) -> Self {
+        let nibbles = (
+            (opcode & 0xF000) >> 12,
+            (opcode & 0x0F00) >> 8,
+            (opcode & 0x00F0) >> 4,
+            (opcode & 0x000F),
+        );
+
+        let nnn = (opcode & 0x0FFF) as usize;
+        let kk = (opcode & 0x00FF) as u8;
+        let x = nibbles.1 as usize;
+        let y = nibbles.2 as usize;
+        let n = nibbles.3 as usize;
+
+        match nibbles {
+            (0x0, 0x0, 0xC, _) => Self::ScrollDown(n),
+            (0x0, 0x0, 0xD, _) => Self::ScrollUp(n),
+            (0x0, 0x0, 0xE, 0x0) => Self::Clear,
+            (0x0, 0x0, 0xE, 0xE) => Self::Return,
+            (0x0, 0x0, 0xF, 0xB) => Self::ScrollRight,
+            (0x0, 0x0, 0xF, 0xC) => Self::ScrollLeft,
+            (0x0, 0x0, 0xF, 0xD) => Self::Exit,
+            (0x0, 0x0, 0xF, 0xE) => Self::LowRes,
+            (0x0, 0x0, 0xF, 0xF) => Self::HighRes,
+            (0x0, _, _, _) => Self::Sys(nnn),
+            (0x1, _, _, _) => Self::Jump(nnn),
+            (0x2, _, _, _) => Self::Call(nnn),
+            (0x3, _, _, _) => Self::SkipEqualByte(x, kk),
+            (0x4, _, _, _) => Self::SkipNotEqualByte(x, kk),
+            (0x5, _, _, 0x0) => Self::SkipEqual(x, y),
+            (0x5, _, _, 0x2) => Self::Save(x, y),
+            (0x5, _, _, 0x3) => Self::Load(x, y),
+            (0x6, _, _, _) => Self::LoadByte(x, kk),
+            (0x7, _, _, _) => Self::AddByte(x, kk),
+            (0x8, _, _, 0x0) => Self::Move(x, y),
+            (0x8, _, _, 0x1) => Self::Or(x, y),
+            (0x8, _, _, 0x2) => Self::And(x, y),
+            (0x8, _, _, 0x3) => Self::Xor(x, y),
+            (0x8, _, _, 0x4) => Self::Add(x, y),
+            (0x8, _, _, 0x5) => Self::Sub(x, y),
+            (0x8, _, _, 0x6) => Self::ShiftRight(x, y),
+            (0x8, _, _, 0x7) => Self::SubNegated(x, y),
+            (0x8, _, _, 0xE) => Self::ShiftLeft(x, y),
+            (0x9, _, _, 0x0) => Self::SkipNotEqual(x, y),
+            (0xA, _, _, _) => Self::LoadI(nnn),
+            (0xB, _, _, _) => Self::JumpOffset(nnn),
+            (0xC, _, _, _) => Self::Random(x, kk),
+            (0xD, _, _, _) => Self::Draw(x, y, n),
+            (0xE, _, 0x9, 0xE) => Self::SkipKey(x),
+            (0xE, _, 0xA, 0x1) => Self::SkipNotKey(x),
+            (0xF, 0x0, 0x0, 0x0) => Self::LoadILong,
+            (0xF, _, 0x0, 0x1) => Self::Plane(x),
+            (0xF, 0x0, 0x0, 0x2) => Self::Audio,
+            (0xF, _, 0x0, 0x7) => Self::LoadDelay(x),
+            (0xF, _, 0x0, 0xA) => Self::WaitKey(x),
+            (0xF, _, 0x1, 0x5) => Self::SetDelay(x),
+            (0xF, _, 0x1, 0x8) => Self::SetSound(x),
+            (0xF, _, 0x1, 0xE) => Self::AddI(x),
+            (0xF, _, 0x2, 0x9) => Self::LoadFont(x),
+            (0xF, _, 0x3, 0x0) => Self::LoadBigFont(x),
+            (0xF, _, 0x3, 0x3) => Self::StoreBcd(x),
+            (0xF, _, 0x3, 0xA) => Self::Pitch(x),
+            (0xF, _, 0x5, 0x5) => Self::StoreRegisters(x),
+            (0xF, _, 0x6, 0x5) => Self::LoadRegisters(x),
+            (0xF, _, 0x7, 0x5) => Self::StoreFlags(x),
+            (0xF, _, 0x8, 0x5) => Self::LoadFlags(x),
+            _ => Self::Unknown(opcode),
+        }
+    }
+
+    /// The opcode this instruction decodes from.
+    ///
+    /// Fields wider than their slot in the opcode are truncated to fit.
+    pub fn encode(&self) -> u16 {
+        let xy = |op: u16, x: usize, y: usize, n: u16| {
+            op << 12 | (x as u16 & 0xF) << 8 | (y as u16 & 0xF) << 4 | n
+        };
+        let xkk = |op: u16, x: usize, kk: u8| op << 12 | (x as u16 & 0xF) << 8 | kk as u16;
+        let nnn = |op: u16, nnn: usize| op << 12 | (nnn as u16 & 0x0FFF);
+        let fx = |x: usize, low: u16| 0xF000 | (x as u16 & 0xF) << 8 | low;
+
+        match *self {
+            Self::ScrollDown(n) => 0x00C0 | (n as u16 & 0xF),
+            Self::ScrollUp(n) => 0x00D0 | (n as u16 & 0xF),
+            Self::Clear => 0x00E0,
+            Self::Return => 0x00EE,
+            Self::ScrollRight => 0x00FB,
+            Self::ScrollLeft => 0x00FC,
+            Self::Exit => 0x00FD,
+            Self::LowRes => 0x00FE,
+            Self::HighRes => 0x00FF,
+            Self::Sys(addr) => nnn(0x0, addr),
+            Self::Jump(addr) => nnn(0x1, addr),
+            Self::Call(addr) => nnn(0x2, addr),
+            Self::SkipEqualByte(x, kk) => xkk(0x3, x, kk),
+            Self::SkipNotEqualByte(x, kk) => xkk(0x4, x, kk),
+            Self::SkipEqual(x, y) => xy(0x5, x, y, 0x0),
+            Self::Save(x, y) => xy(0x5, x, y, 0x2),
+            Self::Load(x, y) => xy(0x5, x, y, 0x3),
+            Self::LoadByte(x, kk) => xkk(0x6, x, kk),
+            Self::AddByte(x, kk) => xkk(0x7, x, kk),
+            Self::Move(x, y) => xy(0x8, x, y, 0x0),
+            Self::Or(x, y) => xy(0x8, x, y, 0x1),
+            Self::And(x, y) => xy(0x8, x, y, 0x2),
+            Self::Xor(x, y) => xy(0x8, x, y, 0x3),
+            Self::Add(x, y) => xy(0x8, x, y, 0x4),
+            Self::Sub(x, y) => xy(0x8, x, y, 0x5),
+            Self::ShiftRight(x, y) => xy(0x8, x, y, 0x6),
+            Self::SubNegated(x, y) => xy(0x8, x, y, 0x7),
+            Self::ShiftLeft(x, y) => xy(0x8, x, y, 0xE),
+            Self::SkipNotEqual(x, y) => xy(0x9, x, y, 0x0),
+            Self::LoadI(addr) => nnn(0xA, addr),
+            Self::JumpOffset(addr) => nnn(0xB, addr),
+            Self::Random(x, kk) => xkk(0xC, x, kk),
+            Self::Draw(x, y, n) => xy(0xD, x, y, n as u16 & 0xF),
+            Self::SkipKey(x) => 0xE09E | (x as u16 & 0xF) << 8,
+            Self::SkipNotKey(x) => 0xE0A1 | (x as u16 & 0xF) << 8,
+            Self::LoadILong => 0xF000,
+            Self::Plane(n) => fx(n, 0x01),
+            Self::Audio => 0xF002,
+            Self::LoadDelay(x) => fx(x, 0x07),
+            Self::WaitKey(x) => fx(x, 0x0A),
+            Self::SetDelay(x) => fx(x, 0x15),
+            Self::SetSound(x) => fx(x, 0x18),
+            Self::AddI(x) => fx(x, 0x1E),
+            Self::LoadFont(x) => fx(x, 0x29),
+            Self::LoadBigFont(x) => fx(x, 0x30),
+            Self::StoreBcd(x) => fx(x, 0x33),
+            Self::Pitch(x) => fx(x, 0x3A),
+            Self::StoreRegisters(x) => fx(x, 0x55),
+            Self::LoadRegisters(x) => fx(x, 0x65),
+            Self::StoreFlags(x) => fx(x, 0x75),
+            Self::LoadFlags(x) => fx(x, 0x85),
+            Self::Unknown(opcode) => opcode,
+        }
+    }
+
+    /// The opcode pattern of this kind of instruction, such as `6xkk`, the same whatever its operands.
+    pub fn pattern(&self) -> &'static str {
+        match self {
+            Self::ScrollDown(_) => "00Cn",
+            Self::ScrollUp(_) => "00Dn",
+            Self::Clear => "00E0",
+            Self::Return => "00EE",
+            Self::ScrollRight => "00FB",
+            Self::ScrollLeft => "00FC",
+            Self::Exit => "00FD",
+            Self::LowRes => "00FE",
+            Self::HighRes => "00FF",
+            Self::Sys(_) => "0nnn",
+            Self::Jump(_) => "1nnn",
+            Self::Call(_) => "2nnn",
+            Self::SkipEqualByte(..) => "3xkk",
+            Self::SkipNotEqualByte(..) => "4xkk",
+            Self::SkipEqual(..) => "5xy0",
+            Self::Save(..) => "5xy2",
+            Self::Load(..) => "5xy3",
+            Self::LoadByte(..) => "6xkk",
+            Self::AddByte(..) => "7xkk",
+            Self::Move(..) => "8xy0",
+            Self::Or(..) => "8xy1",
+            Self::And(..) => "8xy2",
+            Self::Xor(..) => "8xy3",
+            Self::Add(..) => "8xy4",
+            Self::Sub(..) => "8xy5",
+            Self::ShiftRight(..) => "8xy6",
+            Self::SubNegated(..) => "8xy7",
+            Self::ShiftLeft(..) => "8xyE",
+            Self::SkipNotEqual(..) => "9xy0",
+            Self::LoadI(_) => "Annn",
+            Self::JumpOffset(_) => "Bnnn",
+            Self::Random(..) => "Cxkk",
+            Self::Draw(..) => "Dxyn",
+            Self::SkipKey(_) => "Ex9E",
+            Self::SkipNotKey(_) => "ExA1",
+            Self::LoadILong => "F000",
+            Self::Plane(_) => "Fn01",
+            Self::Audio => "F002",
+            Self::LoadDelay(_) => "Fx07",
+            Self::WaitKey(_) => "Fx0A",
+            Self::SetDelay(_) => "Fx15",
+            Self::SetSound(_) => "Fx18",
+            Self::AddI(_) => "Fx1E",
+            Self::LoadFont(_) => "Fx29",
+            Self::LoadBigFont(_) => "Fx30",
+            Self::StoreBcd(_) => "Fx33",
+            Self::Pitch(_) => "Fx3A",
+            Self::StoreRegisters(_) => "Fx55",
+            Self::LoadRegisters(_) => "Fx65",
+            Self::StoreFlags(_) => "Fx75",
+            Self::LoadFlags(_) => "Fx85",
+            Self::Unknown(_) => "????",
+        }
+    }
+
+    /// The address this instruction jumps to, calls or points I at, if it has one in its opcode.
+    pub fn target(&self) -> Option<usize> {
+        match *self {
+            Self::Jump(addr) | Self::Call(addr) | Self::LoadI(addr) | Self::JumpOffset(addr) => {
+                Some(addr)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the next instruction may be skipped.
+    pub fn is_skip(&self) -> bool {
+        matches!(
+            self,
+            Self::SkipEqualByte(..)
+                | Self::SkipNotEqualByte(..)
+                | Self::SkipEqual(..)
+                | Self::SkipNotEqual(..)
+                | Self::SkipKey(..)
+                | Self::SkipNotKey(..)
+        )
+    }
+}
+
+impl From<u16> for Instruction {
+    fn from(opcode: u16) -> Self {
+        Self::decode(opcode)
+    }
+}
+
+impl From<Instruction> for u16 {
+    fn from(instruction: Instruction) -> Self {
+        instruction.encode()
+    }
+}
+
+/// Cowgod style mnemonics, with addresses as raw hex.
+///
+/// The address of `LD I, long addr` is in the following word, so it is shown as `LD I, long`.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::ScrollDown(n) => write!(f, "SCD {n}"),
+            Self::ScrollUp(n) => write!(f, "SCU {n}"),
+            Self::Clear => write!(f, "CLS"),
+            Self::Return => write!(f, "RET"),
+            Self::ScrollRight => write!(f, "SCR"),
+            Self::ScrollLeft => write!(f, "SCL"),
+            Self::Exit => write!(f, "EXIT"),
+            Self::LowRes => write!(f, "LOW"),
+            Self::HighRes => write!(f, "HIGH"),
+            Self::Sys(addr) => write!(f, "SYS #{addr:03X}"),
+            Self::Jump(addr) => write!(f, "JP #{addr:03X}"),
+            Self::Call(addr) => write!(f, "CALL #{addr:03X}"),
+            Self::SkipEqualByte(x, kk) => write!(f, "SE V{x:X}, #{kk:02X}"),
+            Self::SkipNotEqualByte(x, kk) => write!(f, "SNE V{x:X}, #{kk:02X}"),
+            Self::SkipEqual(x, y) => write!(f, "SE V{x:X}, V{y:X}"),
+            Self::Save(x, y) => write!(f, "SAVE V{x:X} - V{y:X}"),
+            Self::Load(x, y) => write!(f, "LOAD V{x:X} - V{y:X}"),
+            Self::LoadByte(x, kk) => write!(f, "LD V{x:X}, #{kk:02X}"),
+            Self::AddByte(x, kk) => write!(f, "ADD V{x:X}, #{kk:02X}"),
+            Self::Move(x, y) => write!(f, "LD V{x:X}, V{y:X}"),
+            Self::Or(x, y) => write!(f, "OR V{x:X}, V{y:X}"),
+            Self::And(x, y) => write!(f, "AND V{x:X}, V{y:X}"),
+            Self::Xor(x, y) => write!(f, "XOR V{x:X}, V{y:X}"),
+            Self::Add(x, y) => write!(f, "ADD V{x:X}, V{y:X}"),
+            Self::Sub(x, y) => write!(f, "SUB V{x:X}, V{y:X}"),
+            Self::ShiftRight(x, y) => write!(f, "SHR V{x:X} {{, V{y:X}}}"),
+            Self::SubNegated(x, y) => write!(f, "SUBN V{x:X}, V{y:X}"),
+            Self::ShiftLeft(x, y) => write!(f, "SHL V{x:X} {{, V{y:X}}}"),
+            Self::SkipNotEqual(x, y) => write!(f, "SNE V{x:X}, V{y:X}"),
+            Self::LoadI(addr) => write!(f, "LD I, #{addr:03X}"),
+            Self::JumpOffset(addr) => write!(f, "JP V0, #{addr:03X}"),
+            Self::Random(x, kk) => write!(f, "RND V{x:X}, #{kk:02X}"),
+            Self::Draw(x, y, n) => write!(f, "DRW V{x:X}, V{y:X}, {n}"),
+            Self::SkipKey(x) => write!(f, "SKP V{x:X}"),
+            Self::SkipNotKey(x) => write!(f, "SKNP V{x:X}"),
+            Self::LoadILong => write!(f, "LD I, long"),
+            Self::Plane(n) => write!(f, "PLANE {n}"),
+            Self::Audio => write!(f, "AUDIO"),
+            Self::LoadDelay(x) => write!(f, "LD V{x:X}, DT"),
+            Self::WaitKey(x) => write!(f, "LD V{x:X}, K"),
+            Self::SetDelay(x) => write!(f, "LD DT, V{x:X}"),
+            Self::SetSound(x) => write!(f, "LD ST, V{x:X}"),
+            Self::AddI(x) => write!(f, "ADD I, V{x:X}"),
+            Self::LoadFont(x) => write!(f, "LD F, V{x:X}"),
+            Self::LoadBigFont(x) => write!(f, "LD HF, V{x:X}"),
+            Self::StoreBcd(x) => write!(f, "LD B, V{x:X}"),
+            Self::Pitch(x) => write!(f, "PITCH V{x:X}"),
+            Self::StoreRegisters(x) => write!(f, "LD [I], V{x:X}"),
+            Self::LoadRegisters(x) => write!(f, "LD V{x:X}, [I]"),
+            Self::StoreFlags(x) => write!(f, "LD R, V{x:X}"),
+            Self::LoadFlags(x) => write!(f, "LD V{x:X}, R"),
+            Self::Unknown(opcode) => write!(f, "DW #{opcode:04X}"),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/instruction/instruction_tests.rs"]
+mod instruction_tests;
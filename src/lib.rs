@@ -0,0 +1,51 @@
+pub mod buzzer;
+pub mod cheat;
+pub mod conformance;
+pub mod constants;
+pub mod coverage;
+pub mod cpu;
+pub mod debugger;
+pub mod disasm;
+pub mod fault;
+pub mod framebuffer;
+pub mod gdb;
+pub mod gif;
+pub mod instruction;
+pub mod movie;
+pub mod profiler;
+pub mod quirks;
+pub mod random;
+pub mod rewind;
+pub mod state;
+pub mod timer;
+pub mod trace;
+pub mod wav;
+
+#[cfg(feature = "frontend")]
+pub mod machine;
+#[cfg(feature = "frontend")]
+pub mod rom;
+#[cfg(feature = "frontend")]
+pub mod types;
+#[cfg(feature = "frontend")]
+pub mod utils;
+
+pub use buzzer::Buzzer;
+pub use cheat::{Cheat, CheatList, CheatSearch};
+pub use coverage::Coverage;
+pub use cpu::{Cpu, CpuOutput};
+pub use debugger::Debugger;
+pub use fault::CpuFault;
+pub use framebuffer::{FrameDiff, Framebuffer};
+pub use gdb::GdbStub;
+pub use gif::GifRecorder;
+pub use instruction::Instruction;
+pub use movie::{Movie, MovieError};
+pub use profiler::Profiler;
+pub use quirks::Quirks;
+pub use random::{Random, RandomMode};
+pub use rewind::Rewind;
+pub use state::{CpuState, StateError};
+pub use timer::{Clock, ScaledClock, SystemClock, VirtualClock};
+pub use trace::Tracer;
+pub use wav::WavRecorder;
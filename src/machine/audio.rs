@@ -1,63 +1,116 @@
-use crate::types::Result;
+use crate::{
+    buzzer::{Buzzer, BuzzerSettings},
+    constants::{AUDIO_PATTERN_SIZE, SAMPLE_RATE},
+    types::Result,
+    wav::WavRecorder,
+};
 use sdl2::{
-    audio::{AudioCallback, AudioDevice, AudioSpecDesired, AudioStatus},
+    audio::{AudioCallback, AudioDevice, AudioSpecDesired},
     Sdl,
 };
 
-struct SquareWave {
-    phase_inc: f32,
-    phase: f32,
-    volume: f32,
+struct Callback {
+    buzzer: Buzzer,
+    /// Every sample handed to the device while a recording runs.
+    capture: Option<WavRecorder>,
 }
 
-impl AudioCallback for SquareWave {
+impl AudioCallback for Callback {
     type Channel = f32;
 
     fn callback(&mut self, out: &mut [f32]) {
-        // Generate a square wave
-        for x in out.iter_mut() {
-            *x = if self.phase <= 0.5 {
-                self.volume
-            } else {
-                -self.volume
-            };
-            self.phase = (self.phase + self.phase_inc) % 1.0;
+        self.buzzer.fill(out);
+
+        if let Some(capture) = self.capture.as_mut() {
+            capture.push(out);
         }
     }
 }
 
+/// The audio device, kept running so silence is recorded too. Pausing only silences the buzzer.
 pub struct Audio {
-    device: AudioDevice<SquareWave>,
+    device: AudioDevice<Callback>,
+    pattern: Option<[u8; AUDIO_PATTERN_SIZE]>,
+    pitch: u8,
+    beeping: bool,
+    settings: BuzzerSettings,
 }
 
 impl Audio {
-    pub fn init(context: &Sdl) -> Result<Self> {
+    pub fn init(context: &Sdl, settings: BuzzerSettings) -> Result<Self> {
         let audio = context.audio()?;
 
         let desired_spec = AudioSpecDesired {
-            freq: Some(44100),
+            freq: Some(SAMPLE_RATE as i32),
             channels: Some(1),
             samples: None,
         };
 
-        let device = audio.open_playback(None, &desired_spec, |spec| SquareWave {
-            phase_inc: 440.0 / spec.freq as f32,
-            phase: 0.0,
-            volume: 0.25,
+        let device = audio.open_playback(None, &desired_spec, |spec| {
+            let mut buzzer = Buzzer::new(spec.freq as u32);
+            buzzer.set_settings(settings);
+
+            Callback {
+                buzzer,
+                capture: None,
+            }
         })?;
 
-        Ok(Self { device })
+        device.resume();
+
+        Ok(Self {
+            device,
+            pattern: None,
+            pitch: 0,
+            beeping: false,
+            settings,
+        })
+    }
+
+    /// Play an XO-CHIP audio pattern at the given pitch instead of the square wave.
+    pub fn set_pattern(&mut self, pattern: Option<[u8; AUDIO_PATTERN_SIZE]>, pitch: u8) {
+        if pattern == self.pattern && pitch == self.pitch {
+            return;
+        }
+
+        self.pattern = pattern;
+        self.pitch = pitch;
+        self.device.lock().buzzer.set_pattern(pattern, pitch);
+    }
+
+    pub fn settings(&self) -> BuzzerSettings {
+        self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: BuzzerSettings) {
+        self.settings = settings;
+        self.device.lock().buzzer.set_settings(settings);
+    }
+
+    pub fn play(&mut self) {
+        self.set_beeping(true);
     }
 
-    pub fn play(&self) {
-        if self.device.status() == AudioStatus::Stopped
-            || self.device.status() == AudioStatus::Paused
-        {
-            self.device.resume();
+    pub fn pause(&mut self) {
+        self.set_beeping(false);
+    }
+
+    fn set_beeping(&mut self, beeping: bool) {
+        if beeping != self.beeping {
+            self.beeping = beeping;
+            self.device.lock().buzzer.set_beeping(beeping);
         }
     }
 
-    pub fn pause(&self) {
-        self.device.pause();
+    /// Start keeping the samples played, for a WAV recording.
+    pub fn start_capture(&mut self) {
+        let mut callback = self.device.lock();
+        let sample_rate = callback.buzzer.sample_rate();
+        callback.capture = Some(WavRecorder::new(sample_rate));
+    }
+
+    /// The samples played since the capture started.
+    pub fn finish_capture(&mut self) -> Option<WavRecorder> {
+        self.device.lock().capture.take()
     }
 }
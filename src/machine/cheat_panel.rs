@@ -0,0 +1,190 @@
+use super::debug_window::DebugAction;
+use crate::{
+    cheat::{Cheat, CheatList, CheatSearch, Location, SearchFilter},
+    cpu::Cpu,
+};
+use imgui::{ListClipper, Ui};
+
+/// Memory search and the cheat list for the rom, drawn in the debugger.
+pub struct CheatPanel {
+    search: Option<CheatSearch>,
+    equal_input: String,
+    selected: Option<Location>,
+    name_input: String,
+}
+
+impl CheatPanel {
+    pub fn new() -> Self {
+        Self {
+            search: None,
+            equal_input: String::new(),
+            selected: None,
+            name_input: String::new(),
+        }
+    }
+
+    pub fn draw(&mut self, ui: &Ui, cpu: &Cpu, cheats: &CheatList, actions: &mut Vec<DebugAction>) {
+        self.draw_search(ui, cpu, actions);
+
+        ui.separator();
+        ui.text("Cheats");
+
+        if cheats.cheats.is_empty() {
+            ui.text_disabled("none");
+        }
+
+        for (index, cheat) in cheats.cheats.iter().enumerate() {
+            let _id = ui.push_id_usize(index);
+
+            let mut frozen = cheat.frozen;
+            if ui.checkbox("##frozen", &mut frozen) {
+                actions.push(DebugAction::UpdateCheat(
+                    index,
+                    Cheat {
+                        frozen,
+                        ..cheat.clone()
+                    },
+                ));
+            }
+
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Freeze every frame");
+            }
+
+            ui.same_line();
+            ui.text(format!(
+                "{} {}: {:02X}",
+                cheat.location,
+                cheat.name,
+                cheat.location.read(cpu)
+            ));
+
+            ui.same_line();
+            ui.set_next_item_width(30.0);
+            let mut value = cheat.value;
+            if ui
+                .input_scalar("##value", &mut value)
+                .display_format("%02X")
+                .build()
+            {
+                actions.push(DebugAction::UpdateCheat(
+                    index,
+                    Cheat {
+                        value,
+                        ..cheat.clone()
+                    },
+                ));
+            }
+
+            ui.same_line();
+            if ui.button("Poke") {
+                actions.push(DebugAction::PokeCheat(index));
+            }
+
+            ui.same_line();
+            if ui.button("Remove") {
+                actions.push(DebugAction::RemoveCheat(index));
+            }
+        }
+    }
+
+    fn draw_search(&mut self, ui: &Ui, cpu: &Cpu, actions: &mut Vec<DebugAction>) {
+        if ui.button("New search") {
+            self.search = Some(CheatSearch::new(cpu));
+            self.selected = None;
+        }
+
+        let Some(search) = self.search.as_mut() else {
+            ui.text_disabled("start a search, then narrow it as the game runs");
+            return;
+        };
+
+        ui.same_line();
+        ui.text(format!(
+            "{} left after {} searches",
+            search.candidates().len(),
+            search.searches()
+        ));
+
+        if ui.button("Changed") {
+            search.filter(cpu, SearchFilter::Changed);
+        }
+
+        ui.same_line();
+        if ui.button("Unchanged") {
+            search.filter(cpu, SearchFilter::Unchanged);
+        }
+
+        ui.same_line();
+        if ui.button("Equal to") {
+            if let Ok(value) = u8::from_str_radix(self.equal_input.trim(), 16) {
+                search.filter(cpu, SearchFilter::Equal(value));
+            }
+        }
+
+        ui.same_line();
+        ui.set_next_item_width(30.0);
+        ui.input_text("##equal", &mut self.equal_input)
+            .chars_hexadecimal(true)
+            .build();
+
+        let row_height = ui.text_line_height_with_spacing();
+
+        ui.child_window("candidates")
+            .size([0.0, row_height * 8.0])
+            .border(true)
+            .build(|| {
+                let candidates = search.candidates();
+                let mut clipper = ListClipper::new(candidates.len() as i32)
+                    .items_height(row_height)
+                    .begin(ui);
+
+                while clipper.step() {
+                    for row in clipper.display_start()..clipper.display_end() {
+                        let candidate = candidates[row as usize];
+                        let label = format!(
+                            "{:<5} {:02X} -> {:02X}",
+                            candidate.location.to_string(),
+                            candidate.value,
+                            candidate.location.read(cpu)
+                        );
+
+                        if ui
+                            .selectable_config(label)
+                            .selected(self.selected == Some(candidate.location))
+                            .build()
+                        {
+                            self.selected = Some(candidate.location);
+                        }
+                    }
+                }
+            });
+
+        let Some(location) = self.selected else {
+            ui.text_disabled("pick a location to add it as a cheat");
+            return;
+        };
+
+        ui.set_next_item_width(120.0);
+        ui.input_text("##name", &mut self.name_input)
+            .hint("name")
+            .build();
+
+        ui.same_line();
+        if ui.button(format!("Add {location}")) {
+            let name = match self.name_input.trim() {
+                "" => location.to_string(),
+                name => name.to_string(),
+            };
+
+            actions.push(DebugAction::AddCheat(Cheat {
+                name,
+                location,
+                value: location.read(cpu),
+                frozen: false,
+            }));
+
+            self.name_input.clear();
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use super::{cheat_panel::CheatPanel, display::Display, memory_view::MemoryView};
+use crate::{
+    cheat::{Cheat, CheatList},
+    constants::{DEBUGGER_HEIGHT, DEBUGGER_WIDTH, MEMORY_VIEW_WIDTH, OPCODE_SIZE},
+    cpu::Cpu,
+    debugger::Debugger,
+    instruction::Instruction,
+    profiler::Profiler,
+    types::Result,
+};
+use glow::HasContext;
+use imgui::{Condition, Context, TableFlags, TreeNodeFlags, Ui};
+use imgui_glow_renderer::AutoRenderer;
+use imgui_sdl2_support::SdlPlatform;
+use sdl2::{
+    event::{Event, WindowEvent},
+    video::{GLContext, Window},
+    EventPump, Sdl,
+};
+
+/// Something the player asked the debugger to do, applied by the game loop.
+pub enum DebugAction {
+    Pause,
+    Resume,
+    Step,
+    StepOver,
+    StepOut,
+    ToggleBreakpoint(usize),
+    /// Write a byte to ram.
+    Poke(usize, u8),
+    StartProfiling,
+    StopProfiling,
+    ResetProfile,
+    /// Write the profile out as JSON.
+    ExportProfile,
+    AddCheat(Cheat),
+    /// Replace the cheat at an index in the list.
+    UpdateCheat(usize, Cheat),
+    RemoveCheat(usize),
+    /// Write a cheat's value once, without freezing it.
+    PokeCheat(usize),
+}
+
+/// The debugger and memory panels, drawn with imgui in their own window next to the game.
+pub struct DebugWindow {
+    /* dropped in this order, so the renderer goes before its GL context */
+    renderer: AutoRenderer,
+    platform: SdlPlatform,
+    gl_context: GLContext,
+    window: Window,
+    memory_view: MemoryView,
+    cheat_panel: CheatPanel,
+    breakpoint_input: String,
+    closed: bool,
+}
+
+impl DebugWindow {
+    pub fn open(sdl_context: &Sdl, imgui_context: &mut Context, rom_size: usize) -> Result<Self> {
+        let video_subsystem = sdl_context.video()?;
+
+        let window = video_subsystem
+            .window(
+                "CHIP-8 Debugger",
+                DEBUGGER_WIDTH + MEMORY_VIEW_WIDTH,
+                DEBUGGER_HEIGHT,
+            )
+            .allow_highdpi()
+            .opengl()
+            .build()?;
+
+        let gl_context = window.gl_create_context()?;
+        window.gl_make_current(&gl_context)?;
+
+        /* no vsync here, swapping must not hold up the game loop */
+        video_subsystem.gl_set_swap_interval(0)?;
+
+        let gl = Display::glow_context(&window);
+
+        let platform = SdlPlatform::init(imgui_context);
+        let renderer = AutoRenderer::initialize(gl, imgui_context)?;
+
+        Ok(Self {
+            renderer,
+            platform,
+            gl_context,
+            window,
+            memory_view: MemoryView::new(rom_size),
+            cheat_panel: CheatPanel::new(),
+            breakpoint_input: String::new(),
+            closed: false,
+        })
+    }
+
+    /// Pass an event on to imgui if it belongs to this window.
+    pub fn handle_event(&mut self, imgui_context: &mut Context, event: &Event) {
+        if event.get_window_id() != Some(self.window.id()) {
+            return;
+        }
+
+        if let Event::Window {
+            win_event: WindowEvent::Close,
+            ..
+        } = event
+        {
+            self.closed = true;
+        }
+
+        self.platform.handle_event(imgui_context, event);
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Whether keys are going to this window rather than the game.
+    pub fn has_focus(&self, sdl_context: &Sdl) -> bool {
+        sdl_context.keyboard().focused_window_id() == Some(self.window.id())
+    }
+
+    pub fn draw(
+        &mut self,
+        imgui_context: &mut Context,
+        event_pump: &EventPump,
+        cpu: &Cpu,
+        debugger: &Debugger,
+        cheats: &CheatList,
+    ) -> Result<Vec<DebugAction>> {
+        let mut actions = Vec::new();
+        let breakpoint_input = &mut self.breakpoint_input;
+        let memory_view = &mut self.memory_view;
+        let cheat_panel = &mut self.cheat_panel;
+        let (window_width, window_height) = self.window.size();
+
+        /* the debugger keeps its width and the memory view takes up the rest */
+        let debugger_width = DEBUGGER_WIDTH.min(window_width) as f32;
+
+        self.window.gl_make_current(&self.gl_context)?;
+        self.platform
+            .prepare_frame(imgui_context, &self.window, event_pump);
+
+        let ui = imgui_context.new_frame();
+
+        ui.window("Debugger")
+            .size([debugger_width, window_height as f32], Condition::Always)
+            .position([0.0, 0.0], Condition::Always)
+            .resizable(false)
+            .collapsible(false)
+            .build(|| {
+                if debugger.is_paused() {
+                    if ui.button("Continue") {
+                        actions.push(DebugAction::Resume);
+                    }
+                } else if ui.button("Pause") {
+                    actions.push(DebugAction::Pause);
+                }
+
+                ui.same_line();
+                if ui.button("Step") {
+                    actions.push(DebugAction::Step);
+                }
+
+                ui.same_line();
+                if ui.button("Step Over") {
+                    actions.push(DebugAction::StepOver);
+                }
+
+                ui.same_line();
+                if ui.button("Step Out") {
+                    actions.push(DebugAction::StepOut);
+                }
+
+                ui.separator();
+
+                let status = if !debugger.is_paused() {
+                    "running"
+                } else if cpu.is_waiting_for_key() {
+                    "paused, waiting for key"
+                } else {
+                    "paused"
+                };
+
+                ui.text(format!("Status: {status}"));
+                ui.text(format!("Cycles: {}", cpu.cycles()));
+                ui.text(format!(
+                    "Seed: {} ({})",
+                    cpu.random().seed(),
+                    cpu.random().mode()
+                ));
+                ui.text(format!(
+                    "PC: {:04X}  I: {:04X}  SP: {:X}",
+                    cpu.pc(),
+                    cpu.i(),
+                    cpu.sp()
+                ));
+                ui.text(format!(
+                    "DT: {:02X}  ST: {:02X}",
+                    cpu.delay_timer(),
+                    cpu.sound_timer()
+                ));
+
+                ui.separator();
+
+                for (row, registers) in cpu.registers().chunks(4).enumerate() {
+                    let line = registers
+                        .iter()
+                        .enumerate()
+                        .map(|(column, value)| format!("V{:X}: {value:02X}", row * 4 + column))
+                        .collect::<Vec<_>>()
+                        .join("  ");
+
+                    ui.text(line);
+                }
+
+                ui.separator();
+                ui.text("Stack");
+
+                if cpu.sp() == 0 {
+                    ui.text_disabled("empty");
+                }
+
+                for (depth, addr) in cpu.stack()[1..=cpu.sp()].iter().enumerate().rev() {
+                    ui.text(format!("{:X}: {addr:04X}", depth + 1));
+                }
+
+                ui.separator();
+                ui.text("Breakpoints");
+
+                ui.set_next_item_width(80.0);
+                let entered = ui
+                    .input_text("##breakpoint", breakpoint_input)
+                    .chars_hexadecimal(true)
+                    .enter_returns_true(true)
+                    .build();
+
+                ui.same_line();
+                if ui.button("Add") || entered {
+                    if let Ok(addr) = usize::from_str_radix(breakpoint_input.trim(), 16) {
+                        if !debugger.has_breakpoint(addr) {
+                            actions.push(DebugAction::ToggleBreakpoint(addr));
+                        }
+                    }
+
+                    breakpoint_input.clear();
+                }
+
+                ui.same_line();
+                if ui.button("Toggle at PC") {
+                    actions.push(DebugAction::ToggleBreakpoint(cpu.pc()));
+                }
+
+                for addr in debugger.breakpoints() {
+                    let marker = if addr == cpu.pc() { ">" } else { " " };
+
+                    if ui.selectable(format!("{marker} {addr:04X}")) {
+                        actions.push(DebugAction::ToggleBreakpoint(addr));
+                    }
+
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Click to remove");
+                    }
+                }
+
+                ui.separator();
+
+                if ui.collapsing_header("Profiler", TreeNodeFlags::empty()) {
+                    Self::draw_profiler(ui, cpu, &mut actions);
+                }
+
+                if ui.collapsing_header("Cheats", TreeNodeFlags::empty()) {
+                    cheat_panel.draw(ui, cpu, cheats, &mut actions);
+                }
+            });
+
+        ui.window("Memory")
+            .size(
+                [window_width as f32 - debugger_width, window_height as f32],
+                Condition::Always,
+            )
+            .position([debugger_width, 0.0], Condition::Always)
+            .resizable(false)
+            .collapsible(false)
+            .build(|| memory_view.draw(ui, cpu, &mut actions));
+
+        let draw_data = imgui_context.render();
+
+        unsafe { self.renderer.gl_context().clear(glow::COLOR_BUFFER_BIT) };
+        self.renderer.render(draw_data)?;
+
+        self.window.gl_swap_window();
+
+        Ok(actions)
+    }
+
+    fn draw_profiler(ui: &Ui, cpu: &Cpu, actions: &mut Vec<DebugAction>) {
+        let Some(profiler) = cpu.profiler() else {
+            if ui.button("Start") {
+                actions.push(DebugAction::StartProfiling);
+            }
+
+            return;
+        };
+
+        if ui.button("Stop") {
+            actions.push(DebugAction::StopProfiling);
+        }
+
+        ui.same_line();
+        if ui.button("Reset") {
+            actions.push(DebugAction::ResetProfile);
+        }
+
+        ui.same_line();
+        if ui.button("Export JSON") {
+            actions.push(DebugAction::ExportProfile);
+        }
+
+        let frame_stats = profiler.instructions_per_frame();
+
+        ui.text(format!(
+            "Instructions: {}  Frames: {}",
+            profiler.instructions(),
+            profiler.frames()
+        ));
+        ui.text(format!(
+            "Per frame: min {}  avg {:.1}  max {}",
+            frame_stats.min, frame_stats.average, frame_stats.max
+        ));
+        ui.text(format!(
+            "Waiting for key: {:.2}s ({} frames)",
+            profiler.key_wait_seconds(),
+            profiler.key_wait_frames()
+        ));
+
+        Self::draw_hot_spots(ui, cpu, profiler);
+        Self::draw_instruction_types(ui, profiler);
+    }
+
+    /// The most executed addresses with the instruction in ram there now.
+    fn draw_hot_spots(ui: &Ui, cpu: &Cpu, profiler: &Profiler) {
+        let flags = TableFlags::BORDERS | TableFlags::ROW_BG | TableFlags::SCROLL_Y;
+        let size = [0.0, ui.text_line_height_with_spacing() * 10.0];
+
+        let Some(_table) = ui.begin_table_with_sizing("hot spots", 4, flags, size, 0.0) else {
+            return;
+        };
+
+        ui.table_setup_scroll_freeze(0, 1);
+        ui.table_setup_column("PC");
+        ui.table_setup_column("Count");
+        ui.table_setup_column("%");
+        ui.table_setup_column("Instruction");
+        ui.table_headers_row();
+
+        let total = profiler.instructions().max(1) as f64;
+
+        for hot_spot in profiler.hot_spots() {
+            let instruction = cpu
+                .ram()
+                .get(hot_spot.pc..hot_spot.pc + OPCODE_SIZE)
+                .map(|bytes| Instruction::decode(u16::from_be_bytes([bytes[0], bytes[1]])))
+                .map_or(String::new(), |instruction| instruction.to_string());
+
+            ui.table_next_row();
+            ui.table_next_column();
+            ui.text(format!("{:04X}", hot_spot.pc));
+            ui.table_next_column();
+            ui.text(hot_spot.count.to_string());
+            ui.table_next_column();
+            ui.text(format!("{:.1}", hot_spot.count as f64 * 100.0 / total));
+            ui.table_next_column();
+            ui.text(instruction);
+        }
+    }
+
+    fn draw_instruction_types(ui: &Ui, profiler: &Profiler) {
+        let flags = TableFlags::BORDERS | TableFlags::ROW_BG | TableFlags::SCROLL_Y;
+        let size = [0.0, ui.text_line_height_with_spacing() * 10.0];
+
+        let Some(_table) = ui.begin_table_with_sizing("instruction types", 3, flags, size, 0.0)
+        else {
+            return;
+        };
+
+        ui.table_setup_scroll_freeze(0, 1);
+        ui.table_setup_column("Opcode");
+        ui.table_setup_column("Count");
+        ui.table_setup_column("%");
+        ui.table_headers_row();
+
+        let total = profiler.instructions().max(1) as f64;
+
+        for instruction_count in profiler.instruction_types() {
+            ui.table_next_row();
+            ui.table_next_column();
+            ui.text(instruction_count.pattern);
+            ui.table_next_column();
+            ui.text(instruction_count.count.to_string());
+            ui.table_next_column();
+            ui.text(format!(
+                "{:.1}",
+                instruction_count.count as f64 * 100.0 / total
+            ));
+        }
+    }
+}
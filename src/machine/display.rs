@@ -1,5 +1,9 @@
 use crate::{
-    constants::{DISPLAY_HEIGHT, DISPLAY_WIDTH, SPRITE_SCALE, VRAM_HEIGHT, VRAM_WIDTH},
+    constants::{
+        DISPLAY_HEIGHT, DISPLAY_WIDTH, FONT, HIRES_VRAM_HEIGHT, HIRES_VRAM_WIDTH, SPRITE_SCALE,
+        VRAM_HEIGHT, VRAM_WIDTH,
+    },
+    cpu::{Cpu, Vram},
     rom::Rom,
     types::Result,
     utils::Utils,
@@ -170,39 +174,38 @@ impl Display {
         }
 
         let mut rom_path = Utils::roms_dir()?;
-        rom_path.push(&format!("{rom_name}.ch8"));
+        rom_path.push(format!("{rom_name}.ch8"));
 
         Ok((window, event_pump, rom_path))
     }
 
     pub fn draw_game(
         canvas: &mut Canvas<Window>,
-        vram_buffer: &[[u8; VRAM_WIDTH]; VRAM_HEIGHT],
-        bg_color: Rgb,
-        fg_color: Rgb,
+        vram_buffer: &Vram,
+        hires: bool,
+        palette: &[Rgb; 4],
     ) -> Result<()> {
-        for (y, row) in vram_buffer.iter().enumerate() {
-            for (x, &color) in row.iter().enumerate() {
-                let color = if color == 0 {
-                    pixels::Color::RGB(
-                        bg_color.get_red() as u8,
-                        bg_color.get_green() as u8,
-                        bg_color.get_blue() as u8,
-                    )
-                } else {
-                    pixels::Color::RGB(
-                        fg_color.get_red() as u8,
-                        fg_color.get_green() as u8,
-                        fg_color.get_blue() as u8,
-                    )
-                };
-
-                canvas.set_draw_color(color);
+        /* high resolution mode has twice the pixels in the same window, so each is half the size */
+        let (width, height, scale) = if hires {
+            (HIRES_VRAM_WIDTH, HIRES_VRAM_HEIGHT, SPRITE_SCALE / 2)
+        } else {
+            (VRAM_WIDTH, VRAM_HEIGHT, SPRITE_SCALE)
+        };
+
+        for (y, row) in vram_buffer.iter().take(height).enumerate() {
+            for (x, &planes) in row.iter().take(width).enumerate() {
+                let color = palette[(planes & 0b11) as usize];
+
+                canvas.set_draw_color(pixels::Color::RGB(
+                    color.get_red() as u8,
+                    color.get_green() as u8,
+                    color.get_blue() as u8,
+                ));
                 canvas.fill_rect(Rect::new(
-                    (x * SPRITE_SCALE as usize) as i32,
-                    (y * SPRITE_SCALE as usize) as i32,
-                    SPRITE_SCALE,
-                    SPRITE_SCALE,
+                    (x * scale as usize) as i32,
+                    (y * scale as usize) as i32,
+                    scale,
+                    scale,
                 ))?;
             }
         }
@@ -212,7 +215,88 @@ impl Display {
         Ok(())
     }
 
-    fn glow_context(window: &Window) -> glow::Context {
+    /// Draw the CPU registers in the built-in hex font after a fault.
+    ///
+    /// The rows are PC, I and SP, then V0-V7, V8-VF and the four most recent stack entries.
+    pub fn draw_fault(canvas: &mut Canvas<Window>, cpu: &Cpu) -> Result<()> {
+        let scale = SPRITE_SCALE / 2;
+        let line_height = 7 * scale as i32;
+        let registers = cpu.registers();
+
+        let lines = [
+            format!("{:04X} {:04X} {:X}", cpu.pc(), cpu.i(), cpu.sp()),
+            registers[0..8]
+                .iter()
+                .map(|v| format!("{v:02X}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+            registers[8..16]
+                .iter()
+                .map(|v| format!("{v:02X}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+            cpu.stack()[1..=cpu.sp()]
+                .iter()
+                .rev()
+                .take(4)
+                .map(|addr| format!("{addr:04X}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+        ];
+
+        canvas.set_draw_color(pixels::Color::RGB(120, 0, 0));
+        canvas.clear();
+        canvas.set_draw_color(pixels::Color::RGB(255, 255, 255));
+
+        for (row, line) in lines.iter().enumerate() {
+            Self::draw_hex(
+                canvas,
+                line,
+                (scale as i32, line_height * row as i32 + scale as i32),
+                scale,
+            )?;
+        }
+
+        canvas.present();
+
+        Ok(())
+    }
+
+    /// Draw a string of hex digits with the 4x5 font, skipping any other characters as spaces.
+    fn draw_hex(
+        canvas: &mut Canvas<Window>,
+        text: &str,
+        (x, y): (i32, i32),
+        scale: u32,
+    ) -> Result<()> {
+        let size = scale as i32;
+
+        for (index, digit) in text.chars().enumerate() {
+            let Some(value) = digit.to_digit(16) else {
+                continue;
+            };
+
+            let glyph = &FONT[value as usize * 5..value as usize * 5 + 5];
+            let glyph_x = x + index as i32 * 5 * size;
+
+            for (row, bits) in glyph.iter().enumerate() {
+                for bit in 0..4 {
+                    if (bits >> (7 - bit)) & 1 == 1 {
+                        canvas.fill_rect(Rect::new(
+                            glyph_x + bit * size,
+                            y + row as i32 * size,
+                            scale,
+                            scale,
+                        ))?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn glow_context(window: &Window) -> glow::Context {
         unsafe {
             glow::Context::from_loader_function(|s| window.subsystem().gl_get_proc_address(s) as _)
         }
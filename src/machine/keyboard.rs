@@ -1,10 +1,71 @@
-use sdl2::{event::Event, keyboard::Keycode, EventPump};
+use sdl2::{
+    event::Event,
+    keyboard::{Keycode, Mod},
+    EventPump,
+};
+
+/// An emulator control pressed this frame, separate from the CHIP-8 keypad.
+pub enum Hotkey {
+    /* F1-F9 */
+    SaveState(u8),
+    /* Shift + F1-F9 */
+    LoadState(u8),
+    /* P */
+    Pause,
+    /* N, pauses and runs a single frame */
+    FrameAdvance,
+    /* F10 */
+    ToggleDebugger,
+    /* F11, starts or stops a GIF recording */
+    ToggleRecording,
+    /* F12 */
+    Screenshot,
+    /* M */
+    ToggleMute,
+    /* Minus */
+    VolumeDown,
+    /* Equals */
+    VolumeUp,
+}
+
+pub struct Input {
+    /* every event this frame, for other windows to handle */
+    pub events: Vec<Event>,
+    pub keypad: [bool; 16],
+    pub hotkeys: Vec<Hotkey>,
+    /* Backspace, held */
+    pub rewind: bool,
+    /* Tab, held */
+    pub fast_forward: bool,
+    /* Shift + Tab, held */
+    pub unthrottled: bool,
+    /* Backquote, held */
+    pub slow_motion: bool,
+}
+
+impl Input {
+    /// Drop the keys pressed this frame, keeping only the events, for when another window has
+    /// keyboard focus.
+    pub fn release_keys(&mut self) {
+        self.keypad = [false; 16];
+        self.hotkeys.clear();
+        self.rewind = false;
+        self.fast_forward = false;
+        self.unthrottled = false;
+        self.slow_motion = false;
+    }
+}
 
 pub struct Keyboard;
 
 impl Keyboard {
-    pub fn poll(event_pump: &mut EventPump) -> std::result::Result<[bool; 16], ()> {
+    pub fn poll(event_pump: &mut EventPump) -> std::result::Result<Input, ()> {
+        let mut hotkeys = Vec::new();
+        let mut events = Vec::new();
+
         for event in event_pump.poll_iter() {
+            events.push(event.clone());
+
             match event {
                 Event::Quit { .. }
                 | Event::KeyDown {
@@ -13,6 +74,32 @@ impl Keyboard {
                 } => {
                     return Err(());
                 }
+                Event::KeyDown {
+                    keycode: Some(key),
+                    keymod,
+                    repeat: false,
+                    ..
+                } => {
+                    if let Some(slot) = Self::state_slot(key) {
+                        if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+                            hotkeys.push(Hotkey::LoadState(slot));
+                        } else {
+                            hotkeys.push(Hotkey::SaveState(slot));
+                        }
+                    }
+
+                    match key {
+                        Keycode::P => hotkeys.push(Hotkey::Pause),
+                        Keycode::N => hotkeys.push(Hotkey::FrameAdvance),
+                        Keycode::F10 => hotkeys.push(Hotkey::ToggleDebugger),
+                        Keycode::F11 => hotkeys.push(Hotkey::ToggleRecording),
+                        Keycode::F12 => hotkeys.push(Hotkey::Screenshot),
+                        Keycode::M => hotkeys.push(Hotkey::ToggleMute),
+                        Keycode::Minus => hotkeys.push(Hotkey::VolumeDown),
+                        Keycode::Equals => hotkeys.push(Hotkey::VolumeUp),
+                        _ => {}
+                    }
+                }
                 _ => {}
             }
         }
@@ -24,6 +111,10 @@ impl Keyboard {
             .collect();
 
         let mut keypad = [false; 16];
+        let shift = keys.contains(&Keycode::LShift) || keys.contains(&Keycode::RShift);
+        let tab = keys.contains(&Keycode::Tab);
+        let rewind = keys.contains(&Keycode::Backspace);
+        let slow_motion = keys.contains(&Keycode::Backquote);
 
         for key in keys {
             let pressed = match key {
@@ -51,6 +142,29 @@ impl Keyboard {
             }
         }
 
-        Ok(keypad)
+        Ok(Input {
+            events,
+            keypad,
+            hotkeys,
+            rewind,
+            fast_forward: tab && !shift,
+            unthrottled: tab && shift,
+            slow_motion,
+        })
+    }
+
+    fn state_slot(key: Keycode) -> Option<u8> {
+        match key {
+            Keycode::F1 => Some(1),
+            Keycode::F2 => Some(2),
+            Keycode::F3 => Some(3),
+            Keycode::F4 => Some(4),
+            Keycode::F5 => Some(5),
+            Keycode::F6 => Some(6),
+            Keycode::F7 => Some(7),
+            Keycode::F8 => Some(8),
+            Keycode::F9 => Some(9),
+            _ => None,
+        }
     }
 }
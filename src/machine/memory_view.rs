@@ -0,0 +1,245 @@
+use super::debug_window::DebugAction;
+use crate::{
+    constants::{
+        AUDIO_PATTERN_SIZE, BIG_FONT, BIG_FONT_ADDR, FONT, FONT_ADDR, OPCODE_SIZE, PROGRAM_START,
+        RAM_SIZE,
+    },
+    cpu::Cpu,
+    instruction::Instruction,
+};
+use imgui::{ListClipper, StyleColor, Ui};
+use std::ops::Range;
+
+const BYTES_PER_ROW: usize = 16;
+
+const FONT_COLOR: [f32; 4] = [0.45, 0.65, 1.0, 1.0];
+const ROM_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const RAM_COLOR: [f32; 4] = [0.55, 0.55, 0.55, 1.0];
+const PC_COLOR: [f32; 4] = [0.2, 0.6, 0.2, 1.0];
+const I_COLOR: [f32; 4] = [0.6, 0.5, 0.1, 1.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Follow {
+    Off,
+    Pc,
+    I,
+}
+
+/// A hex view of ram with the fonts, the rom, PC and I picked out, and bytes editable in place.
+pub struct MemoryView {
+    rom: Range<usize>,
+    follow: Follow,
+    goto_input: String,
+    scroll_to: Option<usize>,
+    editing: Option<usize>,
+    edit_input: String,
+    focus_edit: bool,
+}
+
+impl MemoryView {
+    pub fn new(rom_size: usize) -> Self {
+        Self {
+            rom: PROGRAM_START..PROGRAM_START + rom_size,
+            follow: Follow::Off,
+            goto_input: String::new(),
+            scroll_to: None,
+            editing: None,
+            edit_input: String::new(),
+            focus_edit: false,
+        }
+    }
+
+    pub fn draw(&mut self, ui: &Ui, cpu: &Cpu, actions: &mut Vec<DebugAction>) {
+        let pc = cpu.pc()..cpu.pc() + Self::pc_size(cpu);
+        let i = cpu.i()..cpu.i() + Self::i_size(cpu);
+
+        ui.text("Follow");
+        ui.same_line();
+        ui.radio_button("Off", &mut self.follow, Follow::Off);
+        ui.same_line();
+        ui.radio_button("PC", &mut self.follow, Follow::Pc);
+        ui.same_line();
+        ui.radio_button("I", &mut self.follow, Follow::I);
+
+        ui.same_line();
+        ui.set_next_item_width(60.0);
+        let entered = ui
+            .input_text("Go to", &mut self.goto_input)
+            .chars_hexadecimal(true)
+            .enter_returns_true(true)
+            .build();
+
+        if entered {
+            if let Ok(addr) = usize::from_str_radix(self.goto_input.trim(), 16) {
+                self.follow = Follow::Off;
+                self.scroll_to = Some(addr.min(RAM_SIZE - 1));
+            }
+
+            self.goto_input.clear();
+        }
+
+        ui.text_colored(FONT_COLOR, "font");
+        ui.same_line();
+        ui.text_colored(ROM_COLOR, "rom");
+        ui.same_line();
+        ui.text_colored(RAM_COLOR, "ram");
+        ui.same_line();
+        ui.text_colored(PC_COLOR, "PC");
+        ui.same_line();
+        ui.text_colored(I_COLOR, "I");
+        ui.same_line();
+        ui.text_disabled("(click a byte to edit it)");
+
+        ui.separator();
+
+        let row_height = ui.text_line_height_with_spacing();
+
+        ui.child_window("memory").build(|| {
+            let target = match self.follow {
+                Follow::Off => self.scroll_to.take(),
+                Follow::Pc => Some(pc.start),
+                Follow::I => Some(i.start),
+            };
+
+            /* only scroll when the target is off screen, so following doesn't jitter */
+            if let Some(addr) = target {
+                let y = (addr / BYTES_PER_ROW) as f32 * row_height;
+                let height = ui.window_size()[1];
+                let top = ui.scroll_y();
+
+                if y < top || y + row_height > top + height {
+                    ui.set_scroll_y((y - height / 2.0).max(0.0));
+                }
+            }
+
+            let rows = (RAM_SIZE / BYTES_PER_ROW) as i32;
+            let mut clipper = ListClipper::new(rows).items_height(row_height).begin(ui);
+
+            while clipper.step() {
+                for row in clipper.display_start()..clipper.display_end() {
+                    self.draw_row(ui, cpu, row as usize * BYTES_PER_ROW, &pc, &i, actions);
+                }
+            }
+        });
+    }
+
+    fn draw_row(
+        &mut self,
+        ui: &Ui,
+        cpu: &Cpu,
+        start: usize,
+        pc: &Range<usize>,
+        i: &Range<usize>,
+        actions: &mut Vec<DebugAction>,
+    ) {
+        let bytes = &cpu.ram()[start..start + BYTES_PER_ROW];
+        let byte_width = ui.calc_text_size("00")[0];
+
+        ui.text_disabled(format!("{start:04X}"));
+
+        for (offset, &byte) in bytes.iter().enumerate() {
+            let addr = start + offset;
+            ui.same_line();
+
+            if self.editing == Some(addr) {
+                self.draw_edit(ui, addr, byte_width, actions);
+                continue;
+            }
+
+            let highlight = if pc.contains(&addr) {
+                Some(PC_COLOR)
+            } else if i.contains(&addr) {
+                Some(I_COLOR)
+            } else {
+                None
+            };
+
+            let _text = ui.push_style_color(StyleColor::Text, self.color(addr));
+            let _header = ui.push_style_color(StyleColor::Header, highlight.unwrap_or_default());
+
+            let clicked = ui
+                .selectable_config(format!("{byte:02X}##{addr:04X}"))
+                .selected(highlight.is_some())
+                .size([byte_width, 0.0])
+                .build();
+
+            if clicked {
+                self.editing = Some(addr);
+                self.edit_input = format!("{byte:02X}");
+                self.focus_edit = true;
+            }
+        }
+
+        let ascii: String = bytes
+            .iter()
+            .map(|&byte| match byte {
+                0x20..=0x7E => byte as char,
+                _ => '.',
+            })
+            .collect();
+
+        ui.same_line();
+        ui.text_disabled(ascii);
+    }
+
+    /// The input for the byte being edited. Enter writes it and moves on to the next byte.
+    fn draw_edit(&mut self, ui: &Ui, addr: usize, width: f32, actions: &mut Vec<DebugAction>) {
+        if self.focus_edit {
+            ui.set_keyboard_focus_here();
+            self.focus_edit = false;
+        }
+
+        ui.set_next_item_width(width + 8.0);
+        let entered = ui
+            .input_text(format!("##edit{addr:04X}"), &mut self.edit_input)
+            .chars_hexadecimal(true)
+            .auto_select_all(true)
+            .enter_returns_true(true)
+            .build();
+
+        if entered {
+            if let Ok(value) = u8::from_str_radix(self.edit_input.trim(), 16) {
+                actions.push(DebugAction::Poke(addr, value));
+            }
+
+            self.editing = (addr + 1 < RAM_SIZE).then_some(addr + 1);
+            self.edit_input.clear();
+            self.focus_edit = true;
+        } else if ui.is_item_deactivated() {
+            self.editing = None;
+        }
+    }
+
+    fn color(&self, addr: usize) -> [f32; 4] {
+        let font = FONT_ADDR..FONT_ADDR + FONT.len();
+        let big_font = BIG_FONT_ADDR..BIG_FONT_ADDR + BIG_FONT.len();
+
+        if font.contains(&addr) || big_font.contains(&addr) {
+            FONT_COLOR
+        } else if self.rom.contains(&addr) {
+            ROM_COLOR
+        } else {
+            RAM_COLOR
+        }
+    }
+
+    fn pc_size(cpu: &Cpu) -> usize {
+        match cpu.instruction() {
+            Ok(Instruction::LoadILong) => 2 * OPCODE_SIZE,
+            _ => OPCODE_SIZE,
+        }
+    }
+
+    /// How many bytes from I the next instruction works with, or just the one I points at.
+    fn i_size(cpu: &Cpu) -> usize {
+        match cpu.instruction() {
+            Ok(Instruction::Draw(_, _, 0)) => 32,
+            Ok(Instruction::Draw(_, _, n)) => n,
+            Ok(Instruction::StoreRegisters(x) | Instruction::LoadRegisters(x)) => x + 1,
+            Ok(Instruction::Save(x, y) | Instruction::Load(x, y)) => x.abs_diff(y) + 1,
+            Ok(Instruction::StoreBcd(_)) => 3,
+            Ok(Instruction::Audio) => AUDIO_PATTERN_SIZE,
+            _ => 1,
+        }
+    }
+}
@@ -1,34 +1,89 @@
-use self::{audio::Audio, display::Display, keyboard::Keyboard};
-use crate::{cpu::Cpu, rom::Rom, types::Result, utils::Utils};
-use colors_transform::Rgb;
+use self::{
+    audio::Audio,
+    debug_window::{DebugAction, DebugWindow},
+    display::Display,
+    keyboard::{Hotkey, Keyboard},
+    movie_session::MovieSession,
+};
+use crate::{
+    buzzer::BuzzerSettings,
+    cheat::CheatList,
+    constants::{
+        DISPLAY_WIDTH, FAST_FORWARD_SPEED, GIF_SCALE, REWIND_MAX_BYTES, REWIND_SECONDS,
+        SLOW_MOTION_SPEED, TIMER_HZ, TIMER_INTERVAL_NS, VOLUME_STEP,
+    },
+    coverage::Coverage,
+    cpu::Cpu,
+    debugger::Debugger,
+    fault::CpuFault,
+    framebuffer::{Framebuffer, Palette},
+    gdb::GdbStub,
+    gif::GifRecorder,
+    movie::Movie,
+    profiler::Profiler,
+    random::{Random, RandomMode},
+    rewind::Rewind,
+    rom::Rom,
+    state::CpuState,
+    timer::{Clock, ScaledClock, SystemClock},
+    trace::Tracer,
+    types::Result,
+    utils::Utils,
+    wav::WavRecorder,
+};
 use imgui::Context;
-use sdl2::{pixels::Color, video::Window, EventPump, Sdl};
-use snailquote::unescape;
-use std::path::Path;
+use sdl2::{pixels::Color, render::Canvas, video::Window, EventPump, Sdl};
+use std::{
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
 
 mod audio;
+mod cheat_panel;
+mod debug_window;
 mod display;
 mod keyboard;
+mod memory_view;
+mod movie_session;
+
+/// Tools attached to the game from the command line.
+#[derive(Default)]
+pub struct Options {
+    pub tracer: Option<Tracer>,
+    pub gdb: Option<GdbStub>,
+    /// Where to write a coverage report of the rom when the game ends.
+    pub coverage: Option<PathBuf>,
+    /// The seed for `RND`, or a different one every run if not set.
+    pub seed: Option<u64>,
+    pub random_mode: RandomMode,
+    /// Record the keypad to a movie written to this file when the game ends.
+    pub record_movie: Option<PathBuf>,
+    /// Play back a movie instead of taking input from the keyboard.
+    pub play_movie: Option<Movie>,
+}
 
 pub struct Machine {
     sdl_context: Sdl,
     imgui_context: Context,
+    options: Options,
 }
 
 impl Machine {
-    pub fn prepare() -> Result<()> {
+    pub fn prepare(options: Options) -> Result<()> {
         let mut roms = Utils::fetch_rom_list()?;
         roms.sort_by(|a, b| a.title.cmp(&b.title));
 
-        Self::init()?.boot(roms)?;
+        Self::init(options)?.boot(roms)?;
 
         Ok(())
     }
 
-    pub fn init() -> Result<Self> {
+    pub fn init(options: Options) -> Result<Self> {
         Ok(Self {
             sdl_context: sdl2::init()?,
             imgui_context: Context::create(),
+            options,
         })
     }
 
@@ -42,53 +97,571 @@ impl Machine {
     }
 
     pub fn start(&mut self, window: Window, mut event_pump: EventPump, rom: &Path) -> Result<()> {
-        let mut cpu = Cpu::new();
-        cpu.init(rom)?;
+        let program = std::fs::read(rom)?;
+        let rom_size = program.len();
 
-        let audio = Audio::init(&self.sdl_context)?;
         let filename = rom.with_extension("");
         let filename = filename.file_name().unwrap();
         let rom = Utils::find_rom(filename.to_str().unwrap())?;
+        let mut audio = Audio::init(&self.sdl_context, Utils::buzzer_settings(&rom.title)?)?;
 
-        let mut bg_color = Rgb::from(75.0, 75.0, 75.0);
-        let mut fg_color = Rgb::from(0.0, 0.0, 0.0);
-        let mut tickrate = Utils::instruction_time_ns();
+        /* the quirks size ram, so they are set before the program is loaded into it */
+        let mut cpu = Cpu::new();
+        cpu.set_quirks(rom.quirks());
+        cpu.load(&program)?;
+        cpu.set_tracer(self.options.tracer.take());
 
-        if let Some(background_color) = rom.options.background_color {
-            if let Ok(bg_result) = Rgb::from_hex_str(&unescape(&background_color)?) {
-                bg_color = bg_result;
-            }
-        }
+        cpu.set_random(match self.options.seed {
+            Some(seed) => Random::new(seed, self.options.random_mode),
+            None => Random::from_entropy(self.options.random_mode),
+        });
 
-        if let Some(foreground_color) = rom.options.fill_color {
-            if let Ok(fg_result) = Rgb::from_hex_str(&unescape(&foreground_color)?) {
-                fg_color = fg_result;
-            }
-        }
+        let coverage_path = self.options.coverage.take();
 
-        if rom.options.tickrate > 0 {
-            tickrate = rom.options.tickrate;
+        if coverage_path.is_some() {
+            cpu.set_coverage(Some(Coverage::new()));
         }
 
+        let tickrate = rom.instruction_time_ns();
+        let palette = rom.colors()?;
+        let image_palette = rom.palette()?;
+
         let mut canvas = window.into_canvas().build()?;
         canvas.set_draw_color(Color::RGB(0, 0, 0));
         canvas.clear();
         canvas.present();
 
-        while let Ok(result) = Keyboard::poll(&mut event_pump) {
-            let output = cpu.process(result, tickrate);
+        /* the real clock paces rewind playback, the scaled clock paces the game */
+        let real_clock = SystemClock;
+        let mut clock = ScaledClock::new(SystemClock);
+        let instructions_per_frame = rom.instructions_per_frame();
+        let mut paused = false;
+
+        let mut rewind = Rewind::new(REWIND_SECONDS * TIMER_HZ as usize, REWIND_MAX_BYTES);
+        let mut last_frame_t = 0;
+        let mut last_snapshot_t = 0;
+
+        let mut debugger = Debugger::new();
+        let mut debug_window: Option<DebugWindow> = None;
+        let mut gdb = self.options.gdb.take();
+        let mut cheats = Self::load_cheats(&rom.title)?;
+        let mut recording: Option<GifRecorder> = None;
+
+        /* a movie starts from power on, with the seed and quirks it was recorded with */
+        let mut movie_session = match (
+            self.options.play_movie.take(),
+            self.options.record_movie.take(),
+        ) {
+            (Some(movie), _) => {
+                movie.prepare(&program, &mut cpu)?;
+                Some(MovieSession::play(movie))
+            }
+            (None, Some(path)) => Some(MovieSession::record(
+                Movie::new(&program, &cpu, instructions_per_frame),
+                path,
+            )),
+            (None, None) => None,
+        };
+        let mut movie_keypad: Option<[bool; 16]> = None;
+
+        if let Some(session) = movie_session.as_ref() {
+            let message = if session.is_recording() {
+                "recording movie"
+            } else {
+                "playing movie"
+            };
+
+            canvas
+                .window_mut()
+                .set_title(&format!("CHIP-8 - {message}"))?;
+        }
+
+        while let Ok(mut input) = Keyboard::poll(&mut event_pump) {
+            let mut frame_advance = false;
+
+            if let Some(window) = debug_window.as_mut() {
+                for event in &input.events {
+                    window.handle_event(&mut self.imgui_context, event);
+                }
+
+                if window.has_focus(&self.sdl_context) {
+                    input.release_keys();
+                }
+            }
+
+            /* while gdb is attached it alone decides when a paused CPU carries on */
+            let gdb_attached = gdb.as_ref().is_some_and(GdbStub::is_attached);
+
+            if debug_window.as_ref().is_some_and(DebugWindow::is_closed) {
+                debug_window = None;
+
+                if !gdb_attached {
+                    debugger.resume(&cpu);
+                }
+            }
+
+            for hotkey in input.hotkeys {
+                let message = match hotkey {
+                    Hotkey::SaveState(slot) => match Self::save_state(&cpu, &rom.title, slot) {
+                        Ok(()) => format!("saved slot {slot}"),
+                        Err(error) => format!("could not save slot {slot}: {error}"),
+                    },
+                    Hotkey::LoadState(_) if movie_session.is_some() => {
+                        String::from("states can't be loaded during a movie")
+                    }
+                    Hotkey::LoadState(slot) => match Self::load_state(&mut cpu, &rom.title, slot) {
+                        Ok(()) => {
+                            Display::draw_game(&mut canvas, cpu.vram(), cpu.is_hires(), &palette)?;
+                            format!("loaded slot {slot}")
+                        }
+                        Err(error) => format!("could not load slot {slot}: {error}"),
+                    },
+                    Hotkey::Pause => {
+                        paused = !paused;
+                        String::from(if paused { "paused" } else { "running" })
+                    }
+                    Hotkey::FrameAdvance => {
+                        paused = true;
+                        frame_advance = true;
+                        String::from("paused")
+                    }
+                    Hotkey::ToggleDebugger => match debug_window.take() {
+                        Some(_) => {
+                            if !gdb_attached {
+                                debugger.resume(&cpu);
+                            }
+
+                            String::from("debugger closed")
+                        }
+                        None => {
+                            debug_window = Some(DebugWindow::open(
+                                &self.sdl_context,
+                                &mut self.imgui_context,
+                                rom_size,
+                            )?);
+                            String::from("debugger opened")
+                        }
+                    },
+                    Hotkey::ToggleRecording => match recording.take() {
+                        Some(recorder) => {
+                            match Self::save_recording(recorder, audio.finish_capture(), &rom.title)
+                            {
+                                Ok(path) => format!("saved recording {}", path.display()),
+                                Err(error) => format!("could not save recording: {error}"),
+                            }
+                        }
+                        None => {
+                            recording = Some(GifRecorder::new(GIF_SCALE, &image_palette));
+                            audio.start_capture();
+                            String::from("recording")
+                        }
+                    },
+                    Hotkey::ToggleMute => {
+                        let muted = !audio.settings().muted;
+                        Self::change_volume(&mut audio, |settings| settings.muted = muted)
+                    }
+                    Hotkey::VolumeDown => Self::change_volume(&mut audio, |settings| {
+                        settings.volume = (settings.volume - VOLUME_STEP).max(0.0)
+                    }),
+                    Hotkey::VolumeUp => Self::change_volume(&mut audio, |settings| {
+                        settings.volume = (settings.volume + VOLUME_STEP).min(1.0)
+                    }),
+                    Hotkey::Screenshot => {
+                        match Self::screenshot(&cpu, &rom.title, &image_palette) {
+                            Ok(path) => format!("saved screenshot {}", path.display()),
+                            Err(error) => format!("could not save screenshot: {error}"),
+                        }
+                    }
+                };
+
+                canvas
+                    .window_mut()
+                    .set_title(&format!("CHIP-8 - {message}"))?;
+            }
+
+            /* the clock stands still while paused, so time spent at a breakpoint isn't caught up on */
+            let speed = if paused || debugger.is_paused() {
+                0.0
+            } else if input.slow_motion {
+                SLOW_MOTION_SPEED
+            } else if input.fast_forward {
+                FAST_FORWARD_SPEED
+            } else {
+                1.0
+            };
+
+            if clock.speed() != speed {
+                clock.set_speed(speed);
+            }
+
+            /* rewinding plays back one snapshot per real 60 Hz frame */
+            let time_ns = real_clock.now_nanos();
+            let new_frame = time_ns.saturating_sub(last_frame_t) >= TIMER_INTERVAL_NS;
+
+            if new_frame {
+                last_frame_t = time_ns;
+            }
+
+            /* the debugger panel is drawn at 60 Hz and its actions apply straight away */
+            if new_frame {
+                if let Some(window) = debug_window.as_mut() {
+                    let actions = window.draw(
+                        &mut self.imgui_context,
+                        &event_pump,
+                        &cpu,
+                        &debugger,
+                        &cheats,
+                    )?;
+                    let redraw = !actions.is_empty();
+
+                    /* exporting writes a file, so it's done here rather than with the other actions */
+                    let (exports, actions): (Vec<_>, Vec<_>) = actions
+                        .into_iter()
+                        .partition(|action| matches!(action, DebugAction::ExportProfile));
+
+                    if !exports.is_empty() {
+                        let message = match Self::export_profile(&cpu, &rom.title) {
+                            Ok(path) => format!("profile saved to {}", path.display()),
+                            Err(error) => format!("could not save profile: {error}"),
+                        };
+
+                        canvas
+                            .window_mut()
+                            .set_title(&format!("CHIP-8 - {message}"))?;
+                    }
+
+                    let old_cheats = cheats.clone();
+
+                    if let Err(fault) = actions.into_iter().try_for_each(|action| {
+                        Self::debug(&mut debugger, &mut cpu, &mut cheats, action)
+                    }) {
+                        audio.pause();
+                        Self::show_fault(&mut canvas, &mut event_pump, &cpu, fault)?;
+                        break;
+                    }
+
+                    if cheats != old_cheats {
+                        if let Err(error) = Self::save_cheats(&cheats, &rom.title) {
+                            canvas
+                                .window_mut()
+                                .set_title(&format!("CHIP-8 - could not save cheats: {error}"))?;
+                        }
+                    }
+
+                    if redraw {
+                        Display::draw_game(&mut canvas, cpu.vram(), cpu.is_hires(), &palette)?;
+                    }
+                }
+            }
+
+            if let Some(stub) = gdb.as_mut() {
+                stub.poll(&mut cpu, &mut debugger)?;
+
+                if stub.is_attached() != gdb_attached {
+                    let message = if gdb_attached {
+                        "gdb detached"
+                    } else {
+                        "gdb attached"
+                    };
+
+                    canvas
+                        .window_mut()
+                        .set_title(&format!("CHIP-8 - {message}"))?;
+                }
+
+                /* gdb may step or poke memory while the CPU is held */
+                if new_frame && stub.is_attached() && debugger.is_paused() {
+                    Display::draw_game(&mut canvas, cpu.vram(), cpu.is_hires(), &palette)?;
+                }
+            }
+
+            if input.rewind && movie_session.is_none() {
+                audio.pause();
+
+                if new_frame {
+                    if let Some(state) = rewind.pop() {
+                        cpu.load_state(&state)?;
+                        Display::draw_game(&mut canvas, cpu.vram(), cpu.is_hires(), &palette)?;
+                    }
+                }
+
+                continue;
+            }
+
+            /* with the debugger open or gdb attached, it decides whether the next instruction may run */
+            let debugging =
+                debug_window.is_some() || gdb.as_ref().is_some_and(GdbStub::is_attached);
+
+            if debugging && !debugger.should_run(&cpu) {
+                audio.pause();
+                continue;
+            }
+
+            /* frozen cheats would change a movie's run, so they're left alone during one */
+            if (new_frame || frame_advance) && movie_session.is_none() {
+                cheats.apply(&mut cpu);
+            }
+
+            /* breakpoints are checked before every instruction, wherever a frame stops */
+            let stop = |cpu: &Cpu| debugging && !debugger.should_run(cpu);
+
+            /* a movie runs whole frames at 60 Hz, so it plays back the same whatever the clock does */
+            let result = if let Some(session) = movie_session.as_mut() {
+                if session.is_finished() {
+                    movie_session = None;
+                    canvas.window_mut().set_title("CHIP-8 - movie finished")?;
+                    continue;
+                }
+
+                if !frame_advance && (paused || !new_frame) {
+                    continue;
+                }
+
+                /* a frame stopped at a breakpoint carries on with the keys it started with */
+                let keypad = *movie_keypad.get_or_insert_with(|| session.keypad(input.keypad));
+                let frame = cpu.run_frame_until(keypad, session.instructions_per_frame(), stop);
+
+                if let Ok(true) = frame {
+                    movie_keypad = None;
+
+                    if let Err(error) = session.end_frame(keypad, &cpu) {
+                        movie_session = None;
+                        canvas
+                            .window_mut()
+                            .set_title(&format!("CHIP-8 - {error}"))?;
+                    }
+                }
+
+                frame.map(|_| cpu.output())
+            } else if frame_advance || (input.unthrottled && !paused) {
+                /* while advancing frame by frame or unthrottled, whole frames run without the clock */
+                rewind.push(cpu.save_state());
+                cpu.run_frame_until(input.keypad, instructions_per_frame, stop)
+                    .map(|_| cpu.output())
+            } else {
+                let time_ns = clock.now_nanos();
+
+                if time_ns.saturating_sub(last_snapshot_t) >= TIMER_INTERVAL_NS {
+                    last_snapshot_t = time_ns;
+                    rewind.push(cpu.save_state());
+                }
+
+                cpu.process_until(input.keypad, tickrate, &clock, stop)
+            };
+
+            let output = match result {
+                Ok(output) => output,
+                Err(fault) => {
+                    audio.pause();
+                    Self::show_fault(&mut canvas, &mut event_pump, &cpu, fault)?;
+                    break;
+                }
+            };
+
+            if output.should_exit {
+                break;
+            }
 
             if output.should_draw {
-                Display::draw_game(&mut canvas, output.vram, bg_color, fg_color)?;
+                Display::draw_game(&mut canvas, output.vram, output.hires, &palette)?;
             }
 
-            if output.should_beep {
+            /* the recording samples the screen at 60 Hz while the game runs */
+            if let Some(recorder) = recording.as_mut() {
+                if (new_frame && !paused) || frame_advance {
+                    recorder.push(Framebuffer::from_vram(output.vram, output.hires));
+                }
+            }
+
+            audio.set_pattern(output.audio_pattern.copied(), output.pitch);
+
+            if output.should_beep && !paused {
                 audio.play();
             } else {
                 audio.pause();
             }
         }
 
+        if let Some(tracer) = cpu.take_tracer() {
+            tracer.finish()?;
+        }
+
+        if let Some(session) = movie_session {
+            session.finish(&cpu)?;
+        }
+
+        if let Some(recorder) = recording {
+            Self::save_recording(recorder, audio.finish_capture(), &rom.title)?;
+        }
+
+        if let (Some(path), Some(coverage)) = (coverage_path, cpu.coverage()) {
+            std::fs::write(path, coverage.report(&program))?;
+        }
+
+        Ok(())
+    }
+
+    /// Show the registers at a fault until the player quits.
+    fn show_fault(
+        canvas: &mut Canvas<Window>,
+        event_pump: &mut EventPump,
+        cpu: &Cpu,
+        fault: CpuFault,
+    ) -> Result<()> {
+        canvas
+            .window_mut()
+            .set_title(&format!("CHIP-8 - {fault}"))?;
+        Display::draw_fault(canvas, cpu)?;
+
+        while Keyboard::poll(event_pump).is_ok() {
+            thread::sleep(Duration::from_millis(16));
+        }
+
+        Ok(())
+    }
+
+    fn debug(
+        debugger: &mut Debugger,
+        cpu: &mut Cpu,
+        cheats: &mut CheatList,
+        action: DebugAction,
+    ) -> std::result::Result<(), CpuFault> {
+        match action {
+            DebugAction::Pause => debugger.pause(),
+            DebugAction::Resume => debugger.resume(cpu),
+            DebugAction::Step => debugger.step(cpu)?,
+            DebugAction::StepOver => debugger.step_over(cpu)?,
+            DebugAction::StepOut => debugger.step_out(cpu),
+            DebugAction::ToggleBreakpoint(addr) => debugger.toggle_breakpoint(addr),
+            DebugAction::Poke(addr, value) => cpu.ram_mut()[addr] = value,
+            DebugAction::StartProfiling => cpu.set_profiler(Some(Profiler::new())),
+            DebugAction::StopProfiling => cpu.set_profiler(None),
+            DebugAction::ResetProfile => {
+                if let Some(profiler) = cpu.profiler_mut() {
+                    profiler.reset();
+                }
+            }
+            DebugAction::ExportProfile => {}
+            DebugAction::AddCheat(cheat) => cheats.cheats.push(cheat),
+            DebugAction::UpdateCheat(index, cheat) => {
+                if let Some(old) = cheats.cheats.get_mut(index) {
+                    *old = cheat;
+                }
+            }
+            DebugAction::RemoveCheat(index) => {
+                if index < cheats.cheats.len() {
+                    cheats.cheats.remove(index);
+                }
+            }
+            DebugAction::PokeCheat(index) => {
+                if let Some(cheat) = cheats.cheats.get(index) {
+                    cheat.location.write(cpu, cheat.value);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn export_profile(cpu: &Cpu, title: &str) -> Result<PathBuf> {
+        let profiler = cpu.profiler().ok_or("profiling is off")?;
+        let path = Utils::profile_path(title)?;
+        std::fs::write(&path, profiler.to_json()?)?;
+
+        Ok(path)
+    }
+
+    /// Write the screen as a PNG in the rom's colors, the size it's shown at, and as a PBM at one
+    /// pixel per CHIP-8 pixel for image assertions.
+    fn screenshot(cpu: &Cpu, title: &str, palette: &Palette) -> Result<PathBuf> {
+        let frame = Framebuffer::from_cpu(cpu);
+        let scale = DISPLAY_WIDTH as usize / frame.width();
+
+        let [png, pbm] = Utils::screenshot_paths(title)?;
+        std::fs::write(&png, frame.to_png(scale, palette))?;
+        std::fs::write(pbm, frame.to_pbm(1))?;
+
+        Ok(png)
+    }
+
+    /// Change the volume or mute and keep the change in the global settings, for every rom.
+    fn change_volume(audio: &mut Audio, change: impl Fn(&mut BuzzerSettings)) -> String {
+        let mut settings = audio.settings();
+        change(&mut settings);
+        audio.set_settings(settings);
+
+        let message = if settings.muted {
+            String::from("muted")
+        } else {
+            format!("volume {:.0}%", settings.volume * 100.0)
+        };
+
+        match Self::save_volume(&settings) {
+            Ok(()) => message,
+            Err(error) => format!("{message}, could not save it: {error}"),
+        }
+    }
+
+    fn save_volume(settings: &BuzzerSettings) -> Result<()> {
+        let path = Utils::audio_settings_path()?;
+
+        let mut global = if path.exists() {
+            BuzzerSettings::from_json(&std::fs::read_to_string(&path)?)?
+        } else {
+            BuzzerSettings::default()
+        };
+
+        global.volume = settings.volume;
+        global.muted = settings.muted;
+        std::fs::write(path, global.to_json()?)?;
+
+        Ok(())
+    }
+
+    /// Write a recording's GIF, and its WAV beside it, returning the GIF's path.
+    fn save_recording(
+        recorder: GifRecorder,
+        sound: Option<WavRecorder>,
+        title: &str,
+    ) -> Result<PathBuf> {
+        let [gif, wav] = Utils::recording_paths(title)?;
+        std::fs::write(&gif, recorder.finish())?;
+
+        if let Some(sound) = sound {
+            std::fs::write(wav, sound.finish())?;
+        }
+
+        Ok(gif)
+    }
+
+    /// The cheats saved for a rom, or none if it has no cheat file yet.
+    fn load_cheats(title: &str) -> Result<CheatList> {
+        let path = Utils::cheats_path(title)?;
+
+        if !path.exists() {
+            return Ok(CheatList::default());
+        }
+
+        Ok(CheatList::from_json(&std::fs::read_to_string(path)?)?)
+    }
+
+    fn save_cheats(cheats: &CheatList, title: &str) -> Result<()> {
+        std::fs::write(Utils::cheats_path(title)?, cheats.to_json()?)?;
+
+        Ok(())
+    }
+
+    fn save_state(cpu: &Cpu, title: &str, slot: u8) -> Result<()> {
+        let path = Utils::state_path(title, slot)?;
+        std::fs::write(path, cpu.save_state().to_bytes()?)?;
+
+        Ok(())
+    }
+
+    fn load_state(cpu: &mut Cpu, title: &str, slot: u8) -> Result<()> {
+        let bytes = std::fs::read(Utils::state_path(title, slot)?)?;
+        cpu.load_state(&CpuState::from_bytes(&bytes)?)?;
+
         Ok(())
     }
 }
@@ -0,0 +1,81 @@
+use crate::{
+    cpu::Cpu,
+    movie::{Movie, MovieError},
+    types::Result,
+};
+use std::path::PathBuf;
+
+/// An input movie being recorded to a file or played back, a whole frame at a time.
+pub struct MovieSession {
+    movie: Movie,
+    frame: usize,
+    /// Where the movie is written once the game ends, or `None` when playing back.
+    record_to: Option<PathBuf>,
+}
+
+impl MovieSession {
+    pub fn record(movie: Movie, path: PathBuf) -> Self {
+        Self {
+            movie,
+            frame: 0,
+            record_to: Some(path),
+        }
+    }
+
+    pub fn play(movie: Movie) -> Self {
+        Self {
+            movie,
+            frame: 0,
+            record_to: None,
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.record_to.is_some()
+    }
+
+    /// Whether playback has run out of frames.
+    pub fn is_finished(&self) -> bool {
+        !self.is_recording() && self.frame >= self.movie.len()
+    }
+
+    pub fn instructions_per_frame(&self) -> usize {
+        self.movie.instructions_per_frame
+    }
+
+    /// The keys for the next frame: the player's while recording, the movie's while playing.
+    pub fn keypad(&self, input: [bool; 16]) -> [bool; 16] {
+        match self.record_to {
+            Some(_) => input,
+            None => self.movie.keypad(self.frame).unwrap_or_default(),
+        }
+    }
+
+    /// Record the frame just run, or check it against the movie.
+    pub fn end_frame(
+        &mut self,
+        keypad: [bool; 16],
+        cpu: &Cpu,
+    ) -> std::result::Result<(), MovieError> {
+        let result = match self.record_to {
+            Some(_) => {
+                self.movie.record(keypad, cpu);
+                Ok(())
+            }
+            None => self.movie.check(self.frame, cpu),
+        };
+
+        self.frame += 1;
+        result
+    }
+
+    /// Write the movie out if it was being recorded.
+    pub fn finish(mut self, cpu: &Cpu) -> Result<()> {
+        if let Some(path) = self.record_to.take() {
+            self.movie.finish(cpu);
+            std::fs::write(path, self.movie.to_bytes()?)?;
+        }
+
+        Ok(())
+    }
+}
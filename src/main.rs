@@ -1,16 +1,150 @@
-use machine::Machine;
-use types::Result;
+use chip8_interpreter::{
+    buzzer::Buzzer,
+    constants::{GIF_SCALE, SAMPLE_RATE, TRACE_MAX_BYTES},
+    cpu::{Cpu, CpuOutput},
+    disasm,
+    framebuffer::{Framebuffer, DEFAULT_PALETTE},
+    gdb::GdbStub,
+    gif::GifRecorder,
+    machine::{Machine, Options},
+    movie::Movie,
+    random::RandomMode,
+    trace::Tracer,
+    types::Result,
+    utils::Utils,
+    wav::WavRecorder,
+};
+use std::{
+    env,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
 
-mod constants;
-mod cpu;
-mod machine;
-mod rom;
-mod timer;
-mod types;
-mod utils;
+const USAGE: &str = "usage: chip8_interpreter [--trace <file>] [--trace-pc <start>-<end>]... [--trace-max-bytes <bytes>] [--gdb <port>] [--coverage <file>] [--seed <seed>] [--random <modern|vip>] [--record <movie>] [--play <movie>]
+       chip8_interpreter disasm <rom>
+       chip8_interpreter verify-movie <rom> <movie>
+       chip8_interpreter record-gif <rom> <movie> <gif>
+       chip8_interpreter record-wav <rom> <movie> <wav>";
 
 fn main() -> Result<()> {
-    Machine::prepare()?;
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["disasm", rom] => print!("{}", disasm::disassemble(&std::fs::read(rom)?)),
+        ["verify-movie", rom, movie] => {
+            let movie = Movie::from_bytes(&std::fs::read(movie)?)?;
+            movie.verify(&std::fs::read(rom)?)?;
+            println!("movie plays back in sync for {} frames", movie.len());
+        }
+        ["record-gif", rom, movie, gif] => {
+            let movie = Movie::from_bytes(&std::fs::read(movie)?)?;
+            let title = Path::new(rom).file_stem().and_then(|stem| stem.to_str());
+            /* a rom missing from the rom list has no colors of its own */
+            let palette = match title.map(Utils::find_rom) {
+                Some(Ok(rom)) => rom.palette()?,
+                _ => DEFAULT_PALETTE,
+            };
+            let mut recorder = GifRecorder::new(GIF_SCALE, &palette);
+            play_headless(&std::fs::read(rom)?, &movie, |output| {
+                recorder.push(Framebuffer::from_vram(output.vram, output.hires))
+            })?;
+            std::fs::write(gif, recorder.finish())?;
+            println!("recorded {} frames to {gif}", movie.len());
+        }
+        ["record-wav", rom, movie, wav] => {
+            let movie = Movie::from_bytes(&std::fs::read(movie)?)?;
+            let mut buzzer = Buzzer::new(SAMPLE_RATE);
+            let title = Path::new(rom).file_stem().and_then(|stem| stem.to_str());
+            buzzer.set_settings(Utils::buzzer_settings(title.unwrap_or_default())?);
+            let mut recorder = WavRecorder::new(SAMPLE_RATE);
+            play_headless(&std::fs::read(rom)?, &movie, |output| {
+                buzzer.update(output);
+                recorder.push(&buzzer.render_frame());
+            })?;
+            std::fs::write(wav, recorder.finish())?;
+            println!("recorded {} frames to {wav}", movie.len());
+        }
+        ref options => Machine::prepare(parse_options(options)?)?,
+    }
 
     Ok(())
 }
+
+fn parse_options(options: &[&str]) -> Result<Options> {
+    let mut trace_path = None;
+    let mut trace_ranges = Vec::new();
+    let mut trace_max_bytes = TRACE_MAX_BYTES;
+    let mut gdb = None;
+    let mut coverage = None;
+    let mut seed = None;
+    let mut random_mode = RandomMode::default();
+    let mut record_movie = None;
+    let mut play_movie = None;
+    let mut options = options.iter();
+
+    while let Some(&option) = options.next() {
+        let value = options.next().ok_or(USAGE)?;
+
+        match option {
+            "--trace" => trace_path = Some(Path::new(value)),
+            "--trace-pc" => trace_ranges.push(pc_range(value).ok_or(USAGE)?),
+            "--trace-max-bytes" => trace_max_bytes = value.parse()?,
+            "--gdb" => {
+                let stub = GdbStub::bind(value.parse()?)?;
+                println!("gdb stub listening on {}", stub.local_addr()?);
+                gdb = Some(stub);
+            }
+            "--coverage" => coverage = Some(PathBuf::from(value)),
+            "--seed" => seed = Some(value.parse()?),
+            "--random" => random_mode = value.parse()?,
+            "--record" => record_movie = Some(PathBuf::from(value)),
+            "--play" => play_movie = Some(Movie::from_bytes(&std::fs::read(value)?)?),
+            _ => return Err(USAGE.into()),
+        }
+    }
+
+    let tracer = match trace_path {
+        Some(path) => {
+            let mut tracer = Tracer::create(path, trace_max_bytes)?;
+
+            for range in trace_ranges {
+                tracer.add_range(range);
+            }
+
+            Some(tracer)
+        }
+        None => None,
+    };
+
+    Ok(Options {
+        tracer,
+        gdb,
+        coverage,
+        seed,
+        random_mode,
+        record_movie,
+        play_movie,
+    })
+}
+
+/// Play a movie back without a display, handing the output of every frame to `frame`.
+fn play_headless(rom: &[u8], movie: &Movie, mut frame: impl FnMut(&CpuOutput)) -> Result<()> {
+    let mut cpu = Cpu::new();
+    movie.prepare(rom, &mut cpu)?;
+    cpu.load(rom)?;
+
+    for index in 0..movie.len() {
+        let keypad = movie.keypad(index).unwrap_or_default();
+        frame(&cpu.run_frame(keypad, movie.instructions_per_frame)?);
+        movie.check(index, &cpu)?;
+    }
+
+    Ok(())
+}
+
+/// A hex address range such as `200-2FF`, or a single address such as `2A0`.
+fn pc_range(value: &str) -> Option<RangeInclusive<usize>> {
+    let (start, end) = value.split_once('-').unwrap_or((value, value));
+
+    Some(usize::from_str_radix(start, 16).ok()?..=usize::from_str_radix(end, 16).ok()?)
+}
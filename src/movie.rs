@@ -0,0 +1,252 @@
+use crate::{
+    constants::MOVIE_CHECKPOINT_FRAMES,
+    cpu::Cpu,
+    fault::CpuFault,
+    quirks::Quirks,
+    random::{Random, RandomMode},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The movie format version written by [`Movie::to_bytes`].
+pub const MOVIE_VERSION: u32 = 1;
+
+/// FNV-1a, which unlike the standard library's hasher gives the same hash on every build.
+pub fn hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3)
+    })
+}
+
+/// A hash of everything in a save state, to tell whether two runs are still in step.
+pub fn state_hash(cpu: &Cpu) -> u64 {
+    cpu.save_state().to_bytes().map_or(0, |bytes| hash(&bytes))
+}
+
+/// The state hash expected after a frame of the movie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub frame: usize,
+    pub hash: u64,
+}
+
+/// The keypad for every frame of a run from power on, with what's needed to play it back the
+/// same way: the ROM, the `RND` seed and the settings it ran with.
+///
+/// Frames are run whole with [`Cpu::run_frame`], so playback doesn't depend on any clock.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Movie {
+    version: u32,
+    pub rom_hash: u64,
+    pub seed: u64,
+    pub random_mode: RandomMode,
+    pub quirks: Quirks,
+    pub instructions_per_frame: usize,
+    /// The keys held during each frame, bit n set for key n.
+    pub frames: Vec<u16>,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+impl Movie {
+    /// Start recording a CPU that has just loaded `rom` and has its quirks and generator set.
+    pub fn new(rom: &[u8], cpu: &Cpu, instructions_per_frame: usize) -> Self {
+        Self {
+            version: MOVIE_VERSION,
+            rom_hash: hash(rom),
+            seed: cpu.random().seed(),
+            random_mode: cpu.random().mode(),
+            quirks: cpu.quirks(),
+            instructions_per_frame,
+            frames: Vec::new(),
+            checkpoints: Vec::new(),
+        }
+    }
+
+    /// Add a frame that ran with `keypad`, leaving `cpu` as it is now.
+    ///
+    /// Every [`MOVIE_CHECKPOINT_FRAMES`] frames the state hash is kept as a checkpoint.
+    pub fn record(&mut self, keypad: [bool; 16], cpu: &Cpu) {
+        let keys = keypad
+            .iter()
+            .enumerate()
+            .filter(|(_, &pressed)| pressed)
+            .fold(0, |keys, (key, _)| keys | 1 << key);
+
+        self.frames.push(keys);
+
+        if self.frames.len() % MOVIE_CHECKPOINT_FRAMES == 0 {
+            self.checkpoint(cpu);
+        }
+    }
+
+    /// Keep a checkpoint at the last frame, so playback checks the very end of the run too.
+    pub fn finish(&mut self, cpu: &Cpu) {
+        let last = self
+            .checkpoints
+            .last()
+            .map(|checkpoint| checkpoint.frame + 1);
+
+        if !self.frames.is_empty() && last != Some(self.frames.len()) {
+            self.checkpoint(cpu);
+        }
+    }
+
+    fn checkpoint(&mut self, cpu: &Cpu) {
+        self.checkpoints.push(Checkpoint {
+            frame: self.frames.len() - 1,
+            hash: state_hash(cpu),
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The keys held during a frame, or `None` past the end of the movie.
+    pub fn keypad(&self, frame: usize) -> Option<[bool; 16]> {
+        let keys = *self.frames.get(frame)?;
+        Some(std::array::from_fn(|key| keys & 1 << key != 0))
+    }
+
+    /// Set up a CPU to play the movie of `rom` back from its first frame, with the quirks and
+    /// generator it was recorded with.
+    pub fn prepare(&self, rom: &[u8], cpu: &mut Cpu) -> Result<(), MovieError> {
+        let found = hash(rom);
+
+        if found != self.rom_hash {
+            return Err(MovieError::RomMismatch {
+                found,
+                expected: self.rom_hash,
+            });
+        }
+
+        cpu.set_quirks(self.quirks);
+        cpu.set_random(Random::new(self.seed, self.random_mode));
+
+        Ok(())
+    }
+
+    /// Compare the CPU with the checkpoint after `frame`, if there is one.
+    pub fn check(&self, frame: usize, cpu: &Cpu) -> Result<(), MovieError> {
+        let Some(checkpoint) = self
+            .checkpoints
+            .iter()
+            .find(|checkpoint| checkpoint.frame == frame)
+        else {
+            return Ok(());
+        };
+
+        let found = state_hash(cpu);
+
+        if found != checkpoint.hash {
+            return Err(MovieError::Desync {
+                frame,
+                found,
+                expected: checkpoint.hash,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Play the whole movie without a display, checking every checkpoint, and hand back the CPU
+    /// as the movie left it.
+    pub fn verify(&self, rom: &[u8]) -> Result<Cpu, MovieError> {
+        let mut cpu = Cpu::new();
+        self.prepare(rom, &mut cpu)?;
+        cpu.load(rom)
+            .map_err(|fault| MovieError::Fault { frame: 0, fault })?;
+
+        for frame in 0..self.len() {
+            let keypad = self.keypad(frame).unwrap_or_default();
+
+            cpu.run_frame(keypad, self.instructions_per_frame)
+                .map_err(|fault| MovieError::Fault { frame, fault })?;
+            self.check(frame, &cpu)?;
+        }
+
+        Ok(cpu)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MovieError> {
+        serde_json::to_vec(self).map_err(|error| MovieError::Malformed(error.to_string()))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MovieError> {
+        let header: MovieHeader = serde_json::from_slice(bytes)
+            .map_err(|error| MovieError::Malformed(error.to_string()))?;
+
+        if header.version != MOVIE_VERSION {
+            return Err(MovieError::UnsupportedVersion {
+                found: header.version,
+                expected: MOVIE_VERSION,
+            });
+        }
+
+        serde_json::from_slice(bytes).map_err(|error| MovieError::Malformed(error.to_string()))
+    }
+}
+
+/// Only the version is read up front, like [`CpuState`](crate::state::CpuState).
+#[derive(Deserialize)]
+struct MovieHeader {
+    version: u32,
+}
+
+/// An error raised when a movie cannot be loaded or stops matching the run it recorded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MovieError {
+    /// The movie was written by a different version of the format.
+    UnsupportedVersion { found: u32, expected: u32 },
+    /// The movie could not be parsed.
+    Malformed(String),
+    /// The movie was recorded with a different ROM.
+    RomMismatch { found: u64, expected: u64 },
+    /// The state after a frame no longer matches the one recorded.
+    Desync {
+        frame: usize,
+        found: u64,
+        expected: u64,
+    },
+    /// The ROM faulted during playback.
+    Fault { frame: usize, fault: CpuFault },
+}
+
+impl fmt::Display for MovieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion { found, expected } => {
+                write!(
+                    f,
+                    "movie version {found} is not supported, expected {expected}"
+                )
+            }
+            Self::Malformed(message) => write!(f, "movie is malformed: {message}"),
+            Self::RomMismatch { found, expected } => {
+                write!(
+                    f,
+                    "movie was recorded with rom {expected:016X}, not {found:016X}"
+                )
+            }
+            Self::Desync {
+                frame,
+                found,
+                expected,
+            } => write!(
+                f,
+                "movie desynced at frame {frame}: state {found:016X}, expected {expected:016X}"
+            ),
+            Self::Fault { frame, fault } => write!(f, "{fault} at frame {frame} of the movie"),
+        }
+    }
+}
+
+impl std::error::Error for MovieError {}
+
+#[cfg(test)]
+#[path = "../tests/movie/movie_tests.rs"]
+mod movie_tests;
@@ -0,0 +1,203 @@
+use crate::{
+    constants::{RAM_SIZE, TIMER_HZ},
+    instruction::Instruction,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// How many instructions ran in each 60 Hz frame.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct FrameStats {
+    pub min: u64,
+    pub max: u64,
+    pub average: f64,
+}
+
+/// An address and how many times the instruction there was executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct HotSpot {
+    pub pc: usize,
+    pub count: u64,
+}
+
+/// A kind of instruction, by opcode pattern, and how many times one was executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct InstructionCount {
+    pub pattern: &'static str,
+    pub count: u64,
+}
+
+#[derive(Serialize)]
+struct Report {
+    instructions: u64,
+    frames: u64,
+    instructions_per_frame: FrameStats,
+    key_wait_steps: u64,
+    key_wait_frames: u64,
+    key_wait_seconds: f64,
+    hot_spots: Vec<HotSpot>,
+    instruction_types: Vec<InstructionCount>,
+}
+
+/// Where a ROM spends its time, collected by the [`Cpu`] while set with [`Cpu::set_profiler`].
+///
+/// [`Cpu`]: crate::cpu::Cpu
+/// [`Cpu::set_profiler`]: crate::cpu::Cpu::set_profiler
+#[derive(Debug, Clone)]
+pub struct Profiler {
+    pc_counts: Vec<u64>,
+    instruction_counts: BTreeMap<&'static str, u64>,
+    instructions: u64,
+    frames: u64,
+    frame_instructions: u64,
+    min_frame_instructions: Option<u64>,
+    max_frame_instructions: u64,
+    key_wait_steps: u64,
+    key_wait_frames: u64,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            pc_counts: vec![0; RAM_SIZE],
+            instruction_counts: BTreeMap::new(),
+            instructions: 0,
+            frames: 0,
+            frame_instructions: 0,
+            min_frame_instructions: None,
+            max_frame_instructions: 0,
+            key_wait_steps: 0,
+            key_wait_frames: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Count an instruction executed at `pc`.
+    pub fn record(&mut self, pc: usize, instruction: Instruction) {
+        if let Some(count) = self.pc_counts.get_mut(pc) {
+            *count += 1;
+        }
+
+        *self
+            .instruction_counts
+            .entry(instruction.pattern())
+            .or_default() += 1;
+
+        self.instructions += 1;
+        self.frame_instructions += 1;
+    }
+
+    /// Count a step spent blocked on `LD Vx, K`.
+    pub fn record_key_wait(&mut self) {
+        self.key_wait_steps += 1;
+    }
+
+    /// Close off a 60 Hz frame, noting whether it ended still waiting for a key.
+    pub fn end_frame(&mut self, waiting_for_key: bool) {
+        self.frames += 1;
+
+        self.min_frame_instructions = Some(
+            self.min_frame_instructions
+                .map_or(self.frame_instructions, |min| {
+                    min.min(self.frame_instructions)
+                }),
+        );
+        self.max_frame_instructions = self.max_frame_instructions.max(self.frame_instructions);
+        self.frame_instructions = 0;
+
+        if waiting_for_key {
+            self.key_wait_frames += 1;
+        }
+    }
+
+    pub fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    pub fn instructions_per_frame(&self) -> FrameStats {
+        let average = match self.frames {
+            0 => 0.0,
+            frames => (self.instructions - self.frame_instructions) as f64 / frames as f64,
+        };
+
+        FrameStats {
+            min: self.min_frame_instructions.unwrap_or_default(),
+            max: self.max_frame_instructions,
+            average,
+        }
+    }
+
+    /// The number of steps spent waiting for a key instead of running an instruction.
+    pub fn key_wait_steps(&self) -> u64 {
+        self.key_wait_steps
+    }
+
+    /// The number of frames that ended waiting for a key.
+    pub fn key_wait_frames(&self) -> u64 {
+        self.key_wait_frames
+    }
+
+    pub fn key_wait_seconds(&self) -> f64 {
+        self.key_wait_frames as f64 / TIMER_HZ as f64
+    }
+
+    pub fn count_at(&self, pc: usize) -> u64 {
+        self.pc_counts.get(pc).copied().unwrap_or_default()
+    }
+
+    /// Every address executed at least once, most executed first.
+    pub fn hot_spots(&self) -> Vec<HotSpot> {
+        let mut hot_spots: Vec<HotSpot> = self
+            .pc_counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(pc, &count)| HotSpot { pc, count })
+            .collect();
+
+        hot_spots.sort_by(|a, b| b.count.cmp(&a.count).then(a.pc.cmp(&b.pc)));
+        hot_spots
+    }
+
+    /// Every kind of instruction executed at least once, most executed first.
+    pub fn instruction_types(&self) -> Vec<InstructionCount> {
+        let mut counts: Vec<InstructionCount> = self
+            .instruction_counts
+            .iter()
+            .map(|(&pattern, &count)| InstructionCount { pattern, count })
+            .collect();
+
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then(a.pattern.cmp(b.pattern)));
+        counts
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&Report {
+            instructions: self.instructions,
+            frames: self.frames,
+            instructions_per_frame: self.instructions_per_frame(),
+            key_wait_steps: self.key_wait_steps,
+            key_wait_frames: self.key_wait_frames,
+            key_wait_seconds: self.key_wait_seconds(),
+            hot_spots: self.hot_spots(),
+            instruction_types: self.instruction_types(),
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/profiler/profiler_tests.rs"]
+mod profiler_tests;
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+/// Behavior of the instructions that differ between CHIP-8 interpreters.
+///
+/// The default matches the original behavior of this interpreter, with every quirk turned off.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quirks {
+    /// `SHR`/`SHL` copy Vy into Vx before shifting instead of shifting Vx in place.
+    pub shift_uses_vy: bool,
+    /// `LD [I], Vx` and `LD Vx, [I]` leave I pointing past the last register transferred.
+    pub load_store_increments_i: bool,
+    /// `LD [I], Vx` and `LD Vx, [I]` leave I pointing at the last register transferred, by adding
+    /// x rather than x + 1, as CHIP-48 does. [`Quirks::load_store_increments_i`] wins if both are
+    /// set.
+    #[serde(default)]
+    pub load_store_increments_i_by_x: bool,
+    /// `JP V0, addr` is treated as `BXNN`, jumping to XNN + Vx.
+    pub jump_uses_vx: bool,
+    /// `OR`, `AND` and `XOR` reset VF to 0.
+    pub logic_resets_vf: bool,
+    /// Sprites drawn past the edge of the screen are clipped instead of wrapping around.
+    pub clip_sprites: bool,
+    /// `DRW` waits for the next 60 Hz tick, so at most one sprite is drawn per frame.
+    pub display_wait: bool,
+    /// Ram is the 64 KiB of XO-CHIP rather than the 4 KiB of the original interpreters, so
+    /// programs and I can reach past 0xFFF.
+    #[serde(default)]
+    pub extended_memory: bool,
+}
+
+impl Quirks {
+    pub const COSMAC_VIP: Self = Self {
+        shift_uses_vy: true,
+        load_store_increments_i: true,
+        load_store_increments_i_by_x: false,
+        jump_uses_vx: false,
+        logic_resets_vf: true,
+        clip_sprites: true,
+        display_wait: true,
+        extended_memory: false,
+    };
+
+    pub const CHIP_48: Self = Self {
+        shift_uses_vy: false,
+        load_store_increments_i: false,
+        load_store_increments_i_by_x: true,
+        jump_uses_vx: true,
+        logic_resets_vf: false,
+        clip_sprites: true,
+        display_wait: false,
+        extended_memory: false,
+    };
+
+    pub const SUPER_CHIP: Self = Self {
+        shift_uses_vy: false,
+        load_store_increments_i: false,
+        load_store_increments_i_by_x: false,
+        jump_uses_vx: true,
+        logic_resets_vf: false,
+        clip_sprites: true,
+        display_wait: false,
+        extended_memory: false,
+    };
+
+    pub const XO_CHIP: Self = Self {
+        shift_uses_vy: true,
+        load_store_increments_i: true,
+        load_store_increments_i_by_x: false,
+        jump_uses_vx: false,
+        logic_resets_vf: false,
+        clip_sprites: false,
+        display_wait: false,
+        extended_memory: true,
+    };
+
+    /// The preset for a chip8Archive platform name, e.g. `"chip8"`, `"schip"` or `"xochip"`.
+    pub fn for_platform(platform: &str) -> Option<Self> {
+        match platform {
+            "chip8" => Some(Self::COSMAC_VIP),
+            "chip48" => Some(Self::CHIP_48),
+            "schip" => Some(Self::SUPER_CHIP),
+            "xochip" => Some(Self::XO_CHIP),
+            _ => None,
+        }
+    }
+}
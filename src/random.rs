@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Page 0x100 of the COSMAC VIP's ram, the second page of its CHIP-8 interpreter, which the
+/// random routine reads as a table of bytes. The routine itself is at 0x1D9.
+const VIP_INTERPRETER_PAGE: [u8; 256] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x45, 0xA3, 0x98, 0x56, 0xD4, 0xF8, 0x81, 0xBC, 0xF8, 0x95, 0xAC,
+    0x22, 0xDC, 0x12, 0x56, 0xD4, 0x06, 0xB8, 0xD4, 0x06, 0xA8, 0xD4, 0x64, 0x0A, 0x01, 0xE6, 0x8A,
+    0xF4, 0xAA, 0x3B, 0x28, 0x9A, 0xFC, 0x01, 0xBA, 0xD4, 0xF8, 0x81, 0xBA, 0x06, 0xFA, 0x0F, 0xAA,
+    0x0A, 0xAA, 0xD4, 0xE6, 0x06, 0xBF, 0x93, 0xBE, 0xF8, 0x1B, 0xAE, 0x2A, 0x1A, 0xF8, 0x00, 0x5A,
+    0x0E, 0xF5, 0x3B, 0x4B, 0x56, 0x0A, 0xFC, 0x01, 0x5A, 0x30, 0x40, 0x4E, 0xF6, 0x3B, 0x3C, 0x9F,
+    0x56, 0x2A, 0x2A, 0xD4, 0x00, 0x22, 0x86, 0x52, 0xF8, 0xF0, 0xA7, 0x07, 0x5A, 0x87, 0xF3, 0x17,
+    0x1A, 0x3A, 0x5B, 0x12, 0xD4, 0x22, 0x86, 0x52, 0xF8, 0xF0, 0xA7, 0x0A, 0x57, 0x87, 0xF3, 0x17,
+    0x1A, 0x3A, 0x6B, 0x12, 0xD4, 0x15, 0x85, 0x22, 0x73, 0x95, 0x52, 0x25, 0x45, 0xA5, 0x86, 0xFA,
+    0x0F, 0xB5, 0xD4, 0x45, 0xE6, 0xF3, 0x3A, 0x82, 0x15, 0x15, 0xD4, 0x45, 0xE6, 0xF3, 0x3A, 0x88,
+    0xD4, 0x45, 0x07, 0x30, 0x8C, 0x45, 0x07, 0x30, 0x84, 0xE6, 0x62, 0x26, 0x45, 0xA3, 0x36, 0x88,
+    0xD4, 0x3E, 0x88, 0xD4, 0xF8, 0xF0, 0xA7, 0xE7, 0x45, 0xF4, 0xA5, 0x86, 0xFA, 0x0F, 0x3B, 0xB2,
+    0xFC, 0x01, 0xB5, 0xD4, 0x45, 0x56, 0xD4, 0x45, 0xE6, 0xF4, 0x56, 0xD4, 0x45, 0xFA, 0x0F, 0x3A,
+    0xC4, 0x07, 0x56, 0xD4, 0xAF, 0x22, 0xF8, 0xD3, 0x73, 0x8F, 0xF9, 0xF0, 0x52, 0xE6, 0x07, 0xD2,
+    0x56, 0xF8, 0xFF, 0xA6, 0xF8, 0x00, 0x7E, 0x56, 0xD4, 0x19, 0x89, 0xAE, 0x93, 0xBE, 0x99, 0xEE,
+    0xF4, 0x56, 0x76, 0xE6, 0xF4, 0xB9, 0x56, 0x45, 0xF2, 0x56, 0xD4, 0x45, 0xAA, 0x86, 0xFA, 0x0F,
+    0xBA, 0xD4, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+/// Which routine `RND Vx, byte` draws its numbers from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RandomMode {
+    /// A SplitMix64 generator, uniform over every byte.
+    #[default]
+    Modern,
+    /// The original COSMAC VIP interpreter's routine.
+    ///
+    /// Each call increments a 16 bit seed and adds the byte of the interpreter's code at its low
+    /// byte to its high byte. That sum plus itself shifted right, with the carry shifted in,
+    /// becomes both the new high byte and the random number.
+    CosmacVip,
+}
+
+impl FromStr for RandomMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "modern" => Ok(Self::Modern),
+            "vip" => Ok(Self::CosmacVip),
+            _ => Err(format!(
+                "unknown random mode {value}, expected modern or vip"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for RandomMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Modern => write!(f, "modern"),
+            Self::CosmacVip => write!(f, "vip"),
+        }
+    }
+}
+
+/// The random number generator behind `RND Vx, byte`, owned by the [`Cpu`] so runs with the same
+/// seed and input play out the same way.
+///
+/// The whole generator is part of a save state, so a loaded state carries on with the same
+/// numbers it would have drawn.
+///
+/// [`Cpu`]: crate::cpu::Cpu
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Random {
+    mode: RandomMode,
+    seed: u64,
+    state: u64,
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::from_entropy(RandomMode::default())
+    }
+}
+
+impl Random {
+    pub fn new(seed: u64, mode: RandomMode) -> Self {
+        let state = match mode {
+            RandomMode::Modern => seed,
+            RandomMode::CosmacVip => seed & 0xFFFF,
+        };
+
+        Self { mode, seed, state }
+    }
+
+    /// A generator with a seed of its own, different on every run.
+    pub fn from_entropy(mode: RandomMode) -> Self {
+        Self::new(rand::random(), mode)
+    }
+
+    pub fn mode(&self) -> RandomMode {
+        self.mode
+    }
+
+    /// The seed the generator started from, to play a run back the same way.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn next_byte(&mut self) -> u8 {
+        match self.mode {
+            RandomMode::Modern => (self.split_mix() >> 56) as u8,
+            RandomMode::CosmacVip => self.vip(),
+        }
+    }
+
+    fn split_mix(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn vip(&mut self) -> u8 {
+        let seed = (self.state as u16).wrapping_add(1);
+        let [high, low] = seed.to_be_bytes();
+
+        let (sum, carry) = VIP_INTERPRETER_PAGE[low as usize].overflowing_add(high);
+        let byte = sum.wrapping_add(sum >> 1 | (carry as u8) << 7);
+
+        self.state = u16::from_be_bytes([byte, low]) as u64;
+        byte
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/random/random_tests.rs"]
+mod random_tests;
@@ -0,0 +1,152 @@
+use crate::state::CpuState;
+use std::collections::VecDeque;
+
+/// Rough bookkeeping cost of one run in a [`Patch`], on top of its bytes.
+const RUN_OVERHEAD: usize = std::mem::size_of::<(usize, Vec<u8>)>();
+
+/// The runs of bytes that differ between two equally sized buffers.
+struct Patch {
+    runs: Vec<(usize, Vec<u8>)>,
+}
+
+impl Patch {
+    /// The bytes of `from` that differ from `to`, so applying the patch to `to` gives back `from`.
+    fn diff(from: &[u8], to: &[u8]) -> Self {
+        let mut runs: Vec<(usize, Vec<u8>)> = Vec::new();
+
+        for (addr, (&old, &new)) in from.iter().zip(to).enumerate() {
+            if old == new {
+                continue;
+            }
+
+            match runs.last_mut() {
+                Some((start, bytes)) if *start + bytes.len() == addr => bytes.push(old),
+                _ => runs.push((addr, vec![old])),
+            }
+        }
+
+        Self { runs }
+    }
+
+    fn apply(&self, target: &mut [u8]) {
+        for (start, bytes) in &self.runs {
+            target[*start..*start + bytes.len()].copy_from_slice(bytes);
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.runs
+            .iter()
+            .map(|(_, bytes)| bytes.len() + RUN_OVERHEAD)
+            .sum()
+    }
+}
+
+/// One step back: the registers of the earlier state and the ram/vram bytes that changed since.
+struct Frame {
+    state: CpuState,
+    ram: Patch,
+    vram: Patch,
+}
+
+impl Frame {
+    fn size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.ram.size() + self.vram.size()
+    }
+}
+
+/// A bounded history of [`CpuState`] snapshots for stepping gameplay backwards.
+///
+/// Only the newest snapshot is kept in full. Every older one is stored as the bytes that differ
+/// from the snapshot after it, so a frame where little changed costs a few hundred bytes instead
+/// of a copy of all of ram. Once the buffer holds `max_frames` steps or `max_bytes` of deltas,
+/// the oldest steps are dropped.
+pub struct Rewind {
+    current: Option<CpuState>,
+    frames: VecDeque<Frame>,
+    max_frames: usize,
+    max_bytes: usize,
+    bytes: usize,
+}
+
+impl Rewind {
+    pub fn new(max_frames: usize, max_bytes: usize) -> Self {
+        Self {
+            current: None,
+            frames: VecDeque::new(),
+            max_frames,
+            max_bytes,
+            bytes: 0,
+        }
+    }
+
+    /// Record the newest snapshot, usually taken once per 60 Hz frame.
+    pub fn push(&mut self, state: CpuState) {
+        if let Some(previous) = self.current.take() {
+            let frame = Frame {
+                ram: Patch::diff(&previous.ram, &state.ram),
+                vram: Patch::diff(&previous.vram, &state.vram),
+                state: CpuState {
+                    ram: Vec::new(),
+                    vram: Vec::new(),
+                    ..previous
+                },
+            };
+
+            self.bytes += frame.size();
+            self.frames.push_back(frame);
+
+            while self.frames.len() > self.max_frames || self.bytes > self.max_bytes {
+                match self.frames.pop_front() {
+                    Some(oldest) => self.bytes -= oldest.size(),
+                    None => break,
+                }
+            }
+        }
+
+        self.current = Some(state);
+    }
+
+    /// Step back one snapshot, returning it for [`Cpu::load_state`], or `None` once the history
+    /// is used up.
+    ///
+    /// [`Cpu::load_state`]: crate::cpu::Cpu::load_state
+    pub fn pop(&mut self) -> Option<CpuState> {
+        let current = self.current.as_mut()?;
+        let frame = self.frames.pop_back()?;
+        self.bytes -= frame.size();
+
+        let mut state = frame.state;
+        state.ram = std::mem::take(&mut current.ram);
+        state.vram = std::mem::take(&mut current.vram);
+        frame.ram.apply(&mut state.ram);
+        frame.vram.apply(&mut state.vram);
+
+        *current = state;
+        Some(current.clone())
+    }
+
+    /// The number of steps that can be rewound.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The approximate memory held by the stored deltas, in bytes.
+    pub fn size(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn clear(&mut self) {
+        self.current = None;
+        self.frames.clear();
+        self.bytes = 0;
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/rewind/rewind_tests.rs"]
+mod rewind_tests;
@@ -1,11 +1,27 @@
+use crate::{
+    constants::{TICKRATE, TIMER_INTERVAL_NS},
+    framebuffer::Palette,
+    quirks::Quirks,
+    types::Result,
+};
+use colors_transform::{Color as _, Rgb};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
+use snailquote::unescape;
 
 #[derive(Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize, Clone)]
 pub struct RomOptions {
     pub tickrate: u128,
     pub fill_color: Option<String>,
     pub background_color: Option<String>,
+    pub fill_color2: Option<String>,
+    pub blend_color: Option<String>,
+    pub shift_quirks: Option<bool>,
+    pub load_store_quirks: Option<bool>,
+    pub jump_quirks: Option<bool>,
+    pub logic_quirks: Option<bool>,
+    pub clip_quirks: Option<bool>,
+    pub vblank_quirks: Option<bool>,
 }
 
 impl RomOptions {
@@ -25,11 +41,22 @@ impl From<&Map<String, Value>> for RomOptions {
         let tickrate = json.get("tickrate").map(Self::parse_tickrate).unwrap();
         let fill_color = json.get("fillColor").map(|value| value.to_string());
         let background_color = json.get("backgroundColor").map(|value| value.to_string());
+        let fill_color2 = json.get("fillColor2").map(|value| value.to_string());
+        let blend_color = json.get("blendColor").map(|value| value.to_string());
+        let quirk = |key: &str| json.get(key).and_then(Value::as_bool);
 
         Self {
             tickrate,
             fill_color,
             background_color,
+            fill_color2,
+            blend_color,
+            shift_quirks: quirk("shiftQuirks"),
+            load_store_quirks: quirk("loadStoreQuirks"),
+            jump_quirks: quirk("jumpQuirks"),
+            logic_quirks: quirk("logicQuirks"),
+            clip_quirks: quirk("clipQuirks"),
+            vblank_quirks: quirk("vBlankQuirks"),
         }
     }
 }
@@ -44,6 +71,103 @@ pub struct Rom {
     pub options: RomOptions,
 }
 
+impl Rom {
+    /// The quirks preset for this rom's platform, with any quirks set in its options applied on top.
+    ///
+    /// The options use Octo's naming, where `shiftQuirks` and `loadStoreQuirks` turn *off* the
+    /// original COSMAC VIP behavior.
+    pub fn quirks(&self) -> Quirks {
+        let options = &self.options;
+        let mut quirks =
+            Quirks::for_platform(self.platform.trim_matches('"')).unwrap_or(Quirks::COSMAC_VIP);
+
+        if let Some(shift) = options.shift_quirks {
+            quirks.shift_uses_vy = !shift;
+        }
+
+        if let Some(load_store) = options.load_store_quirks {
+            quirks.load_store_increments_i = !load_store;
+            quirks.load_store_increments_i_by_x = false;
+        }
+
+        if let Some(jump) = options.jump_quirks {
+            quirks.jump_uses_vx = jump;
+        }
+
+        if let Some(logic) = options.logic_quirks {
+            quirks.logic_resets_vf = logic;
+        }
+
+        if let Some(clip) = options.clip_quirks {
+            quirks.clip_sprites = clip;
+        }
+
+        if let Some(vblank) = options.vblank_quirks {
+            quirks.display_wait = vblank;
+        }
+
+        quirks
+    }
+
+    /// The time between instructions. chip8Archive gives a rom's `tickrate` in instructions per
+    /// 60 Hz frame, and roms without one run [`TICKRATE`] instructions a second.
+    pub fn instruction_time_ns(&self) -> u128 {
+        TIMER_INTERVAL_NS
+            .checked_div(self.options.tickrate)
+            .unwrap_or(1e9 as u128 / TICKRATE)
+    }
+
+    /// The instructions run in each frame of frame advance and movies, at the same rate as
+    /// [`Rom::instruction_time_ns`].
+    pub fn instructions_per_frame(&self) -> usize {
+        if self.options.tickrate > 0 {
+            self.options.tickrate as usize
+        } else {
+            (TIMER_INTERVAL_NS / self.instruction_time_ns()) as usize
+        }
+    }
+
+    /// The colors the rom is drawn in, indexed by the bitplanes a pixel is lit in. Colors its
+    /// options leave out or that aren't valid hex keep the defaults.
+    pub fn colors(&self) -> Result<[Rgb; 4]> {
+        let options = &self.options;
+        let mut colors = [
+            Rgb::from(75.0, 75.0, 75.0),
+            Rgb::from(0.0, 0.0, 0.0),
+            Rgb::from(255.0, 102.0, 0.0),
+            Rgb::from(102.0, 34.0, 0.0),
+        ];
+
+        let set = [
+            &options.background_color,
+            &options.fill_color,
+            &options.fill_color2,
+            &options.blend_color,
+        ];
+
+        for (color, option) in colors.iter_mut().zip(set) {
+            if let Some(hex) = option {
+                if let Ok(result) = Rgb::from_hex_str(&unescape(hex)?) {
+                    *color = result;
+                }
+            }
+        }
+
+        Ok(colors)
+    }
+
+    /// The rom's colors as bytes, for writing images.
+    pub fn palette(&self) -> Result<Palette> {
+        Ok(self.colors()?.map(|color| {
+            [
+                color.get_red() as u8,
+                color.get_green() as u8,
+                color.get_blue() as u8,
+            ]
+        }))
+    }
+}
+
 impl From<(&String, &Value)> for Rom {
     fn from(json: (&String, &Value)) -> Self {
         let authors = json.1["authors"]
@@ -70,3 +194,7 @@ impl From<(&String, &Value)> for Rom {
         }
     }
 }
+
+#[cfg(test)]
+#[path = "../tests/rom/rom_tests.rs"]
+mod rom_tests;
@@ -0,0 +1,97 @@
+use crate::{constants::AUDIO_PATTERN_SIZE, quirks::Quirks, random::Random};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The save state format version written by [`CpuState::to_bytes`].
+///
+/// Bump this whenever a field of [`CpuState`] is added, removed or changes meaning, so states
+/// written by older builds are rejected instead of being loaded with the wrong layout.
+pub const STATE_VERSION: u32 = 2;
+
+/// A snapshot of everything needed to resume a program, taken with [`Cpu::save_state`].
+///
+/// [`Cpu::save_state`]: crate::cpu::Cpu::save_state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CpuState {
+    pub(crate) version: u32,
+    pub(crate) ram: Vec<u8>,
+    /// The framebuffer rows laid end to end.
+    pub(crate) vram: Vec<u8>,
+    pub(crate) v: [u8; 16],
+    pub(crate) i: usize,
+    pub(crate) stack: [usize; 12],
+    pub(crate) sp: usize,
+    pub(crate) pc: usize,
+    pub(crate) delay_timer: u8,
+    pub(crate) sound_timer: u8,
+    pub(crate) rpl: [u8; 16],
+    pub(crate) planes: u8,
+    pub(crate) audio_pattern: Option<[u8; AUDIO_PATTERN_SIZE]>,
+    pub(crate) pitch: u8,
+    pub(crate) keypad_wait_input: usize,
+    pub(crate) should_keypad_wait: bool,
+    pub(crate) should_display_wait: bool,
+    pub(crate) hires: bool,
+    pub(crate) halted: bool,
+    pub(crate) quirks: Quirks,
+    pub(crate) random: Random,
+}
+
+/// Only the version is read up front, so a state from another version fails with
+/// [`StateError::UnsupportedVersion`] rather than a confusing parse error.
+#[derive(Deserialize)]
+struct StateHeader {
+    version: u32,
+}
+
+impl CpuState {
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, StateError> {
+        serde_json::to_vec(self).map_err(|error| StateError::Malformed(error.to_string()))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, StateError> {
+        let header: StateHeader = serde_json::from_slice(bytes)
+            .map_err(|error| StateError::Malformed(error.to_string()))?;
+
+        if header.version != STATE_VERSION {
+            return Err(StateError::UnsupportedVersion {
+                found: header.version,
+                expected: STATE_VERSION,
+            });
+        }
+
+        serde_json::from_slice(bytes).map_err(|error| StateError::Malformed(error.to_string()))
+    }
+}
+
+/// An error raised when a save state cannot be written or restored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateError {
+    /// The state was written by a different version of the format.
+    UnsupportedVersion { found: u32, expected: u32 },
+    /// The state could not be parsed.
+    Malformed(String),
+    /// The state parsed, but a field holds a value the CPU could never be in.
+    Invalid(&'static str),
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion { found, expected } => {
+                write!(
+                    f,
+                    "save state version {found} is not supported, expected {expected}"
+                )
+            }
+            Self::Malformed(message) => write!(f, "save state is malformed: {message}"),
+            Self::Invalid(field) => write!(f, "save state has an invalid {field}"),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
@@ -6,3 +6,97 @@ pub fn time_nanos() -> u128 {
         .unwrap()
         .as_nanos()
 }
+
+/// A source of time, in nanoseconds, used to pace instructions and the 60 Hz timers.
+pub trait Clock {
+    fn now_nanos(&self) -> u128;
+}
+
+/// Wall clock time, used when running a game in real time.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_nanos(&self) -> u128 {
+        time_nanos()
+    }
+}
+
+/// Time that only moves when advanced, for reproducible runs and tests.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct VirtualClock {
+    nanos: u128,
+}
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&mut self, nanos: u128) {
+        self.nanos += nanos;
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now_nanos(&self) -> u128 {
+        self.nanos
+    }
+}
+
+/// A clock running at an adjustable multiple of another clock's speed.
+///
+/// Because [`Cpu::process`] paces both instructions and the 60 Hz timers from the same clock,
+/// scaling the clock pauses, fast-forwards or slows down the whole machine consistently.
+///
+/// [`Cpu::process`]: crate::cpu::Cpu::process
+#[derive(Debug, Clone, Copy)]
+pub struct ScaledClock<C: Clock> {
+    source: C,
+    speed: f64,
+    source_base: u128,
+    base: u128,
+}
+
+impl<C: Clock> ScaledClock<C> {
+    pub fn new(source: C) -> Self {
+        let source_base = source.now_nanos();
+
+        Self {
+            source,
+            speed: 1.0,
+            source_base,
+            base: source_base,
+        }
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Change the speed from now on, where `0.0` pauses and `1.0` is the source clock's speed.
+    pub fn set_speed(&mut self, speed: f64) {
+        self.base = self.now_nanos();
+        self.source_base = self.source.now_nanos();
+        self.speed = speed.max(0.0);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.speed == 0.0
+    }
+
+    pub fn source_mut(&mut self) -> &mut C {
+        &mut self.source
+    }
+}
+
+impl<C: Clock> Clock for ScaledClock<C> {
+    fn now_nanos(&self) -> u128 {
+        let elapsed = self.source.now_nanos().saturating_sub(self.source_base);
+        self.base + (elapsed as f64 * self.speed) as u128
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/timer/timer_tests.rs"]
+mod timer_tests;
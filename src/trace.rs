@@ -0,0 +1,130 @@
+use crate::{constants::OPCODE_SIZE, cpu::Cpu, instruction::Instruction};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    ops::RangeInclusive,
+    path::Path,
+};
+
+/// Writes a line per executed instruction, for comparing a run against other emulators' logs.
+///
+/// Each line shows the machine as it was just before the instruction ran:
+///
+/// ```text
+/// 000000000042 PC:0204 OP:6A05 LD VA, #05           V:00 01 .. 0F I:0000 SP:0 DT:00 ST:00
+/// ```
+///
+/// Tracing stops once the size cap is reached or a write fails. The first write error is kept and
+/// returned by [`Tracer::finish`], since the CPU can't stop to report it.
+pub struct Tracer {
+    writer: Box<dyn Write>,
+    ranges: Vec<RangeInclusive<usize>>,
+    max_bytes: u64,
+    written: u64,
+    error: Option<io::Error>,
+}
+
+impl Tracer {
+    pub fn new(writer: impl Write + 'static, max_bytes: u64) -> Self {
+        Self {
+            writer: Box::new(writer),
+            ranges: Vec::new(),
+            max_bytes,
+            written: 0,
+            error: None,
+        }
+    }
+
+    /// Trace to a file, replacing it if it exists.
+    pub fn create(path: &Path, max_bytes: u64) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?), max_bytes))
+    }
+
+    /// Only trace instructions with a program counter in `range`.
+    ///
+    /// With several ranges, an instruction in any of them is traced. With none, everything is.
+    pub fn add_range(&mut self, range: RangeInclusive<usize>) {
+        self.ranges.push(range);
+    }
+
+    /// The number of bytes written so far.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Whether the size cap has been reached.
+    pub fn is_full(&self) -> bool {
+        self.written >= self.max_bytes
+    }
+
+    /// Record the instruction the CPU is about to execute.
+    pub fn trace(&mut self, cpu: &Cpu, instruction: Instruction) {
+        if self.error.is_some() || self.is_full() || !self.is_traced(cpu.pc()) {
+            return;
+        }
+
+        let line = Self::line(cpu, instruction);
+
+        /* a line that doesn't fit is dropped, so the file never goes over the cap */
+        if self.written + line.len() as u64 > self.max_bytes {
+            self.written = self.max_bytes;
+            return;
+        }
+
+        match self.writer.write_all(line.as_bytes()) {
+            Ok(()) => self.written += line.len() as u64,
+            Err(error) => self.error = Some(error),
+        }
+    }
+
+    /// Flush the trace, returning the first error hit while tracing.
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+
+        self.writer.flush()
+    }
+
+    /// Format one trace line, ending in a newline.
+    pub fn line(cpu: &Cpu, instruction: Instruction) -> String {
+        let mnemonic = match instruction {
+            Instruction::LoadILong => {
+                let addr = cpu.pc() + OPCODE_SIZE;
+                let long = cpu
+                    .ram()
+                    .get(addr..addr + OPCODE_SIZE)
+                    .map_or(0, |bytes| (bytes[0] as u16) << 8 | bytes[1] as u16);
+
+                format!("LD I, long #{long:04X}")
+            }
+            _ => instruction.to_string(),
+        };
+
+        let registers = cpu
+            .registers()
+            .iter()
+            .map(|value| format!("{value:02X}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        format!(
+            "{:012} PC:{:04X} OP:{:04X} {mnemonic:<20} V:{registers} I:{:04X} SP:{:X} DT:{:02X} ST:{:02X}\n",
+            cpu.cycles(),
+            cpu.pc(),
+            instruction.encode(),
+            cpu.i(),
+            cpu.sp(),
+            cpu.delay_timer(),
+            cpu.sound_timer(),
+        )
+    }
+
+    fn is_traced(&self, pc: usize) -> bool {
+        self.ranges.is_empty() || self.ranges.iter().any(|range| range.contains(&pc))
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/trace/trace_tests.rs"]
+mod trace_tests;
@@ -1,17 +1,26 @@
 use crate::{
-    constants::{CHIP8_ARCHIVE_RAW_URL, CHIP8_ARCHIVE_URL, TICKRATE},
+    buzzer::BuzzerSettings,
+    constants::{CHIP8_ARCHIVE_RAW_URL, CHIP8_ARCHIVE_URL, ROM_LIST_VERSION, TICKRATE},
     rom::Rom,
     types::Result,
 };
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{
     collections::HashMap,
-    io::{Cursor, Error, Read},
-    path::PathBuf,
+    io::{Cursor, Error},
+    path::{Path, PathBuf},
 };
 
 pub struct Utils;
 
+/// The rom list cached next to the executable, with the version of the code that built it.
+#[derive(Serialize, Deserialize)]
+struct RomList {
+    version: u32,
+    roms: Vec<Rom>,
+}
+
 impl Utils {
     pub fn exe_dir() -> std::result::Result<PathBuf, Error> {
         let mut path = std::env::current_exe()?;
@@ -25,6 +34,160 @@ impl Utils {
         Ok(path)
     }
 
+    pub fn states_dir() -> std::result::Result<PathBuf, Error> {
+        let mut path = Self::exe_dir()?;
+        path.push("states");
+        Ok(path)
+    }
+
+    /// The save state file for a numbered slot of a rom, creating the states directory if needed.
+    pub fn state_path(title: &str, slot: u8) -> std::result::Result<PathBuf, Error> {
+        let mut path = Self::states_dir()?;
+
+        if !path.exists() {
+            std::fs::create_dir(&path)?;
+        }
+
+        path.push(format!("{title}.{slot}.state"));
+        Ok(path)
+    }
+
+    pub fn profiles_dir() -> std::result::Result<PathBuf, Error> {
+        let mut path = Self::exe_dir()?;
+        path.push("profiles");
+        Ok(path)
+    }
+
+    /// The profile report file for a rom, creating the profiles directory if needed.
+    pub fn profile_path(title: &str) -> std::result::Result<PathBuf, Error> {
+        let mut path = Self::profiles_dir()?;
+
+        if !path.exists() {
+            std::fs::create_dir(&path)?;
+        }
+
+        path.push(format!("{title}.profile.json"));
+        Ok(path)
+    }
+
+    pub fn cheats_dir() -> std::result::Result<PathBuf, Error> {
+        let mut path = Self::exe_dir()?;
+        path.push("cheats");
+        Ok(path)
+    }
+
+    /// The cheat list file for a rom, creating the cheats directory if needed.
+    pub fn cheats_path(title: &str) -> std::result::Result<PathBuf, Error> {
+        let mut path = Self::cheats_dir()?;
+
+        if !path.exists() {
+            std::fs::create_dir(&path)?;
+        }
+
+        path.push(format!("{title}.cheats.json"));
+        Ok(path)
+    }
+
+    pub fn screenshots_dir() -> std::result::Result<PathBuf, Error> {
+        let mut path = Self::exe_dir()?;
+        path.push("screenshots");
+        Ok(path)
+    }
+
+    /// The PNG and PBM files for the first screenshot of a rom not taken yet, creating the
+    /// screenshots directory if needed.
+    pub fn screenshot_paths(title: &str) -> std::result::Result<[PathBuf; 2], Error> {
+        let dir = Self::screenshots_dir()?;
+
+        if !dir.exists() {
+            std::fs::create_dir(&dir)?;
+        }
+
+        Ok(Self::numbered_paths(&dir, title, ["png", "pbm"]))
+    }
+
+    pub fn recordings_dir() -> std::result::Result<PathBuf, Error> {
+        let mut path = Self::exe_dir()?;
+        path.push("recordings");
+        Ok(path)
+    }
+
+    /// The GIF and WAV files for the first recording of a rom not made yet, creating the
+    /// recordings directory if needed.
+    pub fn recording_paths(title: &str) -> std::result::Result<[PathBuf; 2], Error> {
+        let dir = Self::recordings_dir()?;
+
+        if !dir.exists() {
+            std::fs::create_dir(&dir)?;
+        }
+
+        Ok(Self::numbered_paths(&dir, title, ["gif", "wav"]))
+    }
+
+    /// Files named `title.number.extension` in `dir`, one for each extension, with the lowest
+    /// number none of them are taken for.
+    fn numbered_paths<const N: usize>(
+        dir: &Path,
+        title: &str,
+        extensions: [&str; N],
+    ) -> [PathBuf; N] {
+        let mut number = 0;
+
+        loop {
+            let paths =
+                extensions.map(|extension| dir.join(format!("{title}.{number}.{extension}")));
+
+            if paths.iter().all(|path| !path.exists()) {
+                return paths;
+            }
+
+            number += 1;
+        }
+    }
+
+    /// The buzzer settings used for every rom unless it overrides them.
+    pub fn audio_settings_path() -> std::result::Result<PathBuf, Error> {
+        let mut path = Self::exe_dir()?;
+        path.push("audio.json");
+        Ok(path)
+    }
+
+    pub fn audio_dir() -> std::result::Result<PathBuf, Error> {
+        let mut path = Self::exe_dir()?;
+        path.push("audio");
+        Ok(path)
+    }
+
+    /// The buzzer settings a rom overrides, creating the audio directory if needed.
+    pub fn rom_audio_path(title: &str) -> std::result::Result<PathBuf, Error> {
+        let mut path = Self::audio_dir()?;
+
+        if !path.exists() {
+            std::fs::create_dir(&path)?;
+        }
+
+        path.push(format!("{title}.audio.json"));
+        Ok(path)
+    }
+
+    /// The global buzzer settings with a rom's overrides on top, each only if its file exists.
+    pub fn buzzer_settings(title: &str) -> Result<BuzzerSettings> {
+        let global_path = Self::audio_settings_path()?;
+        let rom_path = Self::rom_audio_path(title)?;
+
+        let mut settings = if global_path.exists() {
+            BuzzerSettings::from_json(&std::fs::read_to_string(global_path)?)?
+        } else {
+            BuzzerSettings::default()
+        };
+
+        if rom_path.exists() {
+            settings = settings.with_overrides(&std::fs::read_to_string(rom_path)?)?;
+        }
+
+        Ok(settings)
+    }
+
     pub fn download_rom(name: &str) -> Result<()> {
         let mut rom_path = Utils::roms_dir()?;
 
@@ -48,36 +211,58 @@ impl Utils {
         Ok(())
     }
 
-    pub fn fetch_rom_list() -> Result<Vec<Rom>> {
-        let mut json_file = Utils::exe_dir()?;
-        json_file.push("roms.json");
+    pub fn rom_list_path() -> std::result::Result<PathBuf, Error> {
+        let mut path = Self::exe_dir()?;
+        path.push("roms.json");
+        Ok(path)
+    }
+
+    /// The roms in the rom list at `path`, or `None` if it's missing or was cached by an older
+    /// version and has to be fetched again.
+    fn read_rom_list(path: &Path) -> Option<Vec<Rom>> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        let list: RomList = serde_json::from_str(&contents).ok()?;
 
-        let mut roms: Vec<Rom> = Vec::with_capacity(43);
+        if list.version != ROM_LIST_VERSION {
+            return None;
+        }
+
+        Some(list.roms)
+    }
 
-        if !json_file.exists() {
-            let body =
-                reqwest::blocking::get(format!("{CHIP8_ARCHIVE_RAW_URL}/master/programs.json"))?
-                    .text()?;
-            let json: HashMap<String, Value> = serde_json::from_str(&body)?;
+    fn write_rom_list(path: &Path, roms: Vec<Rom>) -> Result<()> {
+        let list = RomList {
+            version: ROM_LIST_VERSION,
+            roms,
+        };
 
-            let mut filtered_roms: Vec<Rom> = json
-                .iter()
-                .filter(|(_name, item)| item["platform"].to_string().contains("chip8"))
-                .map(Rom::from)
-                .collect();
+        std::fs::write(path, serde_json::to_string_pretty(&list)?.as_bytes())?;
+        Ok(())
+    }
 
-            roms.append(&mut filtered_roms);
-            std::fs::write(json_file, serde_json::to_string_pretty(&roms)?.as_bytes())?;
+    pub fn fetch_rom_list() -> Result<Vec<Rom>> {
+        let json_file = Self::rom_list_path()?;
 
+        if let Some(roms) = Self::read_rom_list(&json_file) {
             return Ok(roms);
         }
 
-        let mut file = std::fs::File::open(json_file)?;
-        let mut file_contents = String::new();
-        file.read_to_string(&mut file_contents)?;
+        let body = reqwest::blocking::get(format!("{CHIP8_ARCHIVE_RAW_URL}/master/programs.json"))?
+            .text()?;
+        let json: HashMap<String, Value> = serde_json::from_str(&body)?;
 
-        let mut deserialized_roms = serde_json::from_str(&file_contents)?;
-        roms.append(&mut deserialized_roms);
+        let roms: Vec<Rom> = json
+            .iter()
+            .filter(|(_name, item)| {
+                let platform = item["platform"].to_string();
+                platform.contains("chip8")
+                    || platform.contains("schip")
+                    || platform.contains("xochip")
+            })
+            .map(Rom::from)
+            .collect();
+
+        Self::write_rom_list(&json_file, roms.clone())?;
 
         Ok(roms)
     }
@@ -98,28 +283,18 @@ impl Utils {
     }
 
     pub fn find_rom(name: &str) -> Result<Rom> {
-        let mut program_file = Self::exe_dir()?;
-        program_file.push("roms.json");
-
-        let mut file = std::fs::File::open(program_file)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
+        let roms = Self::read_rom_list(&Self::rom_list_path()?).unwrap_or_default();
 
-        let deserialized_roms: Vec<Rom> = serde_json::from_str(&contents)?;
-
-        let rom = deserialized_roms
-            .iter()
-            .find(|&rom| rom.title == name)
-            .cloned();
-
-        if rom.is_none() {
-            return Err("Could not find rom!".into());
-        }
-
-        Ok(rom.unwrap())
+        roms.into_iter()
+            .find(|rom| rom.title == name)
+            .ok_or_else(|| "Could not find rom!".into())
     }
 
     pub fn instruction_time_ns() -> u128 {
         1e9 as u128 / TICKRATE
     }
 }
+
+#[cfg(test)]
+#[path = "../tests/utils/utils_tests.rs"]
+mod utils_tests;
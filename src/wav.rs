@@ -0,0 +1,67 @@
+/// IEEE float samples, so the buzzer's output is written exactly.
+const FORMAT_IEEE_FLOAT: u16 = 3;
+
+const BITS_PER_SAMPLE: u16 = 32;
+
+/// Collects mono samples into a WAV file.
+#[derive(Debug, Clone)]
+pub struct WavRecorder {
+    sample_rate: u32,
+    samples: Vec<f32>,
+}
+
+impl WavRecorder {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, samples: &[f32]) {
+        self.samples.extend(samples);
+    }
+
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// The whole WAV file, holding every sample pushed.
+    pub fn finish(self) -> Vec<u8> {
+        let block_align = BITS_PER_SAMPLE / 8;
+        let data_size = self.samples.len() as u32 * block_align as u32;
+
+        let mut wav = b"RIFF".to_vec();
+        /* the WAVE id, the fmt and fact chunks, and the data chunk */
+        wav.extend((4 + 26 + 12 + 8 + data_size).to_le_bytes());
+        wav.extend(b"WAVE");
+
+        wav.extend(b"fmt ");
+        wav.extend(18u32.to_le_bytes());
+        wav.extend(FORMAT_IEEE_FLOAT.to_le_bytes());
+        wav.extend(1u16.to_le_bytes());
+        wav.extend(self.sample_rate.to_le_bytes());
+        wav.extend((self.sample_rate * block_align as u32).to_le_bytes());
+        wav.extend(block_align.to_le_bytes());
+        wav.extend(BITS_PER_SAMPLE.to_le_bytes());
+        wav.extend(0u16.to_le_bytes());
+
+        /* files that aren't PCM must say how many samples they hold */
+        wav.extend(b"fact");
+        wav.extend(4u32.to_le_bytes());
+        wav.extend((self.samples.len() as u32).to_le_bytes());
+
+        wav.extend(b"data");
+        wav.extend(data_size.to_le_bytes());
+
+        for sample in self.samples {
+            wav.extend(sample.to_le_bytes());
+        }
+
+        wav
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/wav/wav_tests.rs"]
+mod wav_tests;
@@ -0,0 +1,245 @@
+use super::*;
+use crate::cpu::Cpu;
+
+/// A buzzer with the default settings but no fade, so its samples are the bare wave.
+fn instant(sample_rate: u32) -> Buzzer {
+    let mut buzzer = Buzzer::new(sample_rate);
+    buzzer.set_settings(BuzzerSettings {
+        attack_ms: 0.0,
+        release_ms: 0.0,
+        ..BuzzerSettings::default()
+    });
+    buzzer
+}
+
+#[test]
+fn silent_until_beeping() {
+    let mut buzzer = Buzzer::new(44100);
+
+    assert!(buzzer.render_frame().iter().all(|&sample| sample == 0.0));
+}
+
+#[test]
+fn square_wave() {
+    let mut buzzer = instant(44100);
+    buzzer.set_beeping(true);
+
+    let samples = buzzer.render_frame();
+
+    assert_eq!(samples.len(), 735);
+    assert!(samples.iter().all(|&sample| sample.abs() == 0.25));
+    /* 440 Hz at 44.1 kHz is about 100 samples a cycle, half of them high */
+    assert!(samples[..50].iter().all(|&sample| sample > 0.0));
+    assert!(samples[52..100].iter().all(|&sample| sample < 0.0));
+}
+
+#[test]
+fn stopping_holds_phase() {
+    let mut continuous = instant(44100);
+    continuous.set_beeping(true);
+    let mut expected = vec![0.0; 80];
+    continuous.fill(&mut expected);
+
+    let mut interrupted = instant(44100);
+    interrupted.set_beeping(true);
+    let mut first = vec![0.0; 30];
+    interrupted.fill(&mut first);
+
+    interrupted.set_beeping(false);
+    let mut silence = vec![1.0; 10];
+    interrupted.fill(&mut silence);
+
+    interrupted.set_beeping(true);
+    let mut rest = vec![0.0; 50];
+    interrupted.fill(&mut rest);
+
+    assert_eq!(silence, [0.0; 10]);
+    assert_eq!([first, rest].concat(), expected);
+}
+
+#[test]
+fn pattern() {
+    let mut buzzer = instant(4000);
+    /* one bit lit in every byte, at the default pitch of 4000 bits a second */
+    buzzer.set_pattern(Some([0x80; AUDIO_PATTERN_SIZE]), 64);
+    buzzer.set_beeping(true);
+
+    let mut samples = vec![0.0; 16];
+    buzzer.fill(&mut samples);
+
+    assert!(samples[0] > 0.0);
+    assert!(samples[1..8].iter().all(|&sample| sample < 0.0));
+    assert!(samples[8] > 0.0);
+}
+
+#[test]
+fn frames_add_up_to_sample_rate() {
+    let mut buzzer = Buzzer::new(1000);
+
+    let lengths: Vec<usize> = (0..60).map(|_| buzzer.render_frame().len()).collect();
+
+    assert!(lengths.iter().all(|&len| len == 16 || len == 17));
+    assert_eq!(lengths.iter().sum::<usize>(), 1000);
+}
+
+#[test]
+fn update() {
+    let mut cpu = Cpu::new();
+    cpu.load(&[
+        0x60, 0x02, // LD V0, 2
+        0xF0, 0x18, // LD ST, V0
+    ])
+    .unwrap();
+
+    let mut buzzer = Buzzer::new(44100);
+    cpu.run_frame([false; 16], 2).unwrap();
+    buzzer.update(&cpu.output());
+
+    assert!(buzzer.is_beeping());
+    assert!(buzzer.render_frame().iter().any(|&sample| sample != 0.0));
+
+    cpu.run_frame([false; 16], 0).unwrap();
+    buzzer.update(&cpu.output());
+
+    assert!(!buzzer.is_beeping());
+}
+
+#[test]
+fn attack_and_release() {
+    let mut buzzer = Buzzer::new(1000);
+    /* slow enough that the wave stays high throughout */
+    buzzer.set_settings(BuzzerSettings {
+        frequency: 1.0,
+        volume: 1.0,
+        attack_ms: 4.0,
+        release_ms: 2.0,
+        ..BuzzerSettings::default()
+    });
+    buzzer.set_beeping(true);
+
+    let mut samples = vec![0.0; 6];
+    buzzer.fill(&mut samples);
+
+    assert_eq!(samples, [0.25, 0.5, 0.75, 1.0, 1.0, 1.0]);
+
+    buzzer.set_beeping(false);
+    buzzer.fill(&mut samples);
+
+    assert_eq!(samples[..3], [0.5, 0.0, 0.0]);
+}
+
+#[test]
+fn muted() {
+    let mut buzzer = instant(44100);
+    buzzer.set_settings(BuzzerSettings {
+        muted: true,
+        ..*buzzer.settings()
+    });
+    buzzer.set_beeping(true);
+
+    assert!(buzzer.render_frame().iter().all(|&sample| sample == 0.0));
+}
+
+#[test]
+fn waveforms() {
+    let wave = |waveform| {
+        let mut buzzer = instant(8);
+        buzzer.set_settings(BuzzerSettings {
+            frequency: 1.0,
+            volume: 1.0,
+            waveform,
+            ..*buzzer.settings()
+        });
+        buzzer.set_beeping(true);
+
+        let mut samples = vec![0.0; 8];
+        buzzer.fill(&mut samples);
+        samples
+    };
+
+    assert_eq!(
+        wave(Waveform::Triangle),
+        [1.0, 0.5, 0.0, -0.5, -1.0, -0.5, 0.0, 0.5]
+    );
+    assert_eq!(
+        wave(Waveform::Square),
+        [1.0, 1.0, 1.0, 1.0, 1.0, -1.0, -1.0, -1.0]
+    );
+
+    let sine = wave(Waveform::Sine);
+    assert!(sine[0].abs() < 1e-6);
+    assert!((sine[2] - 1.0).abs() < 1e-6);
+    assert!((sine[6] + 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn noise() {
+    let noise = || {
+        let mut buzzer = instant(1000);
+        buzzer.set_settings(BuzzerSettings {
+            frequency: 1000.0,
+            volume: 1.0,
+            waveform: Waveform::Noise,
+            ..*buzzer.settings()
+        });
+        buzzer.set_beeping(true);
+
+        (0..60)
+            .flat_map(|_| buzzer.render_frame())
+            .collect::<Vec<f32>>()
+    };
+
+    let samples = noise();
+    let high = samples.iter().filter(|&&sample| sample > 0.0).count();
+
+    assert!(samples.iter().all(|&sample| sample.abs() == 1.0));
+    assert!((400..600).contains(&high), "{high} of {}", samples.len());
+    /* the same settings make the same noise */
+    assert_eq!(samples, noise());
+}
+
+#[test]
+fn waveform_from_str() {
+    assert_eq!("sine".parse(), Ok(Waveform::Sine));
+    assert_eq!(Waveform::Noise.to_string(), "noise");
+    assert!("saw".parse::<Waveform>().is_err());
+}
+
+#[test]
+fn settings_overrides() {
+    let global = BuzzerSettings {
+        volume: 0.5,
+        ..BuzzerSettings::default()
+    };
+
+    let settings = global
+        .with_overrides(r#"{ "frequency": 220.0, "waveform": "triangle" }"#)
+        .unwrap();
+
+    assert_eq!(
+        settings,
+        BuzzerSettings {
+            frequency: 220.0,
+            waveform: Waveform::Triangle,
+            ..global
+        }
+    );
+    assert!(global.with_overrides(r#"{ "waveform": "saw" }"#).is_err());
+}
+
+#[test]
+fn settings_json() {
+    let settings = BuzzerSettings {
+        muted: true,
+        ..BuzzerSettings::default()
+    };
+
+    assert_eq!(
+        BuzzerSettings::from_json(&settings.to_json().unwrap()).unwrap(),
+        settings
+    );
+    assert_eq!(
+        BuzzerSettings::from_json("{}").unwrap(),
+        BuzzerSettings::default()
+    );
+}
@@ -0,0 +1,130 @@
+use super::*;
+
+fn locations(search: &CheatSearch) -> Vec<Location> {
+    search
+        .candidates()
+        .iter()
+        .map(|candidate| candidate.location)
+        .collect()
+}
+
+#[test]
+fn location_read_write() {
+    let mut cpu = Cpu::new();
+
+    Location::Ram(0x300).write(&mut cpu, 0x42);
+    Location::Register(0xA).write(&mut cpu, 0x17);
+
+    assert_eq!(cpu.ram()[0x300], 0x42);
+    assert_eq!(cpu.registers()[0xA], 0x17);
+    assert_eq!(Location::Ram(0x300).read(&cpu), 0x42);
+    assert_eq!(Location::Register(0xA).read(&cpu), 0x17);
+}
+
+#[test]
+fn location_out_of_range() {
+    let mut cpu = Cpu::new();
+
+    Location::Ram(RAM_SIZE).write(&mut cpu, 0x42);
+    Location::Register(16).write(&mut cpu, 0x42);
+
+    assert_eq!(Location::Ram(RAM_SIZE).read(&cpu), 0);
+    assert_eq!(Location::Register(16).read(&cpu), 0);
+}
+
+#[test]
+fn location_display() {
+    assert_eq!(Location::Ram(0x2A0).to_string(), "02A0");
+    assert_eq!(Location::Register(0xF).to_string(), "VF");
+}
+
+#[test]
+fn search_starts_with_everything() {
+    let search = CheatSearch::new(&Cpu::new());
+
+    assert_eq!(search.candidates().len(), 16 + RAM_SIZE);
+    assert_eq!(search.searches(), 0);
+}
+
+#[test]
+fn search_narrows() {
+    let mut cpu = Cpu::new();
+    cpu.ram_mut()[0x300] = 3;
+    cpu.ram_mut()[0x301] = 3;
+    let mut search = CheatSearch::new(&cpu);
+
+    /* a life is lost */
+    cpu.ram_mut()[0x300] = 2;
+    cpu.registers_mut()[5] = 9;
+    search.filter(&cpu, SearchFilter::Changed);
+
+    assert_eq!(
+        locations(&search),
+        vec![Location::Register(5), Location::Ram(0x300)]
+    );
+
+    /* nothing happens */
+    search.filter(&cpu, SearchFilter::Unchanged);
+    assert_eq!(search.candidates().len(), 2);
+
+    search.filter(&cpu, SearchFilter::Equal(2));
+
+    assert_eq!(locations(&search), vec![Location::Ram(0x300)]);
+    assert_eq!(search.candidates()[0].value, 2);
+    assert_eq!(search.searches(), 3);
+}
+
+#[test]
+fn search_compares_with_last_search() {
+    let mut cpu = Cpu::new();
+    let mut search = CheatSearch::new(&cpu);
+
+    cpu.ram_mut()[0x300] = 1;
+    search.filter(&cpu, SearchFilter::Changed);
+    search.filter(&cpu, SearchFilter::Changed);
+
+    assert!(search.candidates().is_empty());
+}
+
+#[test]
+fn apply_writes_frozen_cheats() {
+    let mut cpu = Cpu::new();
+    let cheats = CheatList {
+        cheats: vec![
+            Cheat {
+                name: String::from("lives"),
+                location: Location::Ram(0x300),
+                value: 9,
+                frozen: true,
+            },
+            Cheat {
+                name: String::from("score"),
+                location: Location::Register(3),
+                value: 0x99,
+                frozen: false,
+            },
+        ],
+    };
+
+    cheats.apply(&mut cpu);
+
+    assert_eq!(cpu.ram()[0x300], 9);
+    assert_eq!(cpu.registers()[3], 0);
+}
+
+#[test]
+fn json_round_trip() {
+    let cheats = CheatList {
+        cheats: vec![Cheat {
+            name: String::from("lives"),
+            location: Location::Register(0xE),
+            value: 3,
+            frozen: true,
+        }],
+    };
+
+    assert_eq!(
+        CheatList::from_json(&cheats.to_json().unwrap()).unwrap(),
+        cheats
+    );
+}
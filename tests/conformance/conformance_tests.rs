@@ -0,0 +1,131 @@
+use super::*;
+use std::path::PathBuf;
+
+fn conformance_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/conformance")
+}
+
+/// Run a ROM from `tests/conformance/roms` and compare its last screen with the golden image of
+/// the same name. Run with `UPDATE_GOLDEN=1` to write the golden images after a deliberate change.
+fn check(name: &str, run: ConformanceRun) {
+    let dir = conformance_dir();
+    let rom = std::fs::read(dir.join(format!("roms/{name}.ch8"))).unwrap();
+    let cpu = run.run(&rom).unwrap();
+
+    if let Err(error) = check_golden(&screen(&cpu), &dir.join(format!("golden/{name}.txt"))) {
+        panic!("{name}: {error}");
+    }
+}
+
+// Draws the digits 0-F in two rows with LD F, Vx.
+#[test]
+fn font() {
+    check("font", ConformanceRun::default());
+}
+
+// Runs ADD, SUB, SUBN, SHR and SHL on operands that do and don't carry, drawing VF after each.
+// The digits read 0 1 1 0 0 1 1 0 1 0.
+#[test]
+fn flags() {
+    check("flags", ConformanceRun::default());
+}
+
+// Waits for a key with LD Vx, K and draws it, then waits for it to be released.
+// The digits read A 3 C.
+#[test]
+fn keypad() {
+    check(
+        "keypad",
+        ConformanceRun {
+            presses: vec![
+                KeyPress {
+                    key: 0xA,
+                    frame: 2,
+                    frames: 3,
+                },
+                KeyPress {
+                    key: 0x3,
+                    frame: 10,
+                    frames: 2,
+                },
+                KeyPress {
+                    key: 0xC,
+                    frame: 20,
+                    frames: 1,
+                },
+            ],
+            ..ConformanceRun::default()
+        },
+    );
+}
+
+// Switches to high resolution, draws a big 5 and scrolls it right 4 and down 4.
+#[test]
+fn hires() {
+    check(
+        "hires",
+        ConformanceRun {
+            quirks: Quirks::SUPER_CHIP,
+            ..ConformanceRun::default()
+        },
+    );
+}
+
+#[test]
+fn keypad_schedule() {
+    let run = ConformanceRun {
+        presses: vec![KeyPress {
+            key: 4,
+            frame: 2,
+            frames: 2,
+        }],
+        ..ConformanceRun::default()
+    };
+
+    assert!(!run.keypad(1)[4]);
+    assert!(run.keypad(2)[4]);
+    assert!(run.keypad(3)[4]);
+    assert!(!run.keypad(4)[4]);
+}
+
+#[test]
+fn screen_size_follows_resolution() {
+    let mut cpu = Cpu::new();
+    let lores = screen(&cpu);
+
+    assert_eq!(lores.lines().count(), VRAM_HEIGHT);
+    assert!(lores.lines().all(|row| row == ".".repeat(VRAM_WIDTH)));
+
+    cpu.load(&[0x00, 0xFF]).unwrap();
+    cpu.step().unwrap();
+
+    assert_eq!(screen(&cpu).lines().count(), HIRES_VRAM_HEIGHT);
+}
+
+#[test]
+fn compare_reports_differing_rows() {
+    let golden = "....\n.##.\n....\n";
+    let actual = "....\n.#..\n...#\n";
+
+    let Err(ConformanceError::Mismatch { pixels, diff }) = compare(actual, golden) else {
+        panic!("screens should differ");
+    };
+
+    assert_eq!(pixels, 2);
+    assert_eq!(
+        diff,
+        " 1 expected .##.\n 1 found    .#..\n 2 expected ....\n 2 found    ...#\n"
+    );
+    assert_eq!(compare(golden, golden), Ok(()));
+}
+
+#[test]
+fn compare_checks_size() {
+    assert_eq!(
+        compare("...\n", "....\n....\n"),
+        Err(ConformanceError::SizeMismatch {
+            found: (3, 1),
+            expected: (4, 2),
+        })
+    );
+}
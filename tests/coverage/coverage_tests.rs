@@ -0,0 +1,147 @@
+use super::*;
+use crate::cpu::Cpu;
+
+fn covered_cpu(rom: &[u8]) -> Cpu {
+    let mut cpu = Cpu::new();
+    cpu.load(rom).unwrap();
+    cpu.set_coverage(Some(Coverage::new()));
+    cpu
+}
+
+fn run(cpu: &mut Cpu, steps: usize) {
+    for _ in 0..steps {
+        cpu.step().unwrap();
+    }
+}
+
+// 0x200 SE V0, 1
+// 0x202 JP 0x206
+// 0x204 LD V0, 1
+// 0x206 JP 0x206
+const DEAD_CODE: [u8; 8] = [0x30, 0x01, 0x12, 0x06, 0x60, 0x01, 0x12, 0x06];
+
+#[test]
+fn executed() {
+    let mut cpu = covered_cpu(&DEAD_CODE);
+    run(&mut cpu, 3);
+
+    let coverage = cpu.coverage().unwrap();
+
+    assert!(coverage.is_executed(0x200));
+    assert!(coverage.is_executed(0x203));
+    assert!(!coverage.is_executed(0x204));
+    assert!(!coverage.is_executed(0x205));
+    assert!(coverage.is_executed(0x206));
+    assert!(coverage.is_executed(0x207));
+}
+
+#[test]
+fn long_load_executes_four_bytes() {
+    let mut cpu = covered_cpu(&[0xF0, 0x00, 0x03, 0x00]);
+    run(&mut cpu, 1);
+
+    let coverage = cpu.coverage().unwrap();
+
+    assert!((0x200..0x204).all(|addr| coverage.is_executed(addr)));
+    assert!(!coverage.is_read(0x300));
+}
+
+#[test]
+fn reads_and_writes() {
+    // 0x200 LD I, 0x300
+    // 0x202 LD B, V2
+    // 0x204 LD I, 0x310
+    // 0x206 LD V1, [I]
+    // 0x208 LD I, 0x320
+    // 0x20A DRW V0, V0, 5
+    // 0x20C LD I, 0x330
+    // 0x20E LD [I], V1
+    let mut cpu = covered_cpu(&[
+        0xA3, 0x00, 0xF2, 0x33, 0xA3, 0x10, 0xF1, 0x65, 0xA3, 0x20, 0xD0, 0x05, 0xA3, 0x30, 0xF1,
+        0x55,
+    ]);
+    run(&mut cpu, 8);
+
+    let coverage = cpu.coverage().unwrap();
+
+    assert!((0x300..0x303).all(|addr| coverage.is_written(addr)));
+    assert!(!coverage.is_written(0x303));
+
+    assert!((0x310..0x312).all(|addr| coverage.is_read(addr)));
+    assert!(!coverage.is_read(0x312));
+
+    assert!((0x320..0x325).all(|addr| coverage.is_read(addr)));
+    assert!(!coverage.is_read(0x325));
+
+    assert!((0x330..0x332).all(|addr| coverage.is_written(addr)));
+    assert!(!coverage.is_read(0x330));
+    assert!(!coverage.is_executed(0x300));
+}
+
+#[test]
+fn save_writes_register_range() {
+    // 0x200 LD I, 0x300
+    // 0x202 SAVE V5 - V2
+    let mut cpu = covered_cpu(&[0xA3, 0x00, 0x55, 0x22]);
+    run(&mut cpu, 2);
+
+    let coverage = cpu.coverage().unwrap();
+
+    assert!((0x300..0x304).all(|addr| coverage.is_written(addr)));
+    assert!(!coverage.is_written(0x304));
+    assert!(!coverage.is_read(0x300));
+}
+
+#[test]
+fn load_reads_register_range() {
+    // 0x200 LD I, 0x310
+    // 0x202 LOAD V1 - V3
+    let mut cpu = covered_cpu(&[0xA3, 0x10, 0x51, 0x33]);
+    run(&mut cpu, 2);
+
+    let coverage = cpu.coverage().unwrap();
+
+    assert!((0x310..0x313).all(|addr| coverage.is_read(addr)));
+    assert!(!coverage.is_read(0x313));
+    assert!(!coverage.is_written(0x310));
+}
+
+#[test]
+fn big_sprite_on_both_planes() {
+    // 0x200 PLANE 3
+    // 0x202 LD I, 0x300
+    // 0x204 DRW V0, V0, 0
+    let mut cpu = covered_cpu(&[0xF3, 0x01, 0xA3, 0x00, 0xD0, 0x00]);
+    run(&mut cpu, 3);
+
+    let coverage = cpu.coverage().unwrap();
+
+    assert!((0x300..0x340).all(|addr| coverage.is_read(addr)));
+    assert!(!coverage.is_read(0x340));
+}
+
+#[test]
+fn reset() {
+    let mut cpu = covered_cpu(&DEAD_CODE);
+    run(&mut cpu, 1);
+
+    let mut coverage = cpu.coverage().unwrap().clone();
+    coverage.reset();
+
+    assert!(!coverage.is_executed(0x200));
+}
+
+#[test]
+fn report() {
+    let mut cpu = covered_cpu(&DEAD_CODE);
+    run(&mut cpu, 3);
+
+    let report = cpu.coverage().unwrap().report(&DEAD_CODE);
+
+    assert!(report.contains(";     6 executed (75.0%)"));
+    assert!(report.contains(";     2 untouched (25.0%)"));
+    assert!(report.contains(";     1 instructions never executed"));
+    assert!(report.contains(" X-- 0200  3001"));
+    assert!(report.contains("!--- 0204  6001"));
+    assert!(report.contains(" X-- 0206  1206"));
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::{random::RandomMode, timer::VirtualClock};
 
 #[test]
 fn loads_fonts_top_of_ram() {
@@ -11,3 +12,101 @@ fn loads_fonts_top_of_ram() {
     assert_eq!(zero, [0xF0, 0x90, 0x90, 0x90, 0xF0]);
     assert_eq!(f, [0xF0, 0x80, 0xF0, 0x80, 0x80]);
 }
+
+#[test]
+fn loads_rom_bytes_at_program_start() {
+    let mut cpu = Cpu::new();
+    cpu.load(&[0x60, 0x2A, 0x12, 0x00]).unwrap();
+
+    assert_eq!(cpu.ram()[0x200..0x204], [0x60, 0x2A, 0x12, 0x00]);
+    assert_eq!(cpu.ram()[0..5], [0xF0, 0x90, 0x90, 0x90, 0xF0]);
+}
+
+#[test]
+fn steps_one_instruction() {
+    let mut cpu = Cpu::new();
+    cpu.load(&[0x60, 0x2A, 0x12, 0x00]).unwrap();
+
+    cpu.step().unwrap();
+    assert_eq!(cpu.registers()[0], 0x2A);
+    assert_eq!(cpu.pc(), 0x202);
+
+    cpu.step().unwrap();
+    assert_eq!(cpu.pc(), 0x200);
+}
+
+#[test]
+fn step_waits_for_keypad() {
+    let mut cpu = Cpu::new();
+    cpu.load(&[0xF3, 0x0A, 0x00, 0xE0]).unwrap();
+
+    cpu.step().unwrap();
+    cpu.step().unwrap();
+    assert!(cpu.is_waiting_for_key());
+    assert_eq!(cpu.pc(), 0x202);
+
+    cpu.set_key(0xB, true);
+    cpu.step().unwrap();
+    assert!(!cpu.is_waiting_for_key());
+    assert_eq!(cpu.registers()[3], 0xB);
+}
+
+#[test]
+fn process_follows_virtual_clock() {
+    let mut cpu = Cpu::new();
+    let mut clock = VirtualClock::new();
+    cpu.load(&[0x60, 0x05, 0xF0, 0x15, 0x12, 0x04]).unwrap();
+
+    // Not enough time has passed to run an instruction
+    cpu.process([false; 16], 1_000, &clock).unwrap();
+    assert_eq!(cpu.pc(), 0x200);
+
+    clock.advance(1_000);
+    cpu.process([false; 16], 1_000, &clock).unwrap();
+    assert_eq!(cpu.pc(), 0x202);
+
+    // No time has passed, so nothing runs
+    cpu.process([false; 16], 1_000, &clock).unwrap();
+    assert_eq!(cpu.pc(), 0x202);
+
+    clock.advance(1_000);
+    cpu.process([false; 16], 1_000, &clock).unwrap();
+    assert_eq!(cpu.delay_timer(), 5);
+
+    clock.advance(TIMER_INTERVAL_NS);
+    cpu.process([false; 16], 1_000, &clock).unwrap();
+    assert_eq!(cpu.delay_timer(), 4);
+}
+
+#[test]
+fn run_frame_ticks_timers_once() {
+    let mut cpu = Cpu::new();
+    cpu.load(&[0x60, 0x05, 0xF0, 0x15, 0xF0, 0x18, 0x12, 0x06]).unwrap();
+
+    let output = cpu.run_frame([false; 16], 10).unwrap();
+    assert!(output.should_beep);
+
+    assert_eq!(cpu.delay_timer(), 4);
+    assert_eq!(cpu.sound_timer(), 4);
+    assert_eq!(cpu.pc(), 0x206);
+}
+
+#[test]
+fn run_frame_is_deterministic() {
+    let rom = [0xC0, 0xFF, 0x70, 0x01, 0x12, 0x02];
+    let mut first = Cpu::new();
+    let mut second = Cpu::new();
+    first.load(&rom).unwrap();
+    second.load(&rom).unwrap();
+    first.set_random(Random::new(42, RandomMode::Modern));
+    second.set_random(Random::new(42, RandomMode::Modern));
+
+    for _ in 0..10 {
+        first.run_frame([false; 16], 7).unwrap();
+        second.run_frame([false; 16], 7).unwrap();
+    }
+
+    assert_eq!(first.pc(), second.pc());
+    assert_eq!(first.delay_timer(), second.delay_timer());
+    assert_eq!(first.registers(), second.registers());
+}
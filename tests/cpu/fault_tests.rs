@@ -0,0 +1,166 @@
+use super::*;
+use crate::cpu::opcode_helper::OpcodeHelper;
+
+#[test]
+fn stack_underflow() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+    let pc = helper.cpu.pc;
+
+    // RET with nothing on the stack
+    let fault = helper.try_opcode(0x00EE);
+    assert_eq!(fault, Err(CpuFault::StackUnderflow { pc }));
+    helper.assert_pc_value(pc);
+    helper.assert_sp_value(0);
+}
+
+#[test]
+fn stack_overflow() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+
+    // CALL the current address until the stack is full
+    for _ in 0..11 {
+        let pc = helper.cpu.pc as u16;
+        helper.call_addr(pc);
+    }
+
+    let pc = helper.cpu.pc;
+    let fault = helper.try_opcode(0x2000 | pc as u16);
+    assert_eq!(fault, Err(CpuFault::StackOverflow { pc }));
+    helper.assert_sp_value(11);
+}
+
+fn extended_memory_cpu() -> Cpu {
+    let mut cpu = Cpu::new();
+    cpu.set_quirks(Quirks {
+        extended_memory: true,
+        ..Quirks::default()
+    });
+    cpu
+}
+
+#[test]
+fn memory_out_of_range() {
+    let mut helper = OpcodeHelper {
+        cpu: extended_memory_cpu(),
+    };
+    let pc = helper.cpu.pc;
+
+    helper.load_i_long(0xFFFE);
+    let pc = pc + OPCODE_SIZE * 2;
+
+    // LD B, Vx
+    let fault = helper.try_opcode(0xF033);
+    assert_eq!(
+        fault,
+        Err(CpuFault::MemoryOutOfRange {
+            pc,
+            addr: RAM_SIZE
+        })
+    );
+
+    // LD [I], Vx
+    assert!(helper.try_opcode(0xF255).is_err());
+
+    // LD Vx, [I]
+    assert!(helper.try_opcode(0xF265).is_err());
+
+    // DRW Vx, Vy, nibble
+    assert!(helper.try_opcode(0xD015).is_err());
+
+    helper.assert_pc_value(pc);
+}
+
+#[test]
+fn chip8_memory_out_of_range() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+    let pc = helper.cpu.pc;
+
+    helper.load_i_register(0xFFE);
+    let pc = pc + OPCODE_SIZE;
+
+    // LD B, Vx
+    let fault = helper.try_opcode(0xF033);
+    assert_eq!(
+        fault,
+        Err(CpuFault::MemoryOutOfRange {
+            pc,
+            addr: CHIP8_RAM_SIZE
+        })
+    );
+
+    // LD [I], Vx
+    assert!(helper.try_opcode(0xF255).is_err());
+
+    // LD Vx, [I]
+    assert!(helper.try_opcode(0xF265).is_err());
+
+    // DRW Vx, Vy, nibble
+    assert!(helper.try_opcode(0xD015).is_err());
+
+    helper.assert_pc_value(pc);
+}
+
+#[test]
+fn opcode_out_of_range() {
+    let mut cpu = extended_memory_cpu();
+    cpu.pc = RAM_SIZE - 1;
+
+    // Only the high byte of the opcode is in ram
+    assert_eq!(
+        cpu.step(),
+        Err(CpuFault::MemoryOutOfRange {
+            pc: RAM_SIZE - 1,
+            addr: RAM_SIZE
+        })
+    );
+}
+
+#[test]
+fn unknown_opcode() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+    let pc = helper.cpu.pc;
+
+    let fault = helper.try_opcode(0x5121);
+    assert_eq!(fault, Err(CpuFault::UnknownOpcode { pc, opcode: 0x5121 }));
+    helper.assert_pc_value(pc);
+}
+
+#[test]
+fn rom_too_large() {
+    let mut cpu = extended_memory_cpu();
+    let rom = vec![0; RAM_SIZE - 0x200 + 1];
+
+    assert_eq!(
+        cpu.load(&rom),
+        Err(CpuFault::RomTooLarge {
+            size: rom.len(),
+            max: RAM_SIZE - 0x200
+        })
+    );
+    assert!(cpu.load(&rom[1..]).is_ok());
+}
+
+#[test]
+fn chip8_rom_too_large() {
+    let mut cpu = Cpu::new();
+    let rom = vec![0; CHIP8_RAM_SIZE - 0x200 + 1];
+
+    assert_eq!(
+        cpu.load(&rom),
+        Err(CpuFault::RomTooLarge {
+            size: rom.len(),
+            max: 3584
+        })
+    );
+    assert!(cpu.load(&rom[1..]).is_ok());
+}
+
+#[test]
+fn fault_messages() {
+    let fault = CpuFault::UnknownOpcode {
+        pc: 0x2A4,
+        opcode: 0x5121,
+    };
+
+    assert_eq!(fault.to_string(), "unknown opcode 0x5121 at 0x2A4");
+}
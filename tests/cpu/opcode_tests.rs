@@ -1,10 +1,7 @@
 use super::*;
-use crate::cpu::opcode_tests::opcode_helper::OpcodeHelper;
+use crate::cpu::opcode_helper::OpcodeHelper;
 use all_asserts::{assert_false, assert_true};
 
-#[path = "../helpers/opcode_helper.rs"]
-mod opcode_helper;
-
 #[test]
 fn clear_screen() {
     let mut helper = OpcodeHelper { cpu: Cpu::new() };
@@ -0,0 +1,169 @@
+use super::*;
+use crate::cpu::opcode_helper::OpcodeHelper;
+
+fn helper_with(quirks: Quirks) -> OpcodeHelper {
+    let mut cpu = Cpu::new();
+    cpu.set_quirks(quirks);
+
+    OpcodeHelper { cpu }
+}
+
+#[test]
+fn shift_uses_vy() {
+    let mut helper = helper_with(Quirks {
+        shift_uses_vy: true,
+        ..Quirks::default()
+    });
+
+    // LD V[n], byte
+    helper.load_byte(1, 0xFF);
+    helper.load_byte(2, 0x81);
+
+    // SHR Vx {, Vy}
+    helper.process_opcode(0x8126);
+    helper.assert_register_value(1, 0x40);
+    helper.assert_register_value(2, 0x81);
+    helper.assert_borrow(true);
+
+    // SHL Vx {, Vy}
+    helper.process_opcode(0x812E);
+    helper.assert_register_value(1, 0x02);
+    helper.assert_borrow(true);
+}
+
+#[test]
+fn load_store_increments_i() {
+    let mut helper = helper_with(Quirks {
+        load_store_increments_i: true,
+        ..Quirks::default()
+    });
+
+    helper.load_i_register(0x300);
+
+    // LD [I], Vx
+    helper.store_at_i(3);
+    helper.assert_i_register_value(0x304);
+
+    // LD Vx, [I]
+    helper.read_from_i(1);
+    helper.assert_i_register_value(0x306);
+}
+
+#[test]
+fn load_store_increments_i_by_x() {
+    let mut helper = helper_with(Quirks::CHIP_48);
+
+    helper.load_i_register(0x300);
+
+    // LD [I], Vx
+    helper.store_at_i(3);
+    helper.assert_i_register_value(0x303);
+
+    // LD Vx, [I]
+    helper.read_from_i(1);
+    helper.assert_i_register_value(0x304);
+}
+
+#[test]
+fn jump_uses_vx() {
+    let mut helper = helper_with(Quirks {
+        jump_uses_vx: true,
+        ..Quirks::default()
+    });
+
+    helper.load_byte(0, 0x10);
+    helper.load_byte(2, 0x04);
+
+    // JP V2, 0x2C5
+    helper.jump_addr_v0_offset(0x2C5);
+    helper.assert_pc_value(0x2C9);
+}
+
+#[test]
+fn logic_resets_vf() {
+    let mut helper = helper_with(Quirks {
+        logic_resets_vf: true,
+        ..Quirks::default()
+    });
+
+    helper.load_byte(0xF, 1);
+    helper.bitwise_or_registers(1, 2);
+    helper.assert_borrow(false);
+
+    helper.load_byte(0xF, 1);
+    helper.bitwise_and_registers(1, 2);
+    helper.assert_borrow(false);
+
+    helper.load_byte(0xF, 1);
+    helper.bitwise_xor_registers(1, 2);
+    helper.assert_borrow(false);
+}
+
+#[test]
+fn clips_sprites_at_screen_edge() {
+    let mut helper = helper_with(Quirks {
+        clip_sprites: true,
+        ..Quirks::default()
+    });
+
+    helper.cpu.load_fonts();
+
+    // Draw "0" with its right half past the edge of the screen
+    helper.load_byte(0, VRAM_WIDTH - 2);
+    helper.load_byte(1, 0);
+    helper.load_i_register(0);
+    helper.draw(0, 1, 5);
+
+    let wrapped = helper.cpu.vram.iter().any(|row| row[0..4].contains(&1));
+    assert!(!wrapped);
+    assert_eq!(helper.cpu.vram[0][VRAM_WIDTH - 1], 1);
+}
+
+#[test]
+fn wraps_sprites_at_screen_edge() {
+    let mut helper = helper_with(Quirks::default());
+
+    helper.cpu.load_fonts();
+
+    helper.load_byte(0, VRAM_WIDTH - 2);
+    helper.load_byte(1, 0);
+    helper.load_i_register(0);
+    helper.draw(0, 1, 5);
+
+    assert_eq!(helper.cpu.vram[0][0], 1);
+}
+
+#[test]
+fn display_wait_draws_once_per_frame() {
+    let mut cpu = Cpu::new();
+    cpu.set_quirks(Quirks {
+        display_wait: true,
+        ..Quirks::default()
+    });
+
+    // DRW V0, V0, 1; ADD V1, 1; JP 0x200
+    cpu.load(&[0xD0, 0x01, 0x71, 0x01, 0x12, 0x00]).unwrap();
+
+    cpu.run_frame([false; 16], 10).unwrap();
+    assert_eq!(cpu.registers()[1], 0);
+    assert_eq!(cpu.pc(), 0x202);
+
+    cpu.run_frame([false; 16], 10).unwrap();
+    assert_eq!(cpu.registers()[1], 1);
+}
+
+#[test]
+fn extended_memory() {
+    assert_eq!(helper_with(Quirks::COSMAC_VIP).cpu.memory_size(), 0x1000);
+    assert_eq!(helper_with(Quirks::SUPER_CHIP).cpu.memory_size(), 0x1000);
+    assert_eq!(helper_with(Quirks::XO_CHIP).cpu.memory_size(), 0x10000);
+}
+
+#[test]
+fn platform_presets() {
+    assert_eq!(Quirks::for_platform("chip8"), Some(Quirks::COSMAC_VIP));
+    assert_eq!(Quirks::for_platform("chip48"), Some(Quirks::CHIP_48));
+    assert_eq!(Quirks::for_platform("schip"), Some(Quirks::SUPER_CHIP));
+    assert_eq!(Quirks::for_platform("xochip"), Some(Quirks::XO_CHIP));
+    assert_eq!(Quirks::for_platform("megachip"), None);
+}
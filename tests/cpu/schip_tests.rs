@@ -0,0 +1,140 @@
+use super::*;
+use crate::cpu::opcode_helper::OpcodeHelper;
+use all_asserts::{assert_false, assert_true};
+
+#[test]
+fn switches_resolution() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+
+    helper.draw_to_screen();
+    assert_false!(helper.cpu.is_hires());
+
+    // HIGH
+    helper.high_res();
+    assert_true!(helper.cpu.is_hires());
+    assert_eq!(helper.cpu.screen_size(), (HIRES_VRAM_WIDTH, HIRES_VRAM_HEIGHT));
+    assert_eq!(helper.lit_pixels(), 0);
+
+    // LOW
+    helper.low_res();
+    assert_false!(helper.cpu.is_hires());
+    assert_eq!(helper.cpu.screen_size(), (VRAM_WIDTH, VRAM_HEIGHT));
+}
+
+#[test]
+fn draws_in_high_resolution() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+
+    helper.high_res();
+    helper.cpu.load_fonts();
+
+    helper.load_byte(0, 120);
+    helper.load_byte(1, 60);
+    helper.load_i_register(0);
+    helper.draw(0, 1, 1);
+
+    assert_eq!(helper.cpu.vram[60][120..124], [1, 1, 1, 1]);
+}
+
+#[test]
+fn draws_16x16_sprite() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+
+    for addr in 0x300..0x320 {
+        helper.cpu.ram[addr] = 0xFF;
+    }
+
+    helper.high_res();
+    helper.load_byte(0, 0);
+    helper.load_byte(1, 0);
+    helper.load_i_register(0x300);
+
+    // DRW Vx, Vy, 0
+    helper.draw(0, 1, 0);
+    assert_eq!(helper.lit_pixels(), 256);
+    helper.assert_borrow(false);
+
+    helper.draw(0, 1, 0);
+    assert_eq!(helper.lit_pixels(), 0);
+    helper.assert_borrow(true);
+}
+
+#[test]
+fn scrolls_down() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+
+    helper.draw_to_screen();
+    assert_eq!(helper.cpu.vram[0][0], 1);
+
+    // SCD nibble
+    helper.scroll_down(3);
+    assert_eq!(helper.cpu.vram[0][0], 0);
+    assert_eq!(helper.cpu.vram[3][0], 1);
+    assert_eq!(helper.lit_pixels(), 14);
+}
+
+#[test]
+fn scrolls_left_and_right() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+
+    helper.draw_to_screen();
+
+    // SCR
+    helper.scroll_right();
+    assert_eq!(helper.cpu.vram[0][0], 0);
+    assert_eq!(helper.cpu.vram[0][4], 1);
+
+    // SCL
+    helper.scroll_left();
+    assert_eq!(helper.cpu.vram[0][0], 1);
+    assert_eq!(helper.cpu.vram[0][4], 0);
+    assert_eq!(helper.lit_pixels(), 14);
+}
+
+#[test]
+fn exit_halts() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+    let pc = helper.cpu.pc;
+
+    // EXIT
+    helper.exit();
+    assert_true!(helper.cpu.is_halted());
+
+    helper.load_byte(0, 0xA);
+    helper.assert_register_value(0, 0);
+    helper.assert_pc_value(pc);
+}
+
+#[test]
+fn load_i_from_big_sprite() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+
+    helper.load_byte(1, 0x9);
+
+    // LD HF, Vx
+    helper.load_i_from_big_sprite(1);
+    helper.assert_i_register_value(BIG_FONT_ADDR + 90);
+}
+
+#[test]
+fn stores_and_reads_rpl_flags() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+
+    for n in 0..8 {
+        helper.load_byte(n, n + 1);
+    }
+
+    // LD R, Vx
+    helper.store_rpl_flags(7);
+
+    for n in 0..8 {
+        helper.load_byte(n, 0);
+    }
+
+    // LD Vx, R
+    helper.read_rpl_flags(7);
+
+    for n in 0..8 {
+        helper.assert_register_value(n, (n + 1) as u8);
+    }
+}
@@ -0,0 +1,140 @@
+use super::*;
+use crate::{cpu::opcode_helper::OpcodeHelper, random::RandomMode};
+
+// LD V0, 5; LD DT, V0; LD I, 0x20A; DRW V0, V0, 5; CALL 0x20C; JP 0x20A; RET
+const ROM: [u8; 14] = [
+    0x60, 0x05, 0xF0, 0x15, 0xA2, 0x0A, 0xD0, 0x05, 0x22, 0x0C, 0x12, 0x0A, 0x00, 0xEE,
+];
+
+#[test]
+fn save_state_round_trips_through_bytes() {
+    let mut cpu = Cpu::new();
+    cpu.load(&ROM).unwrap();
+    cpu.set_quirks(Quirks::SUPER_CHIP);
+    cpu.run_frame([false; 16], 5).unwrap();
+
+    let state = cpu.save_state();
+    let restored = CpuState::from_bytes(&state.to_bytes().unwrap()).unwrap();
+
+    assert_eq!(restored, state);
+    assert_eq!(restored.version(), STATE_VERSION);
+}
+
+#[test]
+fn load_state_resumes_where_it_was_saved() {
+    let mut cpu = Cpu::new();
+    cpu.load(&ROM).unwrap();
+    cpu.set_quirks(Quirks::SUPER_CHIP);
+    cpu.run_frame([false; 16], 5).unwrap();
+    let state = cpu.save_state();
+
+    cpu.run_frame([false; 16], 3).unwrap();
+    let pc = cpu.pc();
+    let vram = *cpu.vram();
+
+    let mut other = Cpu::new();
+    other.load_state(&state).unwrap();
+    other.run_frame([false; 16], 3).unwrap();
+
+    assert_eq!(other.pc(), pc);
+    assert_eq!(other.sp(), cpu.sp());
+    assert_eq!(other.delay_timer(), cpu.delay_timer());
+    assert_eq!(other.registers(), cpu.registers());
+    assert_eq!(other.quirks(), Quirks::SUPER_CHIP);
+    assert_eq!(*other.vram(), vram);
+}
+
+#[test]
+fn load_state_keeps_keypad_wait() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+    helper.wait_keypress(0x3);
+    let state = helper.cpu.save_state();
+
+    let mut cpu = Cpu::new();
+    cpu.load_state(&state).unwrap();
+    assert!(cpu.is_waiting_for_key());
+
+    cpu.set_key(0x9, true);
+    cpu.step().unwrap();
+    assert!(!cpu.is_waiting_for_key());
+    assert_eq!(cpu.registers()[0x3], 0x9);
+}
+
+#[test]
+fn rejects_other_versions() {
+    let mut state = Cpu::new().save_state();
+    state.version = STATE_VERSION + 1;
+
+    let expected = StateError::UnsupportedVersion {
+        found: STATE_VERSION + 1,
+        expected: STATE_VERSION,
+    };
+
+    assert_eq!(
+        CpuState::from_bytes(&state.to_bytes().unwrap()),
+        Err(expected.clone())
+    );
+    assert_eq!(Cpu::new().load_state(&state), Err(expected));
+}
+
+#[test]
+fn rejects_old_state_layouts_by_version() {
+    let bytes = br#"{"version":0,"memory":[]}"#;
+
+    assert_eq!(
+        CpuState::from_bytes(bytes),
+        Err(StateError::UnsupportedVersion {
+            found: 0,
+            expected: STATE_VERSION
+        })
+    );
+}
+
+#[test]
+fn rejects_malformed_states() {
+    assert!(matches!(
+        CpuState::from_bytes(b"not a save state"),
+        Err(StateError::Malformed(_))
+    ));
+}
+
+#[test]
+fn rejects_invalid_states_without_changing_cpu() {
+    let mut cpu = Cpu::new();
+    cpu.load(&ROM).unwrap();
+
+    let mut state = cpu.save_state();
+    state.ram.truncate(4096);
+    state.pc = 0x300;
+
+    assert_eq!(cpu.load_state(&state), Err(StateError::Invalid("ram size")));
+    assert_eq!(cpu.pc(), 0x200);
+
+    let mut state = cpu.save_state();
+    state.sp = 12;
+
+    assert_eq!(
+        cpu.load_state(&state),
+        Err(StateError::Invalid("stack pointer"))
+    );
+}
+
+#[test]
+fn load_state_resumes_random_numbers() {
+    // RND V0, #FF; JP 0x200
+    let rom = [0xC0, 0xFF, 0x12, 0x00];
+    let mut cpu = Cpu::new();
+    cpu.load(&rom).unwrap();
+    cpu.set_random(Random::new(7, RandomMode::CosmacVip));
+    cpu.run_frame([false; 16], 4).unwrap();
+    let state = cpu.save_state();
+
+    cpu.run_frame([false; 16], 2).unwrap();
+
+    let mut other = Cpu::new();
+    other.load_state(&state).unwrap();
+    other.run_frame([false; 16], 2).unwrap();
+
+    assert_eq!(other.random(), cpu.random());
+    assert_eq!(other.registers()[0], cpu.registers()[0]);
+}
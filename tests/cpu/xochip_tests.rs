@@ -0,0 +1,132 @@
+use super::*;
+use crate::cpu::opcode_helper::OpcodeHelper;
+
+#[test]
+fn addresses_64k_of_ram() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+    helper.cpu.set_quirks(Quirks {
+        extended_memory: true,
+        ..Quirks::default()
+    });
+
+    assert_eq!(helper.cpu.ram.len(), 0x10000);
+
+    helper.load_byte(0, 0xAB);
+    helper.load_i_long(0xFFF0);
+    helper.store_at_i(0);
+    helper.assert_ram_addr_value(0xFFF0, 0xAB);
+}
+
+#[test]
+fn loads_long_i() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+    let pc = helper.cpu.pc;
+
+    // LD I, long addr
+    helper.load_i_long(0xBEEF);
+    helper.assert_i_register_value(0xBEEF);
+    helper.assert_pc_value(pc + OPCODE_SIZE * 2);
+}
+
+#[test]
+fn skips_over_long_i() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+    let pc = helper.cpu.pc;
+
+    helper.load_addr_ram(pc + OPCODE_SIZE, 0xF000);
+
+    // SE V0, 0
+    helper.skip_equal_byte(0, 0);
+    helper.assert_pc_value(pc + OPCODE_SIZE * 3);
+}
+
+#[test]
+fn saves_and_loads_register_ranges() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+
+    for n in 0..16 {
+        helper.load_byte(n, n * 3);
+    }
+
+    helper.load_i_register(0x400);
+
+    // SAVE V2 - V5
+    helper.save_registers(2, 5);
+    helper.assert_i_register_value(0x400);
+
+    for (offset, n) in (2..=5).enumerate() {
+        helper.assert_ram_addr_value(0x400 + offset, n * 3);
+    }
+
+    // LOAD Vx - Vy in reverse
+    helper.load_registers(9, 6);
+    helper.assert_register_value(9, 6);
+    helper.assert_register_value(8, 9);
+    helper.assert_register_value(7, 12);
+    helper.assert_register_value(6, 15);
+}
+
+#[test]
+fn draws_to_selected_planes() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+
+    helper.cpu.ram[0x300] = 0x80;
+    helper.cpu.ram[0x301] = 0xC0;
+
+    helper.load_byte(0, 0);
+    helper.load_byte(1, 0);
+    helper.load_i_register(0x300);
+
+    // PLANE 3 reads one sprite per plane
+    helper.select_planes(3);
+    helper.draw(0, 1, 1);
+    assert_eq!(helper.cpu.vram[0][0], 0b11);
+    assert_eq!(helper.cpu.vram[0][1], 0b10);
+
+    // CLS only clears the selected plane
+    helper.select_planes(2);
+    helper.clear_screen();
+    assert_eq!(helper.cpu.vram[0][0], 0b01);
+    assert_eq!(helper.cpu.vram[0][1], 0);
+
+    // Drawing to plane 2 does not collide with plane 1
+    helper.draw(0, 1, 1);
+    helper.assert_borrow(false);
+    assert_eq!(helper.cpu.vram[0][0], 0b11);
+}
+
+#[test]
+fn scrolls_selected_planes() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+
+    helper.cpu.vram[5][0] = 0b11;
+
+    helper.select_planes(1);
+
+    // SCU nibble
+    helper.scroll_up(2);
+    assert_eq!(helper.cpu.vram[3][0], 0b01);
+    assert_eq!(helper.cpu.vram[5][0], 0b10);
+}
+
+#[test]
+fn loads_audio_pattern_and_pitch() {
+    let mut helper = OpcodeHelper { cpu: Cpu::new() };
+
+    for (offset, addr) in (0x500..0x510).enumerate() {
+        helper.cpu.ram[addr] = offset as u8;
+    }
+
+    assert!(helper.cpu.audio_pattern().is_none());
+    assert_eq!(helper.cpu.pitch(), DEFAULT_PITCH);
+
+    // AUDIO
+    helper.load_i_register(0x500);
+    helper.load_audio_pattern();
+    assert_eq!(helper.cpu.audio_pattern().unwrap()[15], 15);
+
+    // PITCH Vx
+    helper.load_byte(4, 112);
+    helper.set_pitch(4);
+    assert_eq!(helper.cpu.pitch(), 112);
+}
@@ -0,0 +1,209 @@
+use super::*;
+use crate::timer::VirtualClock;
+
+// 0x200 LD V0, 1
+// 0x202 CALL 0x20A
+// 0x204 LD V2, 3
+// 0x206 JP 0x206
+// 0x208 (unused)
+// 0x20A CALL 0x210
+// 0x20C LD V1, 2
+// 0x20E RET
+// 0x210 ADD V3, 1
+// 0x212 RET
+const ROM: [u8; 20] = [
+    0x60, 0x01, 0x22, 0x0A, 0x62, 0x03, 0x12, 0x06, 0x00, 0x00, 0x22, 0x10, 0x61, 0x02, 0x00, 0xEE,
+    0x73, 0x01, 0x00, 0xEE,
+];
+
+fn cpu() -> Cpu {
+    let mut cpu = Cpu::new();
+    cpu.load(&ROM).unwrap();
+    cpu
+}
+
+/// Drive the CPU the way a frontend would, for at most `limit` instructions.
+fn run(cpu: &mut Cpu, debugger: &mut Debugger, limit: usize) {
+    for _ in 0..limit {
+        if !debugger.should_run(cpu) {
+            return;
+        }
+
+        cpu.step().unwrap();
+    }
+}
+
+#[test]
+fn stops_at_breakpoint_before_executing_it() {
+    let mut cpu = cpu();
+    let mut debugger = Debugger::new();
+    debugger.add_breakpoint(0x20C);
+
+    run(&mut cpu, &mut debugger, 100);
+
+    assert!(debugger.is_paused());
+    assert_eq!(cpu.pc(), 0x20C);
+    assert_eq!(cpu.registers()[1], 0);
+    assert_eq!(cpu.registers()[3], 1);
+}
+
+#[test]
+fn resume_moves_past_current_breakpoint() {
+    let mut cpu = cpu();
+    let mut debugger = Debugger::new();
+    debugger.add_breakpoint(0x20C);
+    debugger.add_breakpoint(0x206);
+
+    run(&mut cpu, &mut debugger, 100);
+    assert_eq!(cpu.pc(), 0x20C);
+
+    debugger.resume(&cpu);
+    run(&mut cpu, &mut debugger, 100);
+
+    assert!(debugger.is_paused());
+    assert_eq!(cpu.pc(), 0x206);
+    assert_eq!(cpu.registers()[2], 3);
+}
+
+#[test]
+fn paused_debugger_runs_nothing() {
+    let mut cpu = cpu();
+    let mut debugger = Debugger::new();
+    debugger.pause();
+
+    run(&mut cpu, &mut debugger, 100);
+    assert_eq!(cpu.pc(), 0x200);
+
+    debugger.step(&mut cpu).unwrap();
+    assert!(debugger.is_paused());
+    assert_eq!(cpu.pc(), 0x202);
+}
+
+#[test]
+fn step_over_runs_whole_call() {
+    let mut cpu = cpu();
+    let mut debugger = Debugger::new();
+    debugger.add_breakpoint(0x202);
+    run(&mut cpu, &mut debugger, 100);
+
+    debugger.step_over(&mut cpu).unwrap();
+    run(&mut cpu, &mut debugger, 100);
+
+    assert!(debugger.is_paused());
+    assert_eq!(cpu.pc(), 0x204);
+    assert_eq!(cpu.sp(), 0);
+    assert_eq!(cpu.registers()[1], 2);
+    assert_eq!(cpu.registers()[3], 1);
+}
+
+#[test]
+fn step_over_steps_other_instructions() {
+    let mut cpu = cpu();
+    let mut debugger = Debugger::new();
+    debugger.pause();
+
+    debugger.step_over(&mut cpu).unwrap();
+
+    assert!(debugger.is_paused());
+    assert_eq!(cpu.pc(), 0x202);
+}
+
+#[test]
+fn step_over_stops_at_breakpoint_inside_call() {
+    let mut cpu = cpu();
+    let mut debugger = Debugger::new();
+    debugger.add_breakpoint(0x202);
+    debugger.add_breakpoint(0x210);
+    run(&mut cpu, &mut debugger, 100);
+
+    debugger.step_over(&mut cpu).unwrap();
+    run(&mut cpu, &mut debugger, 100);
+
+    assert_eq!(cpu.pc(), 0x210);
+    assert_eq!(cpu.sp(), 2);
+}
+
+#[test]
+fn step_out_returns_to_caller() {
+    let mut cpu = cpu();
+    let mut debugger = Debugger::new();
+    debugger.add_breakpoint(0x210);
+    run(&mut cpu, &mut debugger, 100);
+    assert_eq!(cpu.sp(), 2);
+
+    debugger.step_out(&cpu);
+    run(&mut cpu, &mut debugger, 100);
+
+    assert!(debugger.is_paused());
+    assert_eq!(cpu.pc(), 0x20C);
+    assert_eq!(cpu.sp(), 1);
+
+    debugger.step_out(&cpu);
+    run(&mut cpu, &mut debugger, 100);
+
+    assert_eq!(cpu.pc(), 0x204);
+    assert_eq!(cpu.sp(), 0);
+}
+
+#[test]
+fn toggles_breakpoints() {
+    let mut debugger = Debugger::new();
+
+    debugger.toggle_breakpoint(0x300);
+    debugger.add_breakpoint(0x200);
+    assert_eq!(debugger.breakpoints().collect::<Vec<_>>(), [0x200, 0x300]);
+
+    debugger.toggle_breakpoint(0x300);
+    debugger.remove_breakpoint(0x200);
+    assert!(!debugger.has_breakpoint(0x300));
+    assert_eq!(debugger.breakpoints().count(), 0);
+}
+
+#[test]
+fn stops_at_breakpoint_inside_a_frame() {
+    let mut cpu = cpu();
+    let mut debugger = Debugger::new();
+    debugger.add_breakpoint(0x20C);
+
+    let finished = cpu
+        .run_frame_until([false; 16], 10, |cpu| !debugger.should_run(cpu))
+        .unwrap();
+
+    assert!(!finished);
+    assert!(debugger.is_paused());
+    assert_eq!(cpu.pc(), 0x20C);
+    assert_eq!(cpu.cycles(), 5);
+
+    /* the rest of the frame runs once resumed */
+    debugger.resume(&cpu);
+    let finished = cpu
+        .run_frame_until([false; 16], 10, |cpu| !debugger.should_run(cpu))
+        .unwrap();
+
+    assert!(finished);
+    assert_eq!(cpu.cycles(), 10);
+    assert_eq!(cpu.registers()[1], 2);
+}
+
+#[test]
+fn stops_at_breakpoint_while_catching_up() {
+    let mut cpu = cpu();
+    let mut clock = VirtualClock::new();
+    let mut debugger = Debugger::new();
+    debugger.add_breakpoint(0x20C);
+
+    /* ten instructions fall due at once */
+    clock.advance(10_000);
+    cpu.process_until([false; 16], 1_000, &clock, |cpu| !debugger.should_run(cpu))
+        .unwrap();
+
+    assert!(debugger.is_paused());
+    assert_eq!(cpu.pc(), 0x20C);
+    assert_eq!(cpu.cycles(), 5);
+
+    debugger.resume(&cpu);
+    cpu.process_until([false; 16], 1_000, &clock, |cpu| !debugger.should_run(cpu))
+        .unwrap();
+
+    assert_eq!(cpu.cycles(), 10);
+}
@@ -0,0 +1,121 @@
+use super::*;
+
+// 0x200 LD I, 0x20E
+// 0x202 CALL 0x20A
+// 0x204 SE V0, 0
+// 0x206 JP 0x200
+// 0x208 EXIT
+// 0x20A DRW V0, V1, 2
+// 0x20C RET
+// 0x20E sprite
+const ROM: [u8; 16] = [
+    0xA2, 0x0E, 0x22, 0x0A, 0x30, 0x00, 0x12, 0x00, 0x00, 0xFD, 0xD0, 0x12, 0x00, 0xEE, 0xF0, 0x90,
+];
+
+#[test]
+fn mnemonics() {
+    assert_eq!(mnemonic(0x00E0).as_deref(), Some("CLS"));
+    assert_eq!(mnemonic(0x00C4).as_deref(), Some("SCD 4"));
+    assert_eq!(mnemonic(0x1234).as_deref(), Some("JP #234"));
+    assert_eq!(mnemonic(0x5AB2).as_deref(), Some("SAVE VA - VB"));
+    assert_eq!(mnemonic(0x8126).as_deref(), Some("SHR V1 {, V2}"));
+    assert_eq!(mnemonic(0xB300).as_deref(), Some("JP V0, #300"));
+    assert_eq!(mnemonic(0xD125).as_deref(), Some("DRW V1, V2, 5"));
+    assert_eq!(mnemonic(0xF000).as_deref(), Some("LD I, long"));
+    assert_eq!(mnemonic(0xF30A).as_deref(), Some("LD V3, K"));
+    assert_eq!(mnemonic(0xF201).as_deref(), Some("PLANE 2"));
+}
+
+#[test]
+fn mnemonic_unknown() {
+    assert_eq!(mnemonic(0x5121), None);
+    assert_eq!(mnemonic(0x8128), None);
+    assert_eq!(mnemonic(0xE1FF), None);
+    assert_eq!(mnemonic(0xF1FF), None);
+}
+
+#[test]
+fn disassemble_follows_control_flow() {
+    let disassembly = disassemble(&ROM);
+    let kinds: Vec<(usize, LineKind)> = disassembly
+        .lines
+        .iter()
+        .map(|line| (line.addr, line.kind))
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            (0x200, LineKind::Code),
+            (0x202, LineKind::Code),
+            (0x204, LineKind::Code),
+            (0x206, LineKind::Code),
+            (0x208, LineKind::Code),
+            (0x20A, LineKind::Code),
+            (0x20C, LineKind::Code),
+            (0x20E, LineKind::Data),
+        ]
+    );
+}
+
+#[test]
+fn disassemble_labels() {
+    let disassembly = disassemble(&ROM);
+    let line = |addr| disassembly.line_at(addr).unwrap();
+
+    assert_eq!(line(0x200).label.as_deref(), Some("L200"));
+    assert_eq!(line(0x200).text, "LD I, L20E");
+    assert_eq!(line(0x202).text, "CALL L20A");
+    assert_eq!(line(0x206).text, "JP L200");
+    assert_eq!(line(0x20A).label.as_deref(), Some("L20A"));
+    assert_eq!(line(0x20E).label.as_deref(), Some("L20E"));
+    assert_eq!(line(0x20E).text, "DB #F0, #90");
+    assert_eq!(line(0x204).label, None);
+}
+
+#[test]
+fn disassemble_unreached_bytes_are_data() {
+    // 0x200 JP 0x204
+    // 0x202 (never reached)
+    // 0x204 JP 0x204
+    let rom = [0x12, 0x04, 0x60, 0x01, 0x12, 0x04];
+    let disassembly = disassemble(&rom);
+    let line = disassembly.line_at(0x202).unwrap();
+
+    assert_eq!(line.kind, LineKind::Data);
+    assert_eq!(line.text, "DB #60, #01");
+}
+
+#[test]
+fn disassemble_skip_over_long_load() {
+    // 0x200 SE V0, 0
+    // 0x202 LD I, long 0x20A
+    // 0x206 EXIT
+    // 0x208 EXIT
+    // 0x20A sprite
+    let rom = [
+        0x30, 0x00, 0xF0, 0x00, 0x02, 0x0A, 0x00, 0xFD, 0x00, 0xFD, 0xFF,
+    ];
+    let disassembly = disassemble(&rom);
+    let line = |addr| disassembly.line_at(addr).unwrap();
+
+    assert_eq!(line(0x202).bytes, vec![0xF0, 0x00, 0x02, 0x0A]);
+    assert_eq!(line(0x202).text, "LD I, long L20A");
+    assert_eq!(line(0x206).kind, LineKind::Code);
+    assert_eq!(line(0x20A).kind, LineKind::Data);
+    assert!(disassembly.line_at(0x204).is_none());
+}
+
+#[test]
+fn disassembly_display() {
+    let rom = [0x22, 0x04, 0x00, 0xFD, 0x00, 0xEE];
+    let text = disassemble(&rom).to_string();
+
+    assert_eq!(
+        text,
+        "    0200  2204              CALL L204\n\
+         \x20   0202  00FD              EXIT\n\
+         L204:\n\
+         \x20   0204  00EE              RET\n"
+    );
+}
@@ -0,0 +1,178 @@
+use super::*;
+
+/// A CPU that has drawn the font's 0 at the top left of the screen.
+fn cpu_with_zero() -> Cpu {
+    let mut cpu = Cpu::new();
+    cpu.load(&[
+        0xF0, 0x29, // LD F, V0
+        0xD0, 0x05, // DRW V0, V0, 5
+    ])
+    .unwrap();
+    cpu.step().unwrap();
+    cpu.step().unwrap();
+    cpu
+}
+
+#[test]
+fn from_cpu() {
+    let frame = Framebuffer::from_cpu(&cpu_with_zero());
+
+    assert_eq!((frame.width(), frame.height()), (64, 32));
+    assert_eq!(frame.pixel(0, 0), 1);
+    assert_eq!(frame.pixel(1, 1), 0);
+    assert_eq!(frame.pixel(3, 4), 1);
+    assert_eq!(frame.pixel(4, 0), 0);
+}
+
+#[test]
+fn from_cpu_hires() {
+    let mut cpu = Cpu::new();
+    cpu.load(&[
+        0x00, 0xFF, // HIGH
+    ])
+    .unwrap();
+    cpu.step().unwrap();
+
+    let frame = Framebuffer::from_cpu(&cpu);
+
+    assert_eq!((frame.width(), frame.height()), (128, 64));
+}
+
+#[test]
+fn pbm() {
+    let frame = Framebuffer::from_cpu(&cpu_with_zero());
+    let pbm = frame.to_pbm(1);
+
+    assert!(pbm.starts_with(b"P4\n64 32\n"));
+    assert_eq!(pbm.len(), 9 + 8 * 32);
+    /* the top row of the 0 is 0xF0 */
+    assert_eq!(pbm[9], 0xF0);
+
+    assert_eq!(Framebuffer::from_pbm(&pbm).unwrap(), frame);
+}
+
+#[test]
+fn pbm_scaled() {
+    let frame = Framebuffer::from_cpu(&cpu_with_zero());
+    let pbm = frame.to_pbm(2);
+
+    assert!(pbm.starts_with(b"P4\n128 64\n"));
+    /* the first two rows are the top row of the 0, each pixel doubled */
+    assert_eq!(&pbm[10..12], &[0xFF, 0x00]);
+    assert_eq!(&pbm[26..28], &[0xFF, 0x00]);
+}
+
+#[test]
+fn from_pbm_comment() {
+    let frame = Framebuffer::from_pbm(b"P4\n# drawn by hand\n8 2\n\xF0\x0F").unwrap();
+
+    assert_eq!((frame.width(), frame.height()), (8, 2));
+    assert_eq!(frame.pixel(0, 0), 1);
+    assert_eq!(frame.pixel(7, 0), 0);
+    assert_eq!(frame.pixel(7, 1), 1);
+}
+
+#[test]
+fn from_pbm_malformed() {
+    assert_eq!(
+        Framebuffer::from_pbm(b"P1\n8 1\n0"),
+        Err(ImageError::Malformed("not a binary PBM"))
+    );
+    assert_eq!(
+        Framebuffer::from_pbm(b"P4\n8"),
+        Err(ImageError::Malformed("header ends early"))
+    );
+    assert_eq!(
+        Framebuffer::from_pbm(b"P4\n8 2\n\xF0"),
+        Err(ImageError::Malformed("pixel data ends early"))
+    );
+}
+
+#[test]
+fn png() {
+    let frame = Framebuffer::from_cpu(&cpu_with_zero());
+    let png = frame.to_png(10, &DEFAULT_PALETTE);
+
+    assert_eq!(png[..8], PNG_SIGNATURE);
+    /* IHDR comes first, holding the scaled size */
+    assert_eq!(&png[12..16], b"IHDR");
+    assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 640);
+    assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 320);
+    assert_eq!(&png[24..29], &[2, 3, 0, 0, 0]);
+    /* then the palette */
+    assert_eq!(&png[37..41], b"PLTE");
+    assert_eq!(&png[41..53], DEFAULT_PALETTE.as_flattened());
+    assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+}
+
+#[test]
+fn crc32_matches_reference() {
+    assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    /* the CRC of an empty IEND chunk, found in every PNG */
+    assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+}
+
+#[test]
+fn zlib_stored_wraps_data() {
+    let zlib = zlib_stored(b"abc");
+
+    assert_eq!(
+        zlib,
+        [0x78, 0x01, 1, 3, 0, 0xFC, 0xFF, b'a', b'b', b'c', 0x02, 0x4D, 0x01, 0x27]
+    );
+}
+
+#[test]
+fn zlib_stored_blocks() {
+    let data = vec![0; STORED_BLOCK_SIZE + 1];
+    let zlib = zlib_stored(&data);
+
+    /* a full block that isn't the last, then a final block of one byte */
+    assert_eq!(&zlib[2..7], &[0, 0xFF, 0xFF, 0, 0]);
+    assert_eq!(
+        &zlib[7 + STORED_BLOCK_SIZE..12 + STORED_BLOCK_SIZE],
+        &[1, 1, 0, 0xFE, 0xFF]
+    );
+    assert_eq!(zlib.len(), 2 + 5 + STORED_BLOCK_SIZE + 5 + 1 + 4);
+}
+
+#[test]
+fn diff() {
+    let blank = Framebuffer::from_cpu(&Cpu::new());
+    let frame = Framebuffer::from_cpu(&cpu_with_zero());
+
+    let diff = blank.diff(&blank).unwrap();
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "frames match");
+
+    let diff = blank.diff(&frame).unwrap();
+    /* the 0 lights 4 + 2 + 2 + 2 + 4 pixels */
+    assert_eq!(diff.len(), 14);
+    assert_eq!(diff.pixels[0], (0, 0));
+    assert_eq!(diff.bounds(), Some(((0, 0), (3, 4))));
+    assert_eq!(
+        diff.to_string(),
+        "14 pixels differ between (0, 0) and (3, 4)"
+    );
+}
+
+#[test]
+fn diff_size_mismatch() {
+    let mut cpu = Cpu::new();
+    cpu.load(&[
+        0x00, 0xFF, // HIGH
+    ])
+    .unwrap();
+    cpu.step().unwrap();
+
+    let lores = Framebuffer::from_cpu(&Cpu::new());
+    let hires = Framebuffer::from_cpu(&cpu);
+
+    assert_eq!(
+        lores.diff(&hires),
+        Err(ImageError::SizeMismatch {
+            found: (128, 64),
+            expected: (64, 32),
+        })
+    );
+}
@@ -0,0 +1,291 @@
+use super::*;
+use std::{
+    net::Shutdown,
+    time::{Duration, Instant},
+};
+
+// 0x200 LD V0, 1
+// 0x202 LD I, 0x300
+// 0x204 ADD V0, 1
+// 0x206 JP 0x204
+const ROM: [u8; 8] = [0x60, 0x01, 0xA3, 0x00, 0x70, 0x01, 0x12, 0x04];
+
+/// A stub with a CPU, driven the way a frontend would, and a GDB client talking to it.
+struct Session {
+    stub: GdbStub,
+    client: TcpStream,
+    cpu: Cpu,
+    debugger: Debugger,
+}
+
+impl Session {
+    fn new() -> Self {
+        let mut stub = GdbStub::bind(0).unwrap();
+        let client = TcpStream::connect(stub.local_addr().unwrap()).unwrap();
+        client.set_nonblocking(true).unwrap();
+
+        let mut cpu = Cpu::new();
+        cpu.load(&ROM).unwrap();
+        let mut debugger = Debugger::new();
+
+        stub.poll(&mut cpu, &mut debugger).unwrap();
+
+        Self {
+            stub,
+            client,
+            cpu,
+            debugger,
+        }
+    }
+
+    /// Run the frontend loop once: poll the stub, then run an instruction if allowed.
+    fn tick(&mut self) {
+        self.stub.poll(&mut self.cpu, &mut self.debugger).unwrap();
+
+        if self.stub.is_attached() && self.debugger.should_run(&self.cpu) {
+            self.cpu.step().unwrap();
+        }
+    }
+
+    fn send(&mut self, command: &str) {
+        let packet = format!("${command}#{:02x}", GdbStub::checksum(command.as_bytes()));
+        self.client.write_all(packet.as_bytes()).unwrap();
+    }
+
+    /// Tick until a whole reply packet arrives, returning its body.
+    fn reply(&mut self) -> String {
+        let mut received = Vec::new();
+        let start = Instant::now();
+
+        while start.elapsed() < Duration::from_secs(5) {
+            self.tick();
+
+            let mut chunk = [0; 1024];
+            match self.client.read(&mut chunk) {
+                Ok(size) => received.extend_from_slice(&chunk[..size]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => {}
+                Err(error) => panic!("{error}"),
+            }
+
+            let text = String::from_utf8_lossy(&received).into_owned();
+            let text = text.trim_start_matches('+');
+
+            if let (Some(0), Some(end)) = (text.find('$'), text.find('#')) {
+                if text.len() >= end + 3 {
+                    let body = &text[1..end];
+                    assert_eq!(
+                        &text[end + 1..end + 3],
+                        format!("{:02x}", GdbStub::checksum(body.as_bytes()))
+                    );
+                    return body.to_string();
+                }
+            }
+        }
+
+        panic!(
+            "no reply, received {:?}",
+            String::from_utf8_lossy(&received)
+        );
+    }
+
+    fn exchange(&mut self, command: &str) -> String {
+        self.send(command);
+        self.reply()
+    }
+}
+
+#[test]
+fn attach_pauses() {
+    let mut session = Session::new();
+
+    assert!(session.stub.is_attached());
+    assert!(session.debugger.is_paused());
+    assert_eq!(session.exchange("?"), "S05");
+}
+
+#[test]
+fn read_registers() {
+    let mut session = Session::new();
+    session.cpu.registers_mut()[0xA] = 0x42;
+    session.cpu.set_delay_timer(0x10);
+
+    assert_eq!(
+        session.exchange("g"),
+        "00000000000000000000420000000000\
+         0000\
+         0200\
+         00\
+         10\
+         00"
+    );
+    assert_eq!(session.exchange("p11"), "0200");
+    assert_eq!(session.exchange("pa"), "42");
+    assert_eq!(session.exchange("p15"), "E01");
+}
+
+#[test]
+fn write_registers() {
+    let mut session = Session::new();
+
+    assert_eq!(session.exchange("P3=7f"), "OK");
+    assert_eq!(session.exchange("P10=0abc"), "OK");
+    assert_eq!(session.exchange("P12=01"), "E01");
+    assert_eq!(session.cpu.registers()[3], 0x7F);
+    assert_eq!(session.cpu.i(), 0xABC);
+
+    let registers = "0102030405060708090a0b0c0d0e0f10\
+                     0300\
+                     0204\
+                     00\
+                     05\
+                     06";
+    assert_eq!(session.exchange(&format!("G{registers}")), "OK");
+    assert_eq!(session.cpu.registers()[0xF], 0x10);
+    assert_eq!(session.cpu.i(), 0x300);
+    assert_eq!(session.cpu.pc(), 0x204);
+    assert_eq!(session.cpu.delay_timer(), 5);
+    assert_eq!(session.cpu.sound_timer(), 6);
+}
+
+#[test]
+fn memory() {
+    let mut session = Session::new();
+
+    assert_eq!(session.exchange("m200,4"), "6001a300");
+    assert_eq!(session.exchange("M300,2:beef"), "OK");
+    assert_eq!(session.cpu.ram()[0x300..0x302], [0xBE, 0xEF]);
+    assert_eq!(session.exchange("mffff,2"), "E01");
+    assert_eq!(session.exchange("M300,2:be"), "E01");
+}
+
+#[test]
+fn step() {
+    let mut session = Session::new();
+
+    assert_eq!(session.exchange("s"), "S05");
+    assert_eq!(session.cpu.pc(), 0x202);
+    assert_eq!(session.cpu.registers()[0], 1);
+    assert!(session.debugger.is_paused());
+}
+
+#[test]
+fn step_fault() {
+    let mut session = Session::new();
+    session.cpu.ram_mut()[0x200..0x202].copy_from_slice(&[0xFF, 0xFF]);
+
+    assert_eq!(session.exchange("s"), "S04");
+}
+
+#[test]
+fn breakpoint_continue() {
+    let mut session = Session::new();
+
+    assert_eq!(session.exchange("Z0,206,2"), "OK");
+    assert!(session.debugger.has_breakpoint(0x206));
+
+    assert_eq!(session.exchange("c"), "S05");
+    assert_eq!(session.cpu.pc(), 0x206);
+    assert_eq!(session.cpu.registers()[0], 2);
+
+    assert_eq!(session.exchange("c"), "S05");
+    assert_eq!(session.cpu.pc(), 0x206);
+    assert_eq!(session.cpu.registers()[0], 3);
+
+    assert_eq!(session.exchange("z0,206,2"), "OK");
+    assert!(!session.debugger.has_breakpoint(0x206));
+}
+
+#[test]
+fn interrupt() {
+    let mut session = Session::new();
+
+    session.send("c");
+    for _ in 0..10 {
+        session.tick();
+    }
+    assert!(!session.debugger.is_paused());
+
+    session.client.write_all(&[0x03]).unwrap();
+    assert_eq!(session.reply(), "S05");
+    assert!(session.debugger.is_paused());
+}
+
+#[test]
+fn detach_resumes() {
+    let mut session = Session::new();
+
+    assert_eq!(session.exchange("D"), "OK");
+    assert!(!session.stub.is_attached());
+    assert!(!session.debugger.is_paused());
+}
+
+/// Poll the stub until `done` holds, for at most five seconds.
+fn poll_until(session: &mut Session, done: impl Fn(&GdbStub) -> bool) {
+    let start = Instant::now();
+
+    while !done(&session.stub) && start.elapsed() < Duration::from_secs(5) {
+        session
+            .stub
+            .poll(&mut session.cpu, &mut session.debugger)
+            .unwrap();
+    }
+}
+
+#[test]
+fn client_gone_during_reply_detaches() {
+    let mut session = Session::new();
+    let addr = session.stub.local_addr().unwrap();
+
+    /* the connection breaks before the reply to `g` can be written */
+    session.send("g");
+    let stub_side = session.stub.client.as_ref().unwrap();
+    stub_side.shutdown(Shutdown::Write).unwrap();
+
+    poll_until(&mut session, |stub| !stub.is_attached());
+    assert!(!session.stub.is_attached());
+    assert!(!session.debugger.is_paused());
+
+    /* the stub keeps listening for the next client, which it drops once that one closes */
+    let client = TcpStream::connect(addr).unwrap();
+    poll_until(&mut session, GdbStub::is_attached);
+    assert!(session.debugger.is_paused());
+
+    drop(client);
+    poll_until(&mut session, |stub| !stub.is_attached());
+    assert!(!session.stub.is_attached());
+    assert!(!session.debugger.is_paused());
+}
+
+#[test]
+fn bad_checksum_is_rejected() {
+    let mut session = Session::new();
+    session.client.write_all(b"$g#00").unwrap();
+
+    let mut received = [0; 1];
+    let start = Instant::now();
+
+    while start.elapsed() < Duration::from_secs(5) {
+        session.tick();
+
+        if let Ok(1) = session.client.read(&mut received) {
+            break;
+        }
+    }
+
+    assert_eq!(&received, b"-");
+}
+
+#[test]
+fn queries() {
+    let mut session = Session::new();
+
+    assert!(session
+        .exchange("qSupported:multiprocess+")
+        .contains("qXfer:features:read+"));
+    assert_eq!(session.exchange("qAttached"), "1");
+    assert_eq!(session.exchange("vMustReplyEmpty"), "");
+
+    let xml = session.exchange("qXfer:features:read:target.xml:0,ffff");
+    assert!(xml.starts_with("l<?xml"));
+    assert!(xml.contains(r#"<reg name="pc" bitsize="16" type="code_ptr"/>"#));
+}
@@ -0,0 +1,215 @@
+use super::*;
+use crate::framebuffer::DEFAULT_PALETTE;
+
+/// Decode GIF LZW data back into color indices, the way a viewer would.
+fn unlzw(data: &[u8]) -> Vec<u8> {
+    let mut bits = data
+        .iter()
+        .flat_map(|&byte| (0..8).map(move |bit| (byte >> bit) & 1));
+    let mut read = |size: u8| -> Option<u16> {
+        (0..size).try_fold(0, |code, bit| Some(code | (bits.next()? as u16) << bit))
+    };
+
+    let mut table: Vec<Vec<u8>> = Vec::new();
+    let mut code_size = MIN_CODE_SIZE + 1;
+    let mut previous: Option<Vec<u8>> = None;
+    let mut indices = Vec::new();
+
+    while let Some(code) = read(code_size) {
+        if code == CLEAR_CODE {
+            table = (0..CLEAR_CODE).map(|index| vec![index as u8]).collect();
+            table.extend([Vec::new(), Vec::new()]);
+            code_size = MIN_CODE_SIZE + 1;
+            previous = None;
+            continue;
+        }
+
+        if code == END_CODE {
+            break;
+        }
+
+        let entry = match (table.get(code as usize), &previous) {
+            (Some(entry), _) => entry.clone(),
+            (None, Some(previous)) => [previous.clone(), vec![previous[0]]].concat(),
+            (None, None) => panic!("code {code} before any entry"),
+        };
+
+        if let Some(previous) = previous {
+            table.push([previous, vec![entry[0]]].concat());
+
+            if table.len() == 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+        }
+
+        indices.extend(&entry);
+        previous = Some(entry);
+    }
+
+    indices
+}
+
+/// The data sub-blocks of each image in a GIF, joined, with the delay before it.
+fn images(gif: &[u8]) -> Vec<(u16, Vec<u8>)> {
+    let mut at = 13 + 12;
+    let mut delay = 0;
+    let mut images = Vec::new();
+
+    let sub_blocks = |at: &mut usize| {
+        let mut data = Vec::new();
+
+        while gif[*at] != 0 {
+            let len = gif[*at] as usize;
+            data.extend(&gif[*at + 1..*at + 1 + len]);
+            *at += 1 + len;
+        }
+
+        *at += 1;
+        data
+    };
+
+    loop {
+        match gif[at] {
+            0x21 => {
+                if gif[at + 1] == 0xF9 {
+                    delay = u16::from_le_bytes([gif[at + 4], gif[at + 5]]);
+                }
+
+                at += 2;
+                sub_blocks(&mut at);
+            }
+            0x2C => {
+                at += 10;
+                assert_eq!(gif[at], MIN_CODE_SIZE);
+                at += 1;
+                images.push((delay, unlzw(&sub_blocks(&mut at))));
+            }
+            0x3B => return images,
+            byte => panic!("unexpected block {byte:02X}"),
+        }
+    }
+}
+
+fn frame_with(pixels: &[(usize, usize)], hires: bool) -> Framebuffer {
+    let mut vram = [[0; HIRES_VRAM_WIDTH]; HIRES_VRAM_HEIGHT];
+
+    for &(x, y) in pixels {
+        vram[y][x] = 1;
+    }
+
+    Framebuffer::from_vram(&vram, hires)
+}
+
+#[test]
+fn lzw_round_trip() {
+    let indices: Vec<u8> = (0..20_000u32).map(|i| ((i * i / 7) % 4) as u8).collect();
+
+    assert_eq!(unlzw(&lzw(indices.iter().copied())), indices);
+}
+
+#[test]
+fn lzw_fills_table() {
+    /* a pattern that keeps adding entries, so the table is cleared several times */
+    let mut seed = 1u32;
+    let indices: Vec<u8> = (0..100_000)
+        .map(|_| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 16) as u8 & 0b11
+        })
+        .collect();
+
+    assert_eq!(unlzw(&lzw(indices.iter().copied())), indices);
+}
+
+#[test]
+fn lzw_short() {
+    for len in 0..40 {
+        let indices: Vec<u8> = (0..len).map(|i| (i % 3) as u8).collect();
+
+        assert_eq!(unlzw(&lzw(indices.iter().copied())), indices, "{len}");
+    }
+}
+
+#[test]
+fn header() {
+    let gif = GifRecorder::new(2, &DEFAULT_PALETTE).finish();
+
+    assert_eq!(&gif[..6], b"GIF89a");
+    assert_eq!(u16::from_le_bytes([gif[6], gif[7]]), 256);
+    assert_eq!(u16::from_le_bytes([gif[8], gif[9]]), 128);
+    assert_eq!(&gif[13..25], DEFAULT_PALETTE.as_flattened());
+    assert_eq!(gif.last(), Some(&0x3B));
+    assert!(images(&gif).is_empty());
+}
+
+#[test]
+fn frames() {
+    let mut recorder = GifRecorder::new(1, &DEFAULT_PALETTE);
+    recorder.push(frame_with(&[(0, 0)], true));
+    recorder.push(frame_with(&[(1, 0)], true));
+
+    assert_eq!(recorder.frames(), 2);
+
+    let images = images(&recorder.finish());
+
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[0].1.len(), HIRES_VRAM_WIDTH * HIRES_VRAM_HEIGHT);
+    assert_eq!(&images[0].1[..2], &[1, 0]);
+    assert_eq!(&images[1].1[..2], &[0, 1]);
+}
+
+#[test]
+fn repeated_frames_merge() {
+    let mut recorder = GifRecorder::new(1, &DEFAULT_PALETTE);
+
+    for _ in 0..3 {
+        recorder.push(frame_with(&[], true));
+    }
+
+    for _ in 0..60 {
+        recorder.push(frame_with(&[(5, 5)], true));
+    }
+
+    let delays: Vec<u16> = images(&recorder.finish())
+        .into_iter()
+        .map(|(delay, _)| delay)
+        .collect();
+
+    /* 3 frames is 5 hundredths of a second, and 60 frames a whole second */
+    assert_eq!(delays, [5, 100]);
+}
+
+#[test]
+fn delays_do_not_drift() {
+    let mut recorder = GifRecorder::new(1, &DEFAULT_PALETTE);
+
+    for frame in 0..60 {
+        recorder.push(frame_with(&[(frame, 0)], true));
+    }
+
+    let delays: Vec<u16> = images(&recorder.finish())
+        .into_iter()
+        .map(|(delay, _)| delay)
+        .collect();
+
+    assert_eq!(delays.len(), 60);
+    assert!(delays.iter().all(|&delay| delay == 1 || delay == 2));
+    assert_eq!(delays.iter().sum::<u16>(), 100);
+}
+
+#[test]
+fn lores_doubled() {
+    let mut recorder = GifRecorder::new(1, &DEFAULT_PALETTE);
+    recorder.push(frame_with(&[(1, 0)], false));
+
+    let images = images(&recorder.finish());
+    let pixels = &images[0].1;
+
+    assert_eq!(pixels.len(), HIRES_VRAM_WIDTH * HIRES_VRAM_HEIGHT);
+    assert_eq!(&pixels[..4], &[0, 0, 1, 1]);
+    assert_eq!(
+        &pixels[HIRES_VRAM_WIDTH..HIRES_VRAM_WIDTH + 4],
+        &[0, 0, 1, 1]
+    );
+    assert_eq!(pixels[2 * HIRES_VRAM_WIDTH + 2], 0);
+}
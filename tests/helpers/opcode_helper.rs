@@ -1,4 +1,4 @@
-use crate::cpu::Cpu;
+use crate::{cpu::Cpu, fault::CpuFault};
 use all_asserts::{assert_range, assert_true};
 
 pub struct OpcodeHelper {
@@ -7,6 +7,34 @@ pub struct OpcodeHelper {
 
 // Opcode Instructions
 impl OpcodeHelper {
+    pub fn scroll_down(&mut self, rows: u16) {
+        self.process_opcode(0x00C0 | rows);
+    }
+
+    pub fn scroll_up(&mut self, rows: u16) {
+        self.process_opcode(0x00D0 | rows);
+    }
+
+    pub fn scroll_right(&mut self) {
+        self.process_opcode(0x00FB);
+    }
+
+    pub fn scroll_left(&mut self) {
+        self.process_opcode(0x00FC);
+    }
+
+    pub fn exit(&mut self) {
+        self.process_opcode(0x00FD);
+    }
+
+    pub fn low_res(&mut self) {
+        self.process_opcode(0x00FE);
+    }
+
+    pub fn high_res(&mut self) {
+        self.process_opcode(0x00FF);
+    }
+
     pub fn clear_screen(&mut self) {
         self.process_opcode(0x00E0);
     }
@@ -40,6 +68,16 @@ impl OpcodeHelper {
         self.process_opcode(opcode);
     }
 
+    pub fn save_registers(&mut self, x_reg: usize, y_reg: usize) {
+        let opcode = (0x5002 | x_reg << 8 | y_reg << 4) as u16;
+        self.process_opcode(opcode);
+    }
+
+    pub fn load_registers(&mut self, x_reg: usize, y_reg: usize) {
+        let opcode = (0x5003 | x_reg << 8 | y_reg << 4) as u16;
+        self.process_opcode(opcode);
+    }
+
     pub fn load_byte(&mut self, reg: usize, value: usize) {
         let opcode = (0x6000 | reg << 8 | value) as u16;
         self.process_opcode(opcode);
@@ -130,6 +168,20 @@ impl OpcodeHelper {
         self.process_opcode(opcode);
     }
 
+    pub fn load_i_long(&mut self, addr: u16) {
+        self.load_addr_ram(self.cpu.pc + 2, addr);
+        self.process_opcode(0xF000);
+    }
+
+    pub fn select_planes(&mut self, planes: usize) {
+        let opcode = (0xF001 | planes << 8) as u16;
+        self.process_opcode(opcode);
+    }
+
+    pub fn load_audio_pattern(&mut self) {
+        self.process_opcode(0xF002);
+    }
+
     pub fn read_delay_timer(&mut self, reg: usize) {
         let opcode = (0xF007 | reg << 8) as u16;
         self.process_opcode(opcode);
@@ -160,11 +212,21 @@ impl OpcodeHelper {
         self.process_opcode(opcode);
     }
 
+    pub fn load_i_from_big_sprite(&mut self, reg: usize) {
+        let opcode = (0xF030 | reg << 8) as u16;
+        self.process_opcode(opcode);
+    }
+
     pub fn store_bcd_of_register(&mut self, reg: usize) {
         let opcode = (0xF033 | reg << 8) as u16;
         self.process_opcode(opcode);
     }
 
+    pub fn set_pitch(&mut self, reg: usize) {
+        let opcode = (0xF03A | reg << 8) as u16;
+        self.process_opcode(opcode);
+    }
+
     pub fn store_at_i(&mut self, to_reg: usize) {
         let opcode = (0xF055 | to_reg << 8) as u16;
         self.process_opcode(opcode);
@@ -174,6 +236,16 @@ impl OpcodeHelper {
         let opcode = (0xF065 | to_reg << 8) as u16;
         self.process_opcode(opcode);
     }
+
+    pub fn store_rpl_flags(&mut self, to_reg: usize) {
+        let opcode = (0xF075 | to_reg << 8) as u16;
+        self.process_opcode(opcode);
+    }
+
+    pub fn read_rpl_flags(&mut self, to_reg: usize) {
+        let opcode = (0xF085 | to_reg << 8) as u16;
+        self.process_opcode(opcode);
+    }
 }
 
 // Opcode assertions
@@ -234,12 +306,16 @@ impl OpcodeHelper {
     }
 
     pub fn process_opcode(&mut self, opcode: u16) {
+        self.try_opcode(opcode).unwrap();
+    }
+
+    pub fn try_opcode(&mut self, opcode: u16) -> Result<(), CpuFault> {
         self.load_addr_ram(self.cpu.pc, opcode);
-        self.cpu.process(self.cpu.keypad, 0);
+        self.cpu.step()
     }
 
     pub fn process_pc(&mut self) {
-        self.cpu.process(self.cpu.keypad, 0);
+        self.cpu.step().unwrap();
     }
 
     pub fn press_key(&mut self, key: usize) {
@@ -254,6 +330,10 @@ impl OpcodeHelper {
         self.draw(0, 1, 5);
     }
 
+    pub fn lit_pixels(&self) -> usize {
+        self.cpu.vram.iter().flatten().filter(|&&pixel| pixel == 1).count()
+    }
+
     pub fn reset_st(&mut self) {
         self.cpu.sound_timer = 0;
     }
@@ -0,0 +1,116 @@
+use super::*;
+use crate::{cpu::Cpu, fault::CpuFault};
+
+#[test]
+fn decode() {
+    assert_eq!(Instruction::decode(0x00C3), Instruction::ScrollDown(3));
+    assert_eq!(Instruction::decode(0x00E0), Instruction::Clear);
+    assert_eq!(Instruction::decode(0x0123), Instruction::Sys(0x123));
+    assert_eq!(Instruction::decode(0x1ABC), Instruction::Jump(0xABC));
+    assert_eq!(
+        Instruction::decode(0x3A12),
+        Instruction::SkipEqualByte(0xA, 0x12)
+    );
+    assert_eq!(Instruction::decode(0x5122), Instruction::Save(0x1, 0x2));
+    assert_eq!(
+        Instruction::decode(0x812E),
+        Instruction::ShiftLeft(0x1, 0x2)
+    );
+    assert_eq!(Instruction::decode(0xB2F0), Instruction::JumpOffset(0x2F0));
+    assert_eq!(
+        Instruction::decode(0xD12F),
+        Instruction::Draw(0x1, 0x2, 0xF)
+    );
+    assert_eq!(Instruction::decode(0xE5A1), Instruction::SkipNotKey(0x5));
+    assert_eq!(Instruction::decode(0xF000), Instruction::LoadILong);
+    assert_eq!(Instruction::decode(0xF201), Instruction::Plane(0x2));
+    assert_eq!(Instruction::decode(0xF785), Instruction::LoadFlags(0x7));
+}
+
+#[test]
+fn decode_unknown() {
+    for opcode in [0x5121, 0x8128, 0x9121, 0xE19F, 0xF102, 0xF1FF] {
+        assert_eq!(Instruction::decode(opcode), Instruction::Unknown(opcode));
+    }
+}
+
+#[test]
+fn encode_decode_every_opcode() {
+    for opcode in 0..=u16::MAX {
+        let instruction = Instruction::decode(opcode);
+
+        assert_eq!(
+            instruction.encode(),
+            opcode,
+            "{opcode:#06X} {instruction:?}"
+        );
+        assert_eq!(Instruction::decode(instruction.encode()), instruction);
+        assert_eq!(u16::from(Instruction::from(opcode)), opcode);
+    }
+}
+
+#[test]
+fn unknown_opcodes_fault_every_opcode() {
+    for opcode in 0..=u16::MAX {
+        let mut cpu = Cpu::new();
+        cpu.load(&opcode.to_be_bytes()).unwrap();
+
+        let unknown = cpu.step() == Err(CpuFault::UnknownOpcode { pc: 0x200, opcode });
+
+        assert_eq!(
+            unknown,
+            matches!(Instruction::decode(opcode), Instruction::Unknown(_)),
+            "{opcode:#06X}"
+        );
+    }
+}
+
+#[test]
+fn display() {
+    assert_eq!(Instruction::Return.to_string(), "RET");
+    assert_eq!(Instruction::Call(0x2A0).to_string(), "CALL #2A0");
+    assert_eq!(Instruction::LoadByte(0xA, 0x05).to_string(), "LD VA, #05");
+    assert_eq!(
+        Instruction::ShiftRight(0x1, 0x2).to_string(),
+        "SHR V1 {, V2}"
+    );
+    assert_eq!(Instruction::StoreRegisters(0x3).to_string(), "LD [I], V3");
+    assert_eq!(Instruction::Unknown(0xFFFF).to_string(), "DW #FFFF");
+}
+
+#[test]
+fn target() {
+    assert_eq!(Instruction::Call(0x300).target(), Some(0x300));
+    assert_eq!(Instruction::LoadI(0x400).target(), Some(0x400));
+    assert_eq!(Instruction::Sys(0x123).target(), None);
+    assert_eq!(Instruction::LoadILong.target(), None);
+}
+
+#[test]
+fn is_skip() {
+    assert!(Instruction::decode(0x3000).is_skip());
+    assert!(Instruction::decode(0x9010).is_skip());
+    assert!(Instruction::decode(0xE09E).is_skip());
+    assert!(!Instruction::decode(0x5012).is_skip());
+    assert!(!Instruction::decode(0x1200).is_skip());
+}
+
+#[test]
+fn pattern_matches_every_opcode() {
+    for opcode in 0..=u16::MAX {
+        let instruction = Instruction::decode(opcode);
+        let pattern = instruction.pattern();
+
+        if let Instruction::Unknown(_) = instruction {
+            assert_eq!(pattern, "????");
+            continue;
+        }
+
+        /* the lowercase letters are operands, every other digit is fixed */
+        for (digit, expected) in format!("{opcode:04X}").chars().zip(pattern.chars()) {
+            if !expected.is_ascii_lowercase() {
+                assert_eq!(digit, expected, "{opcode:04X} {pattern}");
+            }
+        }
+    }
+}
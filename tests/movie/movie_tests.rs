@@ -0,0 +1,141 @@
+use super::*;
+
+// 0x200 RND V0, #FF
+// 0x202 SKP V1
+// 0x204 JP 0x200
+// 0x206 ADD V2, 1
+// 0x208 JP 0x200
+const ROM: [u8; 10] = [0xC0, 0xFF, 0xE1, 0x9E, 0x12, 0x00, 0x72, 0x01, 0x12, 0x00];
+
+fn keypad(frame: usize) -> [bool; 16] {
+    let mut keypad = [false; 16];
+    keypad[0] = frame % 3 == 0;
+    keypad[0xF] = frame % 7 == 0;
+    keypad
+}
+
+fn record(frames: usize) -> (Movie, Cpu) {
+    let mut cpu = Cpu::new();
+    cpu.load(&ROM).unwrap();
+    cpu.set_random(Random::new(42, RandomMode::Modern));
+
+    let mut movie = Movie::new(&ROM, &cpu, 10);
+
+    for frame in 0..frames {
+        cpu.run_frame(keypad(frame), 10).unwrap();
+        movie.record(keypad(frame), &cpu);
+    }
+
+    movie.finish(&cpu);
+    (movie, cpu)
+}
+
+#[test]
+fn hash_is_stable() {
+    assert_eq!(hash(b""), 0xCBF2_9CE4_8422_2325);
+    assert_eq!(hash(b"a"), 0xAF63_DC4C_8601_EC8C);
+}
+
+#[test]
+fn records_keypad_per_frame() {
+    let (movie, _) = record(10);
+
+    assert_eq!(movie.len(), 10);
+    assert_eq!(movie.frames[0], 0x8001);
+    assert_eq!(movie.frames[1], 0);
+    assert_eq!(movie.frames[3], 0x0001);
+    assert_eq!(movie.keypad(0), Some(keypad(0)));
+    assert_eq!(movie.keypad(7), Some(keypad(7)));
+    assert_eq!(movie.keypad(10), None);
+}
+
+#[test]
+fn checkpoints() {
+    let (movie, _) = record(150);
+
+    let frames: Vec<usize> = movie
+        .checkpoints
+        .iter()
+        .map(|checkpoint| checkpoint.frame)
+        .collect();
+
+    assert_eq!(frames, vec![59, 119, 149]);
+}
+
+#[test]
+fn finish_does_not_repeat_checkpoint() {
+    let (movie, _) = record(MOVIE_CHECKPOINT_FRAMES);
+
+    assert_eq!(movie.checkpoints.len(), 1);
+}
+
+#[test]
+fn playback_matches_recording() {
+    let (movie, cpu) = record(150);
+
+    let played = movie.verify(&ROM).unwrap();
+
+    assert_eq!(played.registers(), cpu.registers());
+    assert_eq!(played.random(), cpu.random());
+    assert_eq!(state_hash(&played), state_hash(&cpu));
+}
+
+#[test]
+fn playback_detects_desync() {
+    let (mut movie, _) = record(150);
+    movie.frames[70] ^= 0x0001;
+
+    assert!(matches!(
+        movie.verify(&ROM),
+        Err(MovieError::Desync { frame: 119, .. })
+    ));
+}
+
+#[test]
+fn playback_needs_same_seed() {
+    let (mut movie, _) = record(60);
+    movie.seed += 1;
+
+    assert!(matches!(
+        movie.verify(&ROM),
+        Err(MovieError::Desync { frame: 59, .. })
+    ));
+}
+
+#[test]
+fn playback_needs_same_rom() {
+    let (movie, _) = record(10);
+    let mut rom = ROM;
+    rom[1] = 0x0F;
+
+    assert_eq!(
+        movie.verify(&rom).err(),
+        Some(MovieError::RomMismatch {
+            found: hash(&rom),
+            expected: hash(&ROM),
+        })
+    );
+}
+
+#[test]
+fn bytes_round_trip() {
+    let (movie, _) = record(70);
+
+    assert_eq!(
+        Movie::from_bytes(&movie.to_bytes().unwrap()).unwrap(),
+        movie
+    );
+}
+
+#[test]
+fn rejects_other_versions() {
+    let bytes = br#"{"version":0,"frames":[]}"#;
+
+    assert_eq!(
+        Movie::from_bytes(bytes),
+        Err(MovieError::UnsupportedVersion {
+            found: 0,
+            expected: MOVIE_VERSION
+        })
+    );
+}
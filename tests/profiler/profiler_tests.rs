@@ -0,0 +1,158 @@
+use super::*;
+use crate::cpu::Cpu;
+
+// 0x200 LD V0, 0
+// 0x202 ADD V0, 1
+// 0x204 SE V0, 3
+// 0x206 JP 0x202
+// 0x208 LD V1, K
+const ROM: [u8; 10] = [0x60, 0x00, 0x70, 0x01, 0x30, 0x03, 0x12, 0x02, 0xF1, 0x0A];
+
+fn profiled_cpu() -> Cpu {
+    let mut cpu = Cpu::new();
+    cpu.load(&ROM).unwrap();
+    cpu.set_profiler(Some(Profiler::new()));
+    cpu
+}
+
+#[test]
+fn counts_per_pc() {
+    let mut cpu = profiled_cpu();
+
+    for _ in 0..10 {
+        cpu.step().unwrap();
+    }
+
+    let profiler = cpu.profiler().unwrap();
+
+    assert_eq!(profiler.instructions(), 10);
+    assert_eq!(profiler.count_at(0x200), 1);
+    assert_eq!(profiler.count_at(0x202), 3);
+    assert_eq!(profiler.count_at(0x204), 3);
+    assert_eq!(profiler.count_at(0x206), 2);
+    assert_eq!(profiler.count_at(0x208), 1);
+
+    assert_eq!(
+        profiler.hot_spots()[..3],
+        [
+            HotSpot {
+                pc: 0x202,
+                count: 3
+            },
+            HotSpot {
+                pc: 0x204,
+                count: 3
+            },
+            HotSpot {
+                pc: 0x206,
+                count: 2
+            },
+        ]
+    );
+}
+
+#[test]
+fn counts_per_instruction_type() {
+    let mut cpu = profiled_cpu();
+
+    for _ in 0..10 {
+        cpu.step().unwrap();
+    }
+
+    assert_eq!(
+        cpu.profiler().unwrap().instruction_types(),
+        vec![
+            InstructionCount {
+                pattern: "3xkk",
+                count: 3
+            },
+            InstructionCount {
+                pattern: "7xkk",
+                count: 3
+            },
+            InstructionCount {
+                pattern: "1nnn",
+                count: 2
+            },
+            InstructionCount {
+                pattern: "6xkk",
+                count: 1
+            },
+            InstructionCount {
+                pattern: "Fx0A",
+                count: 1
+            },
+        ]
+    );
+}
+
+#[test]
+fn instructions_per_frame() {
+    let mut cpu = profiled_cpu();
+
+    cpu.run_frame([false; 16], 2).unwrap();
+    cpu.run_frame([false; 16], 4).unwrap();
+
+    let stats = cpu.profiler().unwrap().instructions_per_frame();
+
+    assert_eq!(cpu.profiler().unwrap().frames(), 2);
+    assert_eq!(stats.min, 2);
+    assert_eq!(stats.max, 4);
+    assert_eq!(stats.average, 3.0);
+}
+
+#[test]
+fn key_wait() {
+    let mut cpu = profiled_cpu();
+
+    /* 9 instructions reach LD V1, K, which then blocks */
+    cpu.run_frame([false; 16], 10).unwrap();
+    cpu.run_frame([false; 16], 10).unwrap();
+    cpu.run_frame([false; 16], 10).unwrap();
+
+    let profiler = cpu.profiler().unwrap();
+
+    assert_eq!(profiler.instructions(), 10);
+    assert_eq!(profiler.key_wait_steps(), 20);
+    assert_eq!(profiler.key_wait_frames(), 3);
+    assert_eq!(profiler.key_wait_seconds(), 3.0 / 60.0);
+}
+
+#[test]
+fn faulting_instruction_is_not_counted() {
+    let mut cpu = Cpu::new();
+    cpu.load(&[0xFF, 0xFF]).unwrap();
+    cpu.set_profiler(Some(Profiler::new()));
+
+    assert!(cpu.step().is_err());
+    assert_eq!(cpu.profiler().unwrap().instructions(), 0);
+}
+
+#[test]
+fn reset() {
+    let mut cpu = profiled_cpu();
+    cpu.run_frame([false; 16], 5).unwrap();
+
+    cpu.profiler_mut().unwrap().reset();
+
+    let profiler = cpu.profiler().unwrap();
+    assert_eq!(profiler.instructions(), 0);
+    assert_eq!(profiler.frames(), 0);
+    assert!(profiler.hot_spots().is_empty());
+}
+
+#[test]
+fn to_json() {
+    let mut cpu = profiled_cpu();
+    cpu.run_frame([false; 16], 3).unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&cpu.profiler().unwrap().to_json().unwrap()).unwrap();
+
+    assert_eq!(json["instructions"], 3);
+    assert_eq!(json["frames"], 1);
+    assert_eq!(json["instructions_per_frame"]["max"], 3);
+    assert_eq!(json["hot_spots"][0]["pc"], 0x200);
+    assert_eq!(json["hot_spots"][0]["count"], 1);
+    assert_eq!(json["instruction_types"][0]["pattern"], "3xkk");
+}
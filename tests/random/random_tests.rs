@@ -0,0 +1,112 @@
+use super::*;
+
+fn bytes(random: &mut Random, count: usize) -> Vec<u8> {
+    (0..count).map(|_| random.next_byte()).collect()
+}
+
+#[test]
+fn same_seed_same_numbers() {
+    let mut a = Random::new(1234, RandomMode::Modern);
+    let mut b = Random::new(1234, RandomMode::Modern);
+
+    assert_eq!(bytes(&mut a, 64), bytes(&mut b, 64));
+}
+
+#[test]
+fn different_seeds_differ() {
+    let mut a = Random::new(1, RandomMode::Modern);
+    let mut b = Random::new(2, RandomMode::Modern);
+
+    assert_ne!(bytes(&mut a, 64), bytes(&mut b, 64));
+}
+
+#[test]
+fn modern_covers_every_byte() {
+    let mut random = Random::new(0, RandomMode::Modern);
+    let mut seen = [false; 256];
+
+    for byte in bytes(&mut random, 10_000) {
+        seen[byte as usize] = true;
+    }
+
+    assert!(seen.iter().all(|&seen| seen));
+}
+
+#[test]
+fn vip_matches_the_interpreter_routine() {
+    /* the numbers the VIP's routine at 0x1D9 draws, stepped through an 1802 */
+    let mut random = Random::new(0x2000, RandomMode::CosmacVip);
+    assert_eq!(
+        bytes(&mut random, 8),
+        vec![0x30, 0x48, 0x6C, 0xA2, 0x5A, 0x7B, 0x9C, 0x6B]
+    );
+
+    /* the seed's high byte is the last number drawn */
+    assert_eq!(random.state, 0x6B08);
+
+    let mut random = Random::new(0, RandomMode::CosmacVip);
+    assert_eq!(
+        bytes(&mut random, 8),
+        vec![0x00, 0x00, 0x00, 0x00, 0x67, 0x8F, 0xBA, 0x98]
+    );
+}
+
+#[test]
+fn vip_varies() {
+    let mut random = Random::new(0x1234, RandomMode::CosmacVip);
+    let mut seen = [false; 256];
+
+    for byte in bytes(&mut random, 256) {
+        seen[byte as usize] = true;
+    }
+
+    assert!(seen.iter().filter(|&&seen| seen).count() > 64);
+}
+
+#[test]
+fn vip_seed_is_sixteen_bits() {
+    let mut a = Random::new(0x1_0000_0042, RandomMode::CosmacVip);
+    let mut b = Random::new(0x42, RandomMode::CosmacVip);
+
+    assert_eq!(a.seed(), 0x1_0000_0042);
+    assert_eq!(bytes(&mut a, 8), bytes(&mut b, 8));
+}
+
+#[test]
+fn mode_from_str() {
+    assert_eq!("modern".parse(), Ok(RandomMode::Modern));
+    assert_eq!("vip".parse(), Ok(RandomMode::CosmacVip));
+    assert!("chip8".parse::<RandomMode>().is_err());
+    assert_eq!(RandomMode::CosmacVip.to_string(), "vip");
+}
+
+#[test]
+fn cpu_draws_from_its_seed() {
+    // RND V0, #FF; RND V1, #FF; RND V2, #0F
+    let rom = [0xC0, 0xFF, 0xC1, 0xFF, 0xC2, 0x0F];
+    let run = || {
+        let mut cpu = crate::cpu::Cpu::new();
+        cpu.load(&rom).unwrap();
+        cpu.set_random(Random::new(99, RandomMode::Modern));
+        cpu.run_frame([false; 16], 3).unwrap();
+        *cpu.registers()
+    };
+
+    let registers = run();
+
+    assert_eq!(registers, run());
+    assert!(registers[2] <= 0x0F);
+}
+
+#[test]
+fn cpu_vip_mode_ignores_ram() {
+    // RND V0, #FF; RND V1, #0F
+    let rom = [0xC0, 0xFF, 0xC1, 0x0F];
+    let mut cpu = crate::cpu::Cpu::new();
+    cpu.load(&rom).unwrap();
+    cpu.set_random(Random::new(0x2000, RandomMode::CosmacVip));
+    cpu.run_frame([false; 16], 2).unwrap();
+
+    /* 0x48 masked with 0x0F for the second */
+    assert_eq!(cpu.registers()[..2], [0x30, 0x08]);
+}
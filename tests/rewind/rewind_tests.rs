@@ -0,0 +1,116 @@
+use super::*;
+use crate::{constants::RAM_SIZE, cpu::Cpu};
+
+// LD V0, 0; LD F, V0; CLS; DRW V0, V0, 5; ADD V0, 1; LD F, V0; JP 0x204
+const ROM: [u8; 12] = [
+    0x60, 0x00, 0xF0, 0x29, 0x00, 0xE0, 0xD0, 0x05, 0x70, 0x01, 0x12, 0x02,
+];
+
+fn running_cpu() -> Cpu {
+    let mut cpu = Cpu::new();
+    cpu.load(&ROM).unwrap();
+    cpu
+}
+
+#[test]
+fn pops_back_through_pushed_frames() {
+    let mut cpu = running_cpu();
+    let mut rewind = Rewind::new(100, usize::MAX);
+    let mut states = Vec::new();
+
+    for _ in 0..10 {
+        cpu.run_frame([false; 16], 3).unwrap();
+        states.push(cpu.save_state());
+        rewind.push(cpu.save_state());
+    }
+
+    assert_eq!(rewind.len(), 9);
+
+    for expected in states.iter().rev().skip(1) {
+        assert_eq!(rewind.pop().as_ref(), Some(expected));
+    }
+
+    assert_eq!(rewind.pop(), None);
+    assert!(rewind.is_empty());
+    assert_eq!(rewind.size(), 0);
+}
+
+#[test]
+fn restored_state_replays_identically() {
+    let mut cpu = running_cpu();
+    let mut rewind = Rewind::new(100, usize::MAX);
+
+    for _ in 0..5 {
+        cpu.run_frame([false; 16], 3).unwrap();
+        rewind.push(cpu.save_state());
+    }
+
+    let vram = *cpu.vram();
+    let registers = *cpu.registers();
+    cpu.run_frame([false; 16], 3).unwrap();
+    rewind.push(cpu.save_state());
+
+    let state = rewind.pop().unwrap();
+    cpu.load_state(&state).unwrap();
+
+    assert_eq!(*cpu.vram(), vram);
+    assert_eq!(*cpu.registers(), registers);
+}
+
+#[test]
+fn pushing_after_a_pop_continues_from_the_rewound_state() {
+    let mut cpu = running_cpu();
+    let mut rewind = Rewind::new(100, usize::MAX);
+
+    for _ in 0..4 {
+        cpu.run_frame([false; 16], 3).unwrap();
+        rewind.push(cpu.save_state());
+    }
+
+    let state = rewind.pop().unwrap();
+    let earlier = rewind.pop().unwrap();
+    cpu.load_state(&state).unwrap();
+    rewind.push(cpu.save_state());
+
+    assert_eq!(rewind.pop(), Some(earlier));
+}
+
+#[test]
+fn drops_oldest_frames_past_frame_limit() {
+    let mut cpu = running_cpu();
+    let mut rewind = Rewind::new(3, usize::MAX);
+
+    for _ in 0..10 {
+        cpu.run_frame([false; 16], 3).unwrap();
+        rewind.push(cpu.save_state());
+    }
+
+    assert_eq!(rewind.len(), 3);
+}
+
+#[test]
+fn stays_within_memory_limit() {
+    let mut cpu = running_cpu();
+    let mut rewind = Rewind::new(usize::MAX, 4096);
+
+    for _ in 0..100 {
+        cpu.run_frame([false; 16], 3).unwrap();
+        rewind.push(cpu.save_state());
+        assert!(rewind.size() <= 4096);
+    }
+
+    assert!(!rewind.is_empty());
+}
+
+#[test]
+fn deltas_are_smaller_than_full_states() {
+    let mut cpu = running_cpu();
+    let mut rewind = Rewind::new(100, usize::MAX);
+
+    for _ in 0..60 {
+        cpu.run_frame([false; 16], 3).unwrap();
+        rewind.push(cpu.save_state());
+    }
+
+    assert!(rewind.size() < RAM_SIZE);
+}
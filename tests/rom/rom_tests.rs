@@ -0,0 +1,41 @@
+use super::*;
+use crate::{cpu::Cpu, timer::VirtualClock};
+use serde_json::json;
+
+fn rom_with_tickrate(tickrate: u128) -> Rom {
+    let json = json!({
+        "authors": [],
+        "images": [],
+        "desc": "",
+        "platform": "chip8",
+        "options": { "tickrate": tickrate },
+    });
+
+    Rom::from((&String::from("test"), &json))
+}
+
+#[test]
+fn tickrate_is_instructions_per_frame() {
+    for tickrate in [7, 20, 30, 200, 1000] {
+        let rom = rom_with_tickrate(tickrate);
+        assert_eq!(rom.instructions_per_frame(), tickrate as usize);
+
+        /* a frame of clock time runs as many instructions as a whole frame does */
+        let mut cpu = Cpu::new();
+        let mut clock = VirtualClock::new();
+        cpu.load(&[0x12, 0x00]).unwrap();
+
+        clock.advance(TIMER_INTERVAL_NS);
+        cpu.process([false; 16], rom.instruction_time_ns(), &clock)
+            .unwrap();
+        assert_eq!(cpu.cycles(), tickrate as u64);
+    }
+}
+
+#[test]
+fn default_tickrate() {
+    let rom = rom_with_tickrate(0);
+
+    assert_eq!(rom.instruction_time_ns(), 1_000_000);
+    assert_eq!(rom.instructions_per_frame(), 16);
+}
@@ -0,0 +1,99 @@
+use super::*;
+use crate::{
+    constants::{MAX_CATCH_UP_NS, TIMER_INTERVAL_NS},
+    cpu::Cpu,
+};
+
+#[test]
+fn scaled_clock_follows_its_speed() {
+    let mut clock = ScaledClock::new(VirtualClock::new());
+
+    clock.source_mut().advance(1_000);
+    assert_eq!(clock.now_nanos(), 1_000);
+
+    clock.set_speed(4.0);
+    clock.source_mut().advance(1_000);
+    assert_eq!(clock.now_nanos(), 5_000);
+
+    clock.set_speed(0.25);
+    clock.source_mut().advance(1_000);
+    assert_eq!(clock.now_nanos(), 5_250);
+}
+
+#[test]
+fn paused_clock_stands_still() {
+    let mut clock = ScaledClock::new(VirtualClock::new());
+    clock.source_mut().advance(1_000);
+
+    clock.set_speed(0.0);
+    assert!(clock.is_paused());
+
+    clock.source_mut().advance(1_000_000);
+    assert_eq!(clock.now_nanos(), 1_000);
+
+    clock.set_speed(1.0);
+    clock.source_mut().advance(500);
+    assert_eq!(clock.now_nanos(), 1_500);
+}
+
+#[test]
+fn scaled_clock_scales_cpu_timers() {
+    let mut cpu = Cpu::new();
+    let mut clock = ScaledClock::new(VirtualClock::new());
+    cpu.load(&[0x60, 0x0A, 0xF0, 0x15, 0x12, 0x04]).unwrap();
+
+    clock.source_mut().advance(1_000);
+    cpu.process([false; 16], 1_000, &clock).unwrap();
+    clock.source_mut().advance(1_000);
+    cpu.process([false; 16], 1_000, &clock).unwrap();
+    assert_eq!(cpu.delay_timer(), 10);
+
+    // A quarter of a frame at four times the speed is a whole frame
+    clock.set_speed(4.0);
+    clock.source_mut().advance(TIMER_INTERVAL_NS / 4 + 1);
+    cpu.process([false; 16], 1_000, &clock).unwrap();
+    assert_eq!(cpu.delay_timer(), 9);
+
+    clock.set_speed(0.0);
+    clock.source_mut().advance(TIMER_INTERVAL_NS * 10);
+    cpu.process([false; 16], 1_000, &clock).unwrap();
+    assert_eq!(cpu.delay_timer(), 9);
+}
+
+#[test]
+fn process_keeps_both_rates_at_four_times_speed() {
+    let mut cpu = Cpu::new();
+    let mut clock = ScaledClock::new(VirtualClock::new());
+    // LD V0, #FF; LD DT, V0; JP #204
+    cpu.load(&[0x60, 0xFF, 0xF0, 0x15, 0x12, 0x04]).unwrap();
+    clock.set_speed(4.0);
+
+    /* calls far apart and out of step with both rates, which must not lose the time between */
+    for _ in 0..600 {
+        clock.source_mut().advance(1_700_000);
+        cpu.process([false; 16], 1_000_000, &clock).unwrap();
+    }
+
+    /* 1.02 seconds at four times the speed is 4080 milliseconds of machine time */
+    assert_eq!(clock.now_nanos(), 4_080_000_000);
+    assert_eq!(cpu.cycles(), 4_080);
+    assert_eq!(
+        cpu.delay_timer() as u128,
+        0xFF - 4_080_000_000 / TIMER_INTERVAL_NS
+    );
+}
+
+#[test]
+fn process_skips_a_long_stall() {
+    let mut cpu = Cpu::new();
+    let mut clock = VirtualClock::new();
+    cpu.load(&[0x12, 0x00]).unwrap();
+
+    clock.advance(MAX_CATCH_UP_NS + 1);
+    cpu.process([false; 16], 1_000, &clock).unwrap();
+    assert_eq!(cpu.cycles(), 0);
+
+    clock.advance(10_000);
+    cpu.process([false; 16], 1_000, &clock).unwrap();
+    assert_eq!(cpu.cycles(), 10);
+}
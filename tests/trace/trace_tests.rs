@@ -0,0 +1,137 @@
+use super::*;
+use std::{cell::RefCell, rc::Rc};
+
+/// A writer the test can still read from after handing it to a tracer.
+#[derive(Clone, Default)]
+struct Buffer(Rc<RefCell<Vec<u8>>>);
+
+impl Buffer {
+    fn lines(&self) -> Vec<String> {
+        String::from_utf8(self.0.borrow().clone())
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+}
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// 0x200 LD V0, 1
+// 0x202 LD I, 0x300
+// 0x204 CALL 0x208
+// 0x206 JP 0x206
+// 0x208 ADD V0, 1
+// 0x20A RET
+const ROM: [u8; 12] = [
+    0x60, 0x01, 0xA3, 0x00, 0x22, 0x08, 0x12, 0x06, 0x70, 0x01, 0x00, 0xEE,
+];
+
+fn run(tracer: Tracer, steps: usize) -> Tracer {
+    let mut cpu = Cpu::new();
+    cpu.load(&ROM).unwrap();
+    cpu.set_tracer(Some(tracer));
+
+    for _ in 0..steps {
+        cpu.step().unwrap();
+    }
+
+    cpu.take_tracer().unwrap()
+}
+
+#[test]
+fn line_format() {
+    let buffer = Buffer::default();
+    run(Tracer::new(buffer.clone(), u64::MAX), 2)
+        .finish()
+        .unwrap();
+
+    assert_eq!(
+        buffer.lines(),
+        vec![
+            "000000000000 PC:0200 OP:6001 LD V0, #01           V:00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 I:0000 SP:0 DT:00 ST:00",
+            "000000000001 PC:0202 OP:A300 LD I, #300           V:01 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 I:0000 SP:0 DT:00 ST:00",
+        ]
+    );
+}
+
+#[test]
+fn line_long_load() {
+    let mut cpu = Cpu::new();
+    cpu.load(&[0xF0, 0x00, 0x12, 0x34]).unwrap();
+
+    let line = Tracer::line(&cpu, cpu.instruction().unwrap());
+    assert!(line.contains("OP:F000 LD I, long #1234 "));
+}
+
+#[test]
+fn trace_ranges() {
+    let buffer = Buffer::default();
+    let mut tracer = Tracer::new(buffer.clone(), u64::MAX);
+    tracer.add_range(0x208..=0x20B);
+    tracer.add_range(0x200..=0x200);
+
+    run(tracer, 6).finish().unwrap();
+
+    let pcs: Vec<String> = buffer
+        .lines()
+        .iter()
+        .map(|line| line[13..20].to_string())
+        .collect();
+    assert_eq!(pcs, vec!["PC:0200", "PC:0208", "PC:020A"]);
+}
+
+#[test]
+fn trace_size_cap() {
+    let line_size = Tracer::line(&Cpu::new(), Instruction::Clear).len() as u64;
+    let buffer = Buffer::default();
+
+    let tracer = run(Tracer::new(buffer.clone(), line_size * 2 + 1), 5);
+
+    assert!(tracer.is_full());
+    assert_eq!(buffer.lines().len(), 2);
+    assert_eq!(buffer.0.borrow().len() as u64, line_size * 2);
+}
+
+#[test]
+fn trace_skips_waiting() {
+    let buffer = Buffer::default();
+    let mut cpu = Cpu::new();
+    cpu.load(&[0xF0, 0x0A]).unwrap();
+    cpu.set_tracer(Some(Tracer::new(buffer.clone(), u64::MAX)));
+
+    for _ in 0..3 {
+        cpu.step().unwrap();
+    }
+
+    assert_eq!(buffer.lines().len(), 1);
+}
+
+struct FailingWriter;
+
+impl Write for FailingWriter {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::other("disk full"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn trace_keeps_first_error() {
+    let tracer = run(Tracer::new(FailingWriter, u64::MAX), 3);
+
+    assert_eq!(tracer.written(), 0);
+    assert_eq!(tracer.finish().unwrap_err().to_string(), "disk full");
+}
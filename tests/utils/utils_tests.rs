@@ -0,0 +1,74 @@
+use super::*;
+
+/// An empty directory of its own for a test, removed if left over from an earlier run.
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("chip8-utils-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn numbered_paths_take_the_next_number() {
+    let dir = scratch_dir("screenshots");
+
+    let [png, pbm] = Utils::numbered_paths(&dir, "PONG", ["png", "pbm"]);
+    assert_eq!(png, dir.join("PONG.0.png"));
+    assert_eq!(pbm, dir.join("PONG.0.pbm"));
+    std::fs::write(&png, b"first").unwrap();
+    std::fs::write(&pbm, b"first").unwrap();
+
+    let [png, pbm] = Utils::numbered_paths(&dir, "PONG", ["png", "pbm"]);
+    assert_eq!(png, dir.join("PONG.1.png"));
+    assert_eq!(pbm, dir.join("PONG.1.pbm"));
+    std::fs::write(&png, b"second").unwrap();
+
+    /* the first screenshot is left as it was */
+    assert_eq!(std::fs::read(dir.join("PONG.0.png")).unwrap(), b"first");
+
+    /* a number is skipped if any of its files is taken */
+    let [png, _] = Utils::numbered_paths(&dir, "PONG", ["png", "pbm"]);
+    assert_eq!(png, dir.join("PONG.2.png"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn numbered_paths_keep_recordings_apart() {
+    let dir = scratch_dir("recordings");
+
+    for number in 0..3 {
+        let [gif, wav] = Utils::numbered_paths(&dir, "PONG", ["gif", "wav"]);
+        assert_eq!(gif, dir.join(format!("PONG.{number}.gif")));
+        assert_eq!(wav, dir.join(format!("PONG.{number}.wav")));
+        std::fs::write(gif, [number]).unwrap();
+        std::fs::write(wav, [number]).unwrap();
+    }
+
+    /* another rom's recordings start from 0 */
+    let [gif, _] = Utils::numbered_paths(&dir, "TETRIS", ["gif", "wav"]);
+    assert_eq!(gif, dir.join("TETRIS.0.gif"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn stale_rom_list_is_fetched_again() {
+    let dir = scratch_dir("rom-list");
+    let path = dir.join("roms.json");
+
+    assert_eq!(Utils::read_rom_list(&path), None);
+
+    /* the list cached before it was versioned is a bare array */
+    std::fs::write(&path, "[]").unwrap();
+    assert_eq!(Utils::read_rom_list(&path), None);
+
+    let old = format!(r#"{{"version": {}, "roms": []}}"#, ROM_LIST_VERSION - 1);
+    std::fs::write(&path, old).unwrap();
+    assert_eq!(Utils::read_rom_list(&path), None);
+
+    Utils::write_rom_list(&path, Vec::new()).unwrap();
+    assert_eq!(Utils::read_rom_list(&path), Some(Vec::new()));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
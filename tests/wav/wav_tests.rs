@@ -0,0 +1,48 @@
+use super::*;
+
+fn u16_at(wav: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes(wav[at..at + 2].try_into().unwrap())
+}
+
+fn u32_at(wav: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(wav[at..at + 4].try_into().unwrap())
+}
+
+#[test]
+fn header() {
+    let mut recorder = WavRecorder::new(44100);
+    recorder.push(&[0.25, -0.25, 0.0]);
+
+    let wav = recorder.finish();
+
+    assert_eq!(&wav[..4], b"RIFF");
+    assert_eq!(u32_at(&wav, 4) as usize, wav.len() - 8);
+    assert_eq!(&wav[8..16], b"WAVEfmt ");
+    assert_eq!(u16_at(&wav, 20), FORMAT_IEEE_FLOAT);
+    assert_eq!(u16_at(&wav, 22), 1);
+    assert_eq!(u32_at(&wav, 24), 44100);
+    assert_eq!(u32_at(&wav, 28), 44100 * 4);
+    assert_eq!(u16_at(&wav, 34), 32);
+    assert_eq!(&wav[38..42], b"fact");
+    assert_eq!(u32_at(&wav, 46), 3);
+    assert_eq!(&wav[50..54], b"data");
+    assert_eq!(u32_at(&wav, 54), 12);
+}
+
+#[test]
+fn samples_exact() {
+    let samples = [0.25, -0.25, 0.1, -1.0];
+    let mut recorder = WavRecorder::new(8000);
+    recorder.push(&samples[..2]);
+    recorder.push(&samples[2..]);
+
+    assert_eq!(recorder.samples(), samples);
+
+    let wav = recorder.finish();
+    let written: Vec<f32> = wav[58..]
+        .chunks(4)
+        .map(|sample| f32::from_le_bytes(sample.try_into().unwrap()))
+        .collect();
+
+    assert_eq!(written, samples);
+}